@@ -0,0 +1,189 @@
+//! Derive macros for [Bluest](https://docs.rs/bluest).
+//!
+//! These macros are re-exported by `bluest` when its `derive` feature is enabled and should be used from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit};
+
+/// Derives an async `connect` constructor which discovers a GATT service and its characteristics.
+///
+/// The struct must have a `#[service(uuid = ...)]` attribute naming the service to discover. Each field must have
+/// one of the following attributes:
+///
+/// - `#[characteristic(uuid = ...)]`: the field is initialized from the characteristic with that UUID. Its type may
+///   be any type implementing `From<Characteristic>`, such as `Characteristic` or `TypedCharacteristic<T>`.
+///   - Adding `notify` requires the characteristic to support notifications or indications.
+///   - Adding `optional` allows the characteristic to be missing. The field must then be an `Option`.
+/// - `#[service]`: the field is initialized with the discovered `Service`.
+///
+/// UUIDs may be given either as string literals, which can be 16-bit, 32-bit or full 128-bit UUIDs, or as
+/// expressions of type `Uuid`.
+///
+/// ```rust,ignore
+/// use bluest::codec::TypedCharacteristic;
+/// use bluest::{Characteristic, GattProfile};
+///
+/// #[derive(GattProfile)]
+/// #[service(uuid = "00001523-1212-efde-1523-785feabcd123")]
+/// struct Blinky {
+///     #[characteristic(uuid = "00001524-1212-efde-1523-785feabcd123", notify)]
+///     button: TypedCharacteristic<u8>,
+///     #[characteristic(uuid = "00001525-1212-efde-1523-785feabcd123")]
+///     led: Characteristic,
+/// }
+///
+/// let blinky = Blinky::connect(&device).await?;
+/// ```
+///
+/// `connect` returns an error with a kind of `NotFound` if the service or a required characteristic does not exist,
+/// and `NotSupported` if a `notify` characteristic supports neither notifications nor indications.
+#[proc_macro_derive(GattProfile, attributes(service, characteristic))]
+pub fn derive_gatt_profile(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match gatt_profile(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn gatt_profile(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &data.fields,
+                    "GattProfile requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "GattProfile can only be derived for structs",
+            ))
+        }
+    };
+
+    let service_attr = find_attr(&input.attrs, "service")
+        .ok_or_else(|| syn::Error::new(input.ident.span(), "missing #[service(uuid = ...)] attribute"))?;
+    let service_uuid = parse_service_attr(service_attr)?;
+
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let init = if let Some(attr) = find_attr(&field.attrs, "characteristic") {
+            let CharacteristicAttr { uuid, notify, optional } = parse_characteristic_attr(attr)?;
+            if optional {
+                quote! {
+                    ::bluest::profile::optional_characteristic(&service, #uuid, #notify)
+                        .await?
+                        .map(::core::convert::Into::into)
+                }
+            } else {
+                quote! {
+                    ::core::convert::Into::into(::bluest::profile::characteristic(&service, #uuid, #notify).await?)
+                }
+            }
+        } else if let Some(attr) = find_attr(&field.attrs, "service") {
+            attr.meta
+                .require_path_only()
+                .map_err(|_| syn::Error::new_spanned(attr, "#[service] on a field takes no arguments"))?;
+            quote! { ::core::clone::Clone::clone(&service) }
+        } else {
+            return Err(syn::Error::new(
+                ident.span(),
+                "field must have a #[characteristic(uuid = ...)] or #[service] attribute",
+            ));
+        };
+        inits.push(quote! { #ident: #init });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Discovers this profile's service and characteristics on `device`.
+            pub async fn connect(device: &::bluest::Device) -> ::bluest::Result<Self> {
+                let service = ::bluest::profile::service(device, #service_uuid).await?;
+                ::core::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+fn find_attr<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attrs.iter().find(|attr| attr.path().is_ident(name))
+}
+
+fn parse_service_attr(attr: &Attribute) -> syn::Result<TokenStream2> {
+    let mut uuid = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("uuid") {
+            uuid = Some(parse_uuid(meta.value()?.parse()?)?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported service attribute"))
+        }
+    })?;
+    uuid.ok_or_else(|| syn::Error::new_spanned(attr, "missing service uuid"))
+}
+
+struct CharacteristicAttr {
+    uuid: TokenStream2,
+    notify: bool,
+    optional: bool,
+}
+
+fn parse_characteristic_attr(attr: &Attribute) -> syn::Result<CharacteristicAttr> {
+    let mut uuid = None;
+    let mut notify = false;
+    let mut optional = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("uuid") {
+            uuid = Some(parse_uuid(meta.value()?.parse()?)?);
+        } else if meta.path.is_ident("notify") {
+            notify = true;
+        } else if meta.path.is_ident("optional") {
+            optional = true;
+        } else {
+            return Err(meta.error("unsupported characteristic attribute"));
+        }
+        Ok(())
+    })?;
+    let uuid = uuid.ok_or_else(|| syn::Error::new_spanned(attr, "missing characteristic uuid"))?;
+    Ok(CharacteristicAttr { uuid, notify, optional })
+}
+
+/// Converts a UUID string literal to a constant expression, or passes any other expression through unchanged.
+fn parse_uuid(expr: Expr) -> syn::Result<TokenStream2> {
+    let lit = match &expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(lit), .. }) => lit,
+        _ => return Ok(quote! { #expr }),
+    };
+
+    let digits = lit.value().replace('-', "");
+    let invalid = || syn::Error::new(lit.span(), "invalid UUID");
+    if !digits.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    match digits.len() {
+        4 => {
+            let uuid = u16::from_str_radix(&digits, 16).map_err(|_| invalid())?;
+            Ok(quote! { ::bluest::btuuid::bluetooth_uuid_from_u16(#uuid) })
+        }
+        8 => {
+            let uuid = u32::from_str_radix(&digits, 16).map_err(|_| invalid())?;
+            Ok(quote! { ::bluest::btuuid::bluetooth_uuid_from_u32(#uuid) })
+        }
+        32 => {
+            let uuid = u128::from_str_radix(&digits, 16).map_err(|_| invalid())?;
+            Ok(quote! { ::bluest::Uuid::from_u128(#uuid) })
+        }
+        _ => Err(invalid()),
+    }
+}
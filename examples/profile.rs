@@ -0,0 +1,88 @@
+use std::error::Error;
+use std::time::Duration;
+
+use bluest::codec::TypedCharacteristic;
+use bluest::{Adapter, GattProfile, Uuid};
+use futures_util::future::{select, Either};
+use futures_util::{pin_mut, StreamExt};
+use tracing::metadata::LevelFilter;
+use tracing::{error, info};
+
+const NORDIC_LED_AND_BUTTON_SERVICE: Uuid = Uuid::from_u128(0x00001523_1212_efde_1523_785feabcd123);
+
+#[derive(GattProfile)]
+#[service(uuid = NORDIC_LED_AND_BUTTON_SERVICE)]
+struct Blinky {
+    #[characteristic(uuid = "00001524-1212-efde-1523-785feabcd123", notify)]
+    button: TypedCharacteristic<u8>,
+    #[characteristic(uuid = "00001525-1212-efde-1523-785feabcd123")]
+    led: TypedCharacteristic<bool>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, EnvFilter};
+
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy(),
+        )
+        .init();
+
+    let adapter = Adapter::default().await.ok_or("Bluetooth adapter not found")?;
+    adapter.wait_available().await?;
+
+    info!("looking for device");
+    let device = adapter
+        .discover_devices(&[NORDIC_LED_AND_BUTTON_SERVICE])
+        .await?
+        .next()
+        .await
+        .ok_or("Failed to discover device")??;
+    info!(
+        "found device: {} ({:?})",
+        device.name().as_deref().unwrap_or("(unknown)"),
+        device.id()
+    );
+
+    adapter.connect_device(&device).await?;
+    info!("connected!");
+
+    let blinky = Blinky::connect(&device).await?;
+    info!("discovered LED and button service");
+
+    let button_fut = async {
+        let updates = blinky.button.notify_typed().await?;
+        pin_mut!(updates);
+        info!("waiting for button changes");
+        while let Some(val) = updates.next().await {
+            info!("Button state changed: {}", val?);
+        }
+        Ok(())
+    };
+    pin_mut!(button_fut);
+
+    let blink_fut = async {
+        let mut on = false;
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            on = !on;
+            blinky.led.write_typed(&on).await?;
+            info!("LED {}", if on { "on" } else { "off" });
+        }
+    };
+    pin_mut!(blink_fut);
+
+    type R = Result<(), Box<dyn Error>>;
+    let res: Either<(R, _), (R, _)> = select(blink_fut, button_fut).await;
+    match res {
+        Either::Left((res, _)) => error!("Blink task exited: {:?}", res),
+        Either::Right((res, _)) => error!("Button task exited: {:?}", res),
+    }
+
+    Ok(())
+}
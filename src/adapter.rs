@@ -1,7 +1,10 @@
 #![allow(clippy::let_unit_value)]
 
+use std::sync::Arc;
+
 use futures_util::Stream;
 
+use crate::pairing::{BondData, DefaultAgentGuard, OobData, PairingAgent};
 use crate::{sys, AdapterEvent, AdvertisingDevice, ConnectionEvent, Device, DeviceId, Result, Uuid};
 
 /// The system's Bluetooth adapter interface.
@@ -41,6 +44,18 @@ impl Adapter {
         self.0.connected_devices().await
     }
 
+    /// Finds all devices bonded with this adapter
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, all paired devices are returned.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub async fn bonded_devices(&self) -> Result<Vec<Device>> {
+        self.0.bonded_devices().await
+    }
+
     /// Finds all connected devices providing any service in `services`
     ///
     /// # Panics
@@ -143,4 +158,66 @@ impl Adapter {
     ) -> Result<impl Stream<Item = ConnectionEvent> + 'a> {
         self.0.device_connection_events(device).await
     }
+
+    /// Registers `agent` as the system's default pairing agent.
+    ///
+    /// The default agent handles pairing requests which are not started by [`Device::pair_with_agent`], such as
+    /// security requests from a device when a protected characteristic is accessed, or a device pairing again after
+    /// losing its keys. The agent stays registered until the returned guard is dropped.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, the agent replaces any other default agent registered with BlueZ (such as a desktop environment's
+    /// pairing dialog) for all adapters. BlueZ may require additional permissions to register a default agent.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS, where pairing
+    /// requests are always handled by the OS.
+    #[inline]
+    pub async fn register_default_agent(&self, agent: Arc<dyn PairingAgent>) -> Result<DefaultAgentGuard> {
+        self.0.register_default_agent(agent).await.map(DefaultAgentGuard::new)
+    }
+
+    /// Creates new LE Secure Connections out-of-band pairing data for this adapter.
+    ///
+    /// The data should be passed to the remote device over the OOB channel. It is used to authenticate the next
+    /// pairing which uses OOB data, and calling this method again replaces it.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this uses the kernel's Bluetooth management API, which requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn local_oob_data(&self) -> Result<OobData> {
+        self.0.local_oob_data().await
+    }
+
+    /// Exports the stored bond with the device identified by `id`, including its keys.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this reads BlueZ's storage for the device, which usually requires root permissions.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn export_bond(&self, id: &DeviceId) -> Result<BondData> {
+        self.0.export_bond(id).await
+    }
+
+    /// Stores a bond previously exported with [`export_bond`][Self::export_bond], replacing any existing bond with
+    /// the same device.
+    ///
+    /// The bond is only valid if the device knows this adapter by the same identity as the exporting adapter, such
+    /// as when the adapter's address or identity resolving key has also been transferred.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this writes BlueZ's storage for the device, which usually requires root permissions. BlueZ only
+    /// reads its storage at startup, so the bond is not used until the Bluetooth service is restarted.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn import_bond(&self, bond: &BondData) -> Result<()> {
+        self.0.import_bond(bond).await
+    }
 }
@@ -0,0 +1,193 @@
+//! AES-128 and AES-CCM as used by the Bluetooth LE security functions.
+//!
+//! The block cipher is provided by the `aes` crate, which uses hardware instructions where available and is otherwise
+//! constant time.
+
+use aes::cipher::{BlockEncrypt, KeyInit};
+
+/// An expanded AES-128 key
+#[derive(Clone)]
+pub(crate) struct Aes128(aes::Aes128);
+
+impl Aes128 {
+    /// Expands `key`. Keys and blocks use the byte order of FIPS-197, which is also the most significant octet first
+    /// order used by the Bluetooth Core Specification.
+    pub(crate) fn new(key: &[u8; 16]) -> Self {
+        Aes128(aes::Aes128::new(key.into()))
+    }
+
+    /// Encrypts a single block
+    pub(crate) fn encrypt(&self, block: &[u8; 16]) -> [u8; 16] {
+        let mut block = (*block).into();
+        self.0.encrypt_block(&mut block);
+        block.into()
+    }
+}
+
+/// AES-CCM with a 13 octet nonce, as used by the Bluetooth LE security functions
+///
+/// See RFC 3610.
+impl Aes128 {
+    /// Encrypts `data` and returns the encrypted data followed by a `mic_len` octet MIC.
+    ///
+    /// `data` must be shorter than 65536 octets.
+    pub(crate) fn ccm_encrypt(&self, nonce: &[u8; 13], aad: &[u8], data: &[u8], mic_len: usize) -> Vec<u8> {
+        let mac = self.ccm_mac(nonce, aad, data, mic_len);
+        let mut out = data.to_vec();
+        let s0 = self.ccm_ctr(nonce, &mut out);
+        out.extend(mac.iter().zip(s0).take(mic_len).map(|(x, s)| x ^ s));
+        out
+    }
+
+    /// Decrypts `data`, which is the encrypted data followed by a `mic_len` octet MIC, returning `None` if the MIC is
+    /// not valid.
+    pub(crate) fn ccm_decrypt(&self, nonce: &[u8; 13], aad: &[u8], data: &[u8], mic_len: usize) -> Option<Vec<u8>> {
+        let split = data.len().checked_sub(mic_len)?;
+        let (data, mic) = data.split_at(split);
+        let mut out = data.to_vec();
+        let s0 = self.ccm_ctr(nonce, &mut out);
+        let mac = self.ccm_mac(nonce, aad, &out, mic_len);
+        let diff = mic
+            .iter()
+            .zip(mac.iter().zip(s0))
+            .fold(0, |acc, (m, (x, s))| acc | (m ^ x ^ s));
+        (diff == 0).then_some(out)
+    }
+
+    /// Encrypts `data` in counter mode, returning the first block of the key stream which is used to encrypt the MIC
+    fn ccm_ctr(&self, nonce: &[u8; 13], data: &mut [u8]) -> [u8; 16] {
+        let mut counter = [0; 16];
+        counter[0] = 0x01; // L - 1, with a 2 octet length field
+        counter[1..14].copy_from_slice(nonce);
+        let s0 = self.encrypt(&counter);
+        for (i, chunk) in data.chunks_mut(16).enumerate() {
+            counter[14..].copy_from_slice(&(i as u16 + 1).to_be_bytes());
+            for (x, s) in chunk.iter_mut().zip(self.encrypt(&counter)) {
+                *x ^= s;
+            }
+        }
+        s0
+    }
+
+    /// Computes the CBC-MAC of `aad` and `data`
+    fn ccm_mac(&self, nonce: &[u8; 13], aad: &[u8], data: &[u8], mic_len: usize) -> [u8; 16] {
+        assert!((4..=16).contains(&mic_len) && mic_len & 1 == 0 && aad.len() < 0xff00);
+        let len = u16::try_from(data.len()).expect("data too long for AES-CCM with a 13 octet nonce");
+
+        let mut b0 = [0; 16];
+        b0[0] = if aad.is_empty() { 0 } else { 0x40 } | ((mic_len as u8 - 2) / 2) << 3 | 0x01;
+        b0[1..14].copy_from_slice(nonce);
+        b0[14..].copy_from_slice(&len.to_be_bytes());
+        let mut mac = self.encrypt(&b0);
+
+        let mut header = Vec::with_capacity(2 + aad.len());
+        if !aad.is_empty() {
+            header.extend_from_slice(&(aad.len() as u16).to_be_bytes());
+            header.extend_from_slice(aad);
+        }
+        // Each of the header and data is zero padded to a multiple of the block size
+        for block in header.chunks(16).chain(data.chunks(16)) {
+            for (x, b) in mac.iter_mut().zip(block) {
+                *x ^= b;
+            }
+            mac = self.encrypt(&mac);
+        }
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let bytes: Vec<u8> = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn fips_197_known_answer() {
+        // FIPS-197, Appendix C.1
+        let cipher = Aes128::new(&hex("000102030405060708090a0b0c0d0e0f"));
+        assert_eq!(
+            cipher.encrypt(&hex("00112233445566778899aabbccddeeff")),
+            hex("69c4e0d86a7b0430d8cdb78070b4c55a")
+        );
+
+        // FIPS-197, Appendix B
+        let cipher = Aes128::new(&hex("2b7e151628aed2a6abf7158809cf4f3c"));
+        assert_eq!(
+            cipher.encrypt(&hex("3243f6a8885a308d313198a2e0370734")),
+            hex("3925841d02dc09fbdc118597196a0b32")
+        );
+    }
+
+    /// Checks an RFC 3610 packet vector, where the first 8 octets of the packet are authenticated but not encrypted
+    fn check_rfc_3610(nonce: &str, packet: &str, expected: &str) {
+        let cipher = Aes128::new(&hex("c0c1c2c3c4c5c6c7c8c9cacbcccdcecf"));
+        let nonce = hex(nonce);
+        let packet = hex_vec(packet);
+        let expected = hex_vec(expected);
+        let (header, data) = packet.split_at(8);
+
+        let encrypted = cipher.ccm_encrypt(&nonce, header, data, 8);
+        assert_eq!(encrypted, expected[8..]);
+        assert_eq!(cipher.ccm_decrypt(&nonce, header, &encrypted, 8).as_deref(), Some(data));
+
+        // Any change to the header, data or MIC is detected
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+        assert_eq!(cipher.ccm_decrypt(&nonce, header, &tampered, 8), None);
+        tampered = encrypted.clone();
+        tampered[0] ^= 0x80;
+        assert_eq!(cipher.ccm_decrypt(&nonce, header, &tampered, 8), None);
+        assert_eq!(cipher.ccm_decrypt(&nonce, &header[1..], &encrypted, 8), None);
+    }
+
+    fn hex_vec(s: &str) -> Vec<u8> {
+        let s = s.replace(' ', "");
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn rfc_3610_packet_vector_1() {
+        check_rfc_3610(
+            "00000003020100a0a1a2a3a4a5",
+            "00010203 04050607 08090a0b 0c0d0e0f 10111213 14151617 18191a1b 1c1d1e",
+            "00010203 04050607 588c979a 61c663d2 f066d0c2 c0f98980 6d5f6b61 dac38417 e8d12cfd f926e0",
+        );
+    }
+
+    #[test]
+    fn rfc_3610_packet_vector_2() {
+        check_rfc_3610(
+            "00000004030201a0a1a2a3a4a5",
+            "00010203 04050607 08090a0b 0c0d0e0f 10111213 14151617 18191a1b 1c1d1e1f",
+            "00010203 04050607 72c91a36 e135f8cf 291ca894 085c87e3 cc15c439 c9e43a3b a091d56e 10400916",
+        );
+    }
+
+    #[test]
+    fn rfc_3610_packet_vector_3() {
+        check_rfc_3610(
+            "00000005040302a0a1a2a3a4a5",
+            "00010203 04050607 08090a0b 0c0d0e0f 10111213 14151617 18191a1b 1c1d1e1f 20",
+            "00010203 04050607 51b1e5f4 4a197d1d a46b0f8e 2d282ae8 71e838bb 64da8596 574adaa7 6fbd9fb0 c5",
+        );
+    }
+
+    #[test]
+    fn ccm_short_input() {
+        let cipher = Aes128::new(&[0; 16]);
+        assert_eq!(cipher.ccm_decrypt(&[0; 13], &[], &[0; 3], 4), None);
+        let encrypted = cipher.ccm_encrypt(&[0; 13], &[], &[], 4);
+        assert_eq!(encrypted.len(), 4);
+        assert_eq!(cipher.ccm_decrypt(&[0; 13], &[], &encrypted, 4), Some(Vec::new()));
+    }
+}
@@ -2,11 +2,29 @@ pub mod adapter;
 pub mod characteristic;
 pub mod descriptor;
 pub mod device;
+pub mod reliable_write;
 pub mod service;
 
+mod agent;
 mod error;
+mod hci;
+mod mgmt;
 
 /// A platform-specific device identifier.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceId(bluer::Address);
+
+impl std::fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+impl DeviceId {
+    /// A device identifier for use in tests, distinct for each value of `n`
+    pub(crate) fn test(n: u8) -> Self {
+        DeviceId(bluer::Address::new([0, 0, 0, 0, 0, n]))
+    }
+}
@@ -1,10 +1,20 @@
 use std::future::ready;
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 use bluer::{AdapterProperty, Session};
 use futures_util::{Stream, StreamExt};
 use once_cell::sync::OnceCell;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use super::{agent, mgmt};
 
 use crate::error::ErrorKind;
+use crate::pairing::{BondData, OobData, PairingAgent};
 use crate::{AdapterEvent, AdvertisingDevice, ConnectionEvent, Device, DeviceId, Error, Result, Uuid};
 
 static SESSION: OnceCell<Session> = OnceCell::new();
@@ -113,6 +123,24 @@ impl AdapterImpl {
         Ok(devices)
     }
 
+    /// Finds all devices bonded with this adapter
+    pub async fn bonded_devices(&self) -> Result<Vec<Device>> {
+        let mut devices = Vec::new();
+        for device in self
+            .inner
+            .device_addresses()
+            .await?
+            .into_iter()
+            .filter_map(|addr| Device::new(&self.inner, addr).ok())
+        {
+            if device.is_paired().await? {
+                devices.push(device);
+            }
+        }
+
+        Ok(devices)
+    }
+
     /// Finds all connected devices providing any service in `services`
     ///
     /// # Panics
@@ -238,4 +266,118 @@ impl AdapterImpl {
             })
         }))
     }
+
+    /// Registers `agent` as the system's default pairing agent.
+    pub async fn register_default_agent(&self, agent: Arc<dyn PairingAgent>) -> Result<DefaultAgentImpl> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let session = session().await?;
+        let handle = session
+            .register_agent(bluer::agent::Agent {
+                request_default: true,
+                ..agent::agent(agent.io_capability(), sender)
+            })
+            .await?;
+
+        let task = tokio::spawn(async move {
+            let rejected = AtomicBool::new(false);
+            agent::serve(&*agent, receiver, &rejected).await
+        });
+        Ok(DefaultAgentImpl { _handle: handle, task })
+    }
+
+    /// Exports the stored bond with the device identified by `id`, including its keys.
+    pub async fn export_bond(&self, id: &DeviceId) -> Result<BondData> {
+        let path = bond_path(&self.inner, id.0).await?;
+        let data = blocking(move || std::fs::read(&path).map_err(|err| storage_error(err, &path))).await?;
+        Ok(BondData {
+            device: id.clone(),
+            data,
+        })
+    }
+
+    /// Stores a bond previously exported with [`export_bond`][Self::export_bond].
+    pub async fn import_bond(&self, bond: &BondData) -> Result<()> {
+        let path = bond_path(&self.inner, bond.device.0).await?;
+        let data = bond.data.clone();
+        blocking(move || {
+            if let Some(dir) = path.parent() {
+                std::fs::DirBuilder::new()
+                    .recursive(true)
+                    .mode(0o700)
+                    .create(dir)
+                    .map_err(|err| storage_error(err, dir))?;
+            }
+
+            // The file contains the bond's keys, so it must only be readable by BlueZ
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(0o600)
+                .open(&path)
+                .and_then(|mut file| file.write_all(&data))
+                .map_err(|err| storage_error(err, &path))
+        })
+        .await
+    }
+
+    /// Creates new LE Secure Connections out-of-band pairing data for this adapter.
+    pub async fn local_oob_data(&self) -> Result<OobData> {
+        // Request the data for both LE address types
+        let index = mgmt::controller_index(self.inner.name())?;
+        let reply = mgmt::command(index, mgmt::READ_LOCAL_OOB_EXT_DATA, vec![0x06]).await?;
+
+        // The reply is the address types followed by the length of the EIR data and the data itself
+        let eir = reply.get(3..).unwrap_or_default();
+        OobData::decode(eir).map_err(|err| {
+            Error::new(
+                ErrorKind::NotSupported,
+                Some(Box::new(err)),
+                "LE Secure Connections is not enabled".to_string(),
+            )
+        })
+    }
+}
+
+/// A default pairing agent registered with BlueZ
+pub struct DefaultAgentImpl {
+    _handle: bluer::agent::AgentHandle,
+    task: JoinHandle<()>,
+}
+
+impl std::fmt::Debug for DefaultAgentImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DefaultAgentImpl").finish_non_exhaustive()
+    }
+}
+
+impl Drop for DefaultAgentImpl {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The path of BlueZ's storage file for `device`
+pub(super) async fn bond_path(adapter: &bluer::Adapter, device: bluer::Address) -> Result<PathBuf> {
+    let adapter = adapter.address().await?;
+    Ok(Path::new("/var/lib/bluetooth")
+        .join(adapter.to_string())
+        .join(device.to_string())
+        .join("info"))
+}
+
+/// Runs the blocking file system operation `f` on Tokio's blocking thread pool
+pub(super) async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|err| Error::new(ErrorKind::Internal, Some(Box::new(err)), String::new()))?
+}
+
+pub(super) fn storage_error(err: std::io::Error, path: &Path) -> Error {
+    let kind = match err.kind() {
+        std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+        std::io::ErrorKind::PermissionDenied => ErrorKind::NotAuthorized,
+        _ => ErrorKind::Other,
+    };
+    Error::new(kind, Some(Box::new(err)), format!("accessing {}", path.display()))
 }
@@ -0,0 +1,482 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use bluer::agent::{
+    Agent, AuthorizeService, AuthorizeServiceFn, DisplayPasskey, DisplayPasskeyFn, DisplayPinCode, DisplayPinCodeFn,
+    ReqError, ReqResult, RequestAuthorization, RequestAuthorizationFn, RequestConfirmation, RequestConfirmationFn,
+    RequestPasskey, RequestPasskeyFn, RequestPinCode, RequestPinCodeFn,
+};
+#[cfg(any(test, feature = "testing"))]
+use futures_util::future::{join, ready};
+use futures_util::future::{select, BoxFuture, Either};
+use futures_util::pin_mut;
+use tokio::sync::{mpsc, oneshot};
+
+use super::adapter::session;
+use super::device::DeviceImpl;
+#[cfg(any(test, feature = "testing"))]
+use crate::pairing::testing::AssociationModel;
+use crate::pairing::{IoCapability, PairingAgent, PairingRejected, Passkey};
+use crate::{Device, Uuid};
+
+/// A request from BlueZ, forwarded to the task which owns the [`PairingAgent`]
+pub(super) enum AgentRequest<D = Device> {
+    RequestPasskey(D, oneshot::Sender<Result<Passkey, PairingRejected>>),
+    DisplayPasskey(D, Passkey),
+    ConfirmPasskey(D, Passkey, oneshot::Sender<Result<(), PairingRejected>>),
+    RequestAuthorization(D, oneshot::Sender<Result<(), PairingRejected>>),
+    AuthorizeService(D, Uuid, oneshot::Sender<Result<(), PairingRejected>>),
+    RequestPinCode(D, oneshot::Sender<Result<String, PairingRejected>>),
+    DisplayPinCode(D, String),
+}
+
+async fn req_device(adapter: &str, addr: bluer::Address) -> ReqResult<Device> {
+    let session = session().await.map_err(|_| ReqError::Rejected)?;
+    let adapter = session.adapter(adapter).map_err(|_| ReqError::Rejected)?;
+    let device = adapter.device(addr).map_err(|_| ReqError::Rejected)?;
+    Ok(Device(DeviceImpl { inner: device }))
+}
+
+/// Sends a request and waits for the agent's response
+async fn request<D, T>(
+    sender: &mpsc::UnboundedSender<AgentRequest<D>>,
+    request: impl FnOnce(oneshot::Sender<Result<T, PairingRejected>>) -> AgentRequest<D>,
+) -> ReqResult<T> {
+    let (tx, rx) = oneshot::channel();
+    sender.send(request(tx)).map_err(|_| ReqError::Rejected)?;
+    match rx.await {
+        Ok(Ok(res)) => Ok(res),
+        _ => Err(ReqError::Rejected),
+    }
+}
+
+/// Handles a request for the device with the given adapter name and address
+type Handler<A, T> = Box<dyn Fn(String, bluer::Address, A) -> BoxFuture<'static, ReqResult<T>> + Send + Sync>;
+
+/// The requests handled by an agent with a given IO capability
+///
+/// BlueZ is told the agent's IO capability based on which requests it handles, so only the handlers which belong to
+/// the IO capability are set.
+pub(super) struct Handlers {
+    request_passkey: Option<Handler<(), u32>>,
+    display_passkey: Option<Handler<u32, ()>>,
+    request_confirmation: Option<Handler<u32, ()>>,
+    request_authorization: Option<Handler<(), ()>>,
+    authorize_service: Option<Handler<Uuid, ()>>,
+    request_pin_code: Option<Handler<(), String>>,
+    display_pin_code: Option<Handler<String, ()>>,
+}
+
+impl Handlers {
+    /// Creates handlers which forward their requests to `sender`, using `device` to look up the device each request
+    /// is for.
+    pub(super) fn new<D, F, Fut>(
+        io_capability: IoCapability,
+        sender: mpsc::UnboundedSender<AgentRequest<D>>,
+        device: F,
+    ) -> Self
+    where
+        D: Send + 'static,
+        F: Fn(String, bluer::Address) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ReqResult<D>> + Send + 'static,
+    {
+        let device = Arc::new(device);
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let request_passkey: Handler<(), u32> = Box::new(move |adapter, addr, ()| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                let passkey = request(&tx, |reply| AgentRequest::RequestPasskey(device, reply)).await?;
+                Ok(passkey.into())
+            })
+        });
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let display_passkey: Handler<u32, ()> = Box::new(move |adapter, addr, passkey| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                let passkey = passkey.try_into().map_err(|_| ReqError::Rejected)?;
+                tx.send(AgentRequest::DisplayPasskey(device, passkey))
+                    .map_err(|_| ReqError::Rejected)
+            })
+        });
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let request_confirmation: Handler<u32, ()> = Box::new(move |adapter, addr, passkey| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                let passkey = passkey.try_into().map_err(|_| ReqError::Rejected)?;
+                request(&tx, |reply| AgentRequest::ConfirmPasskey(device, passkey, reply)).await
+            })
+        });
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let request_authorization: Handler<(), ()> = Box::new(move |adapter, addr, ()| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                request(&tx, |reply| AgentRequest::RequestAuthorization(device, reply)).await
+            })
+        });
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let authorize_service: Handler<Uuid, ()> = Box::new(move |adapter, addr, service| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                request(&tx, |reply| AgentRequest::AuthorizeService(device, service, reply)).await
+            })
+        });
+
+        let (tx, lookup) = (sender.clone(), device.clone());
+        let request_pin_code: Handler<(), String> = Box::new(move |adapter, addr, ()| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                let pin_code = request(&tx, |reply| AgentRequest::RequestPinCode(device, reply)).await?;
+                if (1..=16).contains(&pin_code.len()) {
+                    Ok(pin_code)
+                } else {
+                    Err(ReqError::Rejected)
+                }
+            })
+        });
+
+        let (tx, lookup) = (sender, device);
+        let display_pin_code: Handler<String, ()> = Box::new(move |adapter, addr, pin_code| {
+            let (tx, lookup) = (tx.clone(), lookup.clone());
+            Box::pin(async move {
+                let device = lookup(adapter, addr).await?;
+                tx.send(AgentRequest::DisplayPinCode(device, pin_code))
+                    .map_err(|_| ReqError::Rejected)
+            })
+        });
+
+        let none = Handlers {
+            request_passkey: None,
+            display_passkey: None,
+            request_confirmation: None,
+            request_authorization: None,
+            authorize_service: None,
+            request_pin_code: None,
+            display_pin_code: None,
+        };
+        match io_capability {
+            IoCapability::DisplayOnly => Handlers {
+                display_passkey: Some(display_passkey),
+                display_pin_code: Some(display_pin_code),
+                ..none
+            },
+            IoCapability::DisplayYesNo => Handlers {
+                display_passkey: Some(display_passkey),
+                display_pin_code: Some(display_pin_code),
+                request_confirmation: Some(request_confirmation),
+                request_authorization: Some(request_authorization),
+                authorize_service: Some(authorize_service),
+                ..none
+            },
+            IoCapability::KeyboardOnly => Handlers {
+                request_passkey: Some(request_passkey),
+                request_pin_code: Some(request_pin_code),
+                ..none
+            },
+            IoCapability::NoInputNoOutput => none,
+            IoCapability::KeyboardDisplay => Handlers {
+                request_passkey: Some(request_passkey),
+                request_pin_code: Some(request_pin_code),
+                display_passkey: Some(display_passkey),
+                display_pin_code: Some(display_pin_code),
+                request_confirmation: Some(request_confirmation),
+                request_authorization: Some(request_authorization),
+                authorize_service: Some(authorize_service),
+            },
+        }
+    }
+
+    /// Converts the handlers into a BlueZ agent.
+    pub(super) fn into_agent(self) -> Agent {
+        Agent {
+            request_passkey: self
+                .request_passkey
+                .map(|f| -> RequestPasskeyFn { Box::new(move |req: RequestPasskey| f(req.adapter, req.device, ())) }),
+            display_passkey: self.display_passkey.map(|f| -> DisplayPasskeyFn {
+                Box::new(move |req: DisplayPasskey| f(req.adapter, req.device, req.passkey))
+            }),
+            request_confirmation: self.request_confirmation.map(|f| -> RequestConfirmationFn {
+                Box::new(move |req: RequestConfirmation| f(req.adapter, req.device, req.passkey))
+            }),
+            request_authorization: self.request_authorization.map(|f| -> RequestAuthorizationFn {
+                Box::new(move |req: RequestAuthorization| f(req.adapter, req.device, ()))
+            }),
+            authorize_service: self.authorize_service.map(|f| -> AuthorizeServiceFn {
+                Box::new(move |req: AuthorizeService| f(req.adapter, req.device, req.service))
+            }),
+            request_pin_code: self
+                .request_pin_code
+                .map(|f| -> RequestPinCodeFn { Box::new(move |req: RequestPinCode| f(req.adapter, req.device, ())) }),
+            display_pin_code: self.display_pin_code.map(|f| -> DisplayPinCodeFn {
+                Box::new(move |req: DisplayPinCode| f(req.adapter, req.device, req.pincode))
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if BlueZ makes a request to the agent when pairing with the association model `model`.
+    #[cfg(any(test, feature = "testing"))]
+    pub(super) fn handles(&self, model: AssociationModel) -> bool {
+        match model {
+            AssociationModel::JustWorks => self.request_authorization.is_some(),
+            AssociationModel::NumericComparison => self.request_confirmation.is_some(),
+            AssociationModel::PasskeyDisplay => self.display_passkey.is_some(),
+            AssociationModel::PasskeyInput => self.request_passkey.is_some(),
+        }
+    }
+
+    /// Makes the request BlueZ makes when pairing with the association model `model`, returning the passkey which
+    /// was entered, if any.
+    ///
+    /// BlueZ does not make requests which the agent has no handler for, so no request is made if the handler is not
+    /// set.
+    #[cfg(any(test, feature = "testing"))]
+    pub(super) async fn simulate(&self, model: AssociationModel, passkey: Passkey) -> ReqResult<Option<Passkey>> {
+        let (adapter, addr) = (String::new(), bluer::Address::any());
+        let handler = match model {
+            AssociationModel::JustWorks => self.request_authorization.as_ref().map(|f| f(adapter, addr, ())),
+            AssociationModel::NumericComparison => self
+                .request_confirmation
+                .as_ref()
+                .map(|f| f(adapter, addr, passkey.into())),
+            AssociationModel::PasskeyDisplay => self.display_passkey.as_ref().map(|f| f(adapter, addr, passkey.into())),
+            AssociationModel::PasskeyInput => {
+                return match &self.request_passkey {
+                    Some(f) => {
+                        let entered = f(adapter, addr, ()).await?;
+                        Passkey::try_from(entered).map(Some).map_err(|_| ReqError::Rejected)
+                    }
+                    None => Ok(None),
+                };
+            }
+        };
+        match handler {
+            Some(fut) => fut.await.map(|()| None),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Creates a BlueZ agent which forwards its requests to `sender`.
+pub(super) fn agent(io_capability: IoCapability, sender: mpsc::UnboundedSender<AgentRequest>) -> Agent {
+    Handlers::new(io_capability, sender, |adapter, addr| async move {
+        req_device(&adapter, addr).await
+    })
+    .into_agent()
+}
+
+/// Runs `fut` and sends its result to `reply`, unless BlueZ cancels the request first. Sets `rejected` if the agent
+/// rejects the request.
+async fn respond<T>(
+    mut reply: oneshot::Sender<Result<T, PairingRejected>>,
+    fut: impl Future<Output = Result<T, PairingRejected>>,
+    rejected: &AtomicBool,
+) {
+    let res = {
+        let closed = reply.closed();
+        pin_mut!(fut, closed);
+        match select(fut, closed).await {
+            Either::Left((res, _)) => Some(res),
+            Either::Right(_) => None,
+        }
+    };
+
+    if let Some(res) = res {
+        if res.is_err() {
+            rejected.store(true, Ordering::Relaxed);
+        }
+        let _ = reply.send(res);
+    }
+}
+
+/// Handles requests with `agent` until every sender has been dropped.
+///
+/// `rejected` is set if the agent rejects any request.
+pub(super) async fn serve<T: PairingAgent + ?Sized>(
+    agent: &T,
+    mut receiver: mpsc::UnboundedReceiver<AgentRequest>,
+    rejected: &AtomicBool,
+) {
+    while let Some(req) = receiver.recv().await {
+        match req {
+            AgentRequest::RequestPasskey(device, reply) => {
+                respond(reply, agent.request_passkey(&device), rejected).await
+            }
+            AgentRequest::DisplayPasskey(device, passkey) => agent.display_passkey(&device, passkey),
+            AgentRequest::ConfirmPasskey(device, passkey, reply) => {
+                respond(reply, agent.confirm_passkey(&device, passkey), rejected).await
+            }
+            AgentRequest::RequestAuthorization(device, reply) => {
+                respond(reply, agent.request_authorization(&device), rejected).await
+            }
+            AgentRequest::AuthorizeService(device, service, reply) => {
+                respond(reply, agent.authorize_service(&device, service), rejected).await
+            }
+            AgentRequest::RequestPinCode(device, reply) => {
+                respond(reply, agent.request_pin_code(&device), rejected).await
+            }
+            AgentRequest::DisplayPinCode(device, pin_code) => agent.display_pin_code(&device, &pin_code),
+        }
+    }
+}
+
+/// Sends the request BlueZ makes for the association model `model` to `agent` through the handlers for the agent's
+/// IO capability, returning the passkey the agent entered, if any.
+#[cfg(any(test, feature = "testing"))]
+pub(super) async fn simulate<T: PairingAgent + ?Sized>(
+    agent: &T,
+    device: Device,
+    model: AssociationModel,
+    passkey: Passkey,
+) -> Result<Option<Passkey>, PairingRejected> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let handlers = Handlers::new(agent.io_capability(), sender, move |_, _| ready(Ok(device.clone())));
+    let request = async move {
+        let res = handlers.simulate(model, passkey).await;
+        // Dropping the handlers closes the channel, which stops `serve`
+        drop(handlers);
+        res
+    };
+    let (res, ()) = join(request, serve(agent, receiver, &AtomicBool::new(false))).await;
+    res.map_err(|_| PairingRejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pairing::testing::{AgentCall, IO_CAPABILITIES};
+
+    /// Answers requests like a [`ScriptedPairingAgent`][crate::pairing::testing::ScriptedPairingAgent] which enters
+    /// `passkey`, returning the agent methods [`serve`] calls for them, in order
+    async fn answer(mut receiver: mpsc::UnboundedReceiver<AgentRequest<()>>, passkey: Passkey) -> Vec<AgentCall> {
+        let mut calls = Vec::new();
+        while let Some(req) = receiver.recv().await {
+            match req {
+                AgentRequest::RequestPasskey((), reply) => {
+                    calls.push(AgentCall::RequestPasskey);
+                    let _ = reply.send(Ok(passkey));
+                }
+                AgentRequest::DisplayPasskey((), passkey) => calls.push(AgentCall::DisplayPasskey(passkey)),
+                AgentRequest::ConfirmPasskey((), passkey, reply) => {
+                    calls.push(AgentCall::ConfirmPasskey(passkey));
+                    let _ = reply.send(Ok(()));
+                }
+                // `PairingAgent::request_authorization` calls `confirm` unless it is overridden
+                AgentRequest::RequestAuthorization((), reply) => {
+                    calls.push(AgentCall::Confirm);
+                    let _ = reply.send(Ok(()));
+                }
+                AgentRequest::AuthorizeService((), service, reply) => {
+                    calls.push(AgentCall::AuthorizeService(service));
+                    let _ = reply.send(Ok(()));
+                }
+                AgentRequest::RequestPinCode((), reply) => {
+                    calls.push(AgentCall::RequestPinCode);
+                    let _ = reply.send(Ok("0000".to_owned()));
+                }
+                AgentRequest::DisplayPinCode((), pin_code) => calls.push(AgentCall::DisplayPinCode(pin_code)),
+            }
+        }
+        calls
+    }
+
+    async fn simulate(
+        io_capability: IoCapability,
+        model: AssociationModel,
+        passkey: Passkey,
+    ) -> (ReqResult<Option<Passkey>>, Vec<AgentCall>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let handlers = Handlers::new(io_capability, sender, |_, _| ready(Ok(())));
+        let request = async move {
+            let res = handlers.simulate(model, passkey).await;
+            drop(handlers);
+            res
+        };
+        join(request, answer(receiver, passkey)).await
+    }
+
+    #[test]
+    fn handlers_by_io_capability() {
+        let handled = |io_capability| {
+            let (sender, _receiver) = mpsc::unbounded_channel::<AgentRequest<()>>();
+            let handlers = Handlers::new(io_capability, sender, |_, _| ready(Ok(())));
+            [
+                handlers.request_passkey.is_some(),
+                handlers.display_passkey.is_some(),
+                handlers.request_confirmation.is_some(),
+                handlers.request_authorization.is_some(),
+            ]
+        };
+
+        assert_eq!(handled(IoCapability::DisplayOnly), [false, true, false, false]);
+        assert_eq!(handled(IoCapability::DisplayYesNo), [false, true, true, true]);
+        assert_eq!(handled(IoCapability::KeyboardOnly), [true, false, false, false]);
+        assert_eq!(handled(IoCapability::NoInputNoOutput), [false, false, false, false]);
+        assert_eq!(handled(IoCapability::KeyboardDisplay), [true, true, true, true]);
+    }
+
+    #[tokio::test]
+    async fn association_model_callbacks() {
+        let passkey = Passkey::new(123456);
+        for local in IO_CAPABILITIES {
+            for remote in IO_CAPABILITIES {
+                for secure_connections in [false, true] {
+                    for local_is_initiator in [true, false] {
+                        let model = AssociationModel::new(local, remote, secure_connections, local_is_initiator);
+                        let case = format!("{local:?} with {remote:?}, {secure_connections}, {local_is_initiator}");
+                        let (res, calls) = simulate(local, model, passkey).await;
+                        assert_eq!(calls, model.expected_calls(local, passkey), "{case}");
+
+                        let entered = (model == AssociationModel::PasskeyInput).then_some(passkey);
+                        assert!(matches!(res, Ok(x) if x == entered), "{case}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn no_input_no_output_is_never_asked() {
+        for model in [
+            AssociationModel::JustWorks,
+            AssociationModel::NumericComparison,
+            AssociationModel::PasskeyDisplay,
+            AssociationModel::PasskeyInput,
+        ] {
+            let (res, calls) = simulate(IoCapability::NoInputNoOutput, model, Passkey::new(0)).await;
+            assert!(matches!(res, Ok(None)));
+            assert!(calls.is_empty(), "{model:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_requests() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let handlers = Handlers::new(IoCapability::KeyboardDisplay, sender, |_, _| ready(Ok(())));
+        let request = handlers.simulate(AssociationModel::NumericComparison, Passkey::new(0));
+        let reject = async {
+            match receiver.recv().await {
+                Some(AgentRequest::ConfirmPasskey((), _, reply)) => reply.send(Err(PairingRejected)).unwrap(),
+                _ => panic!("expected a passkey confirmation request"),
+            }
+        };
+        let (res, ()) = join(request, reject).await;
+        assert!(matches!(res, Err(ReqError::Rejected)));
+
+        // A request is rejected if the agent has gone away
+        drop(receiver);
+        let res = handlers.simulate(AssociationModel::PasskeyInput, Passkey::new(0)).await;
+        assert!(matches!(res, Err(ReqError::Rejected)));
+    }
+}
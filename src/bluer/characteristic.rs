@@ -1,13 +1,19 @@
-use bluer::gatt::remote::CharacteristicWriteRequest;
+use bluer::gatt::remote::{CharacteristicReadRequest, CharacteristicWriteRequest};
 use bluer::gatt::WriteOp;
 use futures_util::{Stream, StreamExt};
 
-use crate::{Characteristic, CharacteristicProperties, Descriptor, Result, Uuid};
+use super::adapter::session;
+use crate::btuuid::descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION;
+use crate::characteristic::MAX_ATTRIBUTE_VALUE_LEN;
+use crate::codec::GattDecode;
+use crate::descriptors::Cccd;
+use crate::error::ErrorKind;
+use crate::{Characteristic, CharacteristicProperties, Descriptor, Error, Result, Service, Uuid, WriteKind};
 
 /// A Bluetooth GATT characteristic
 #[derive(Debug, Clone)]
 pub struct CharacteristicImpl {
-    inner: bluer::gatt::remote::Characteristic,
+    pub(super) inner: bluer::gatt::remote::Characteristic,
 }
 
 impl PartialEq for CharacteristicImpl {
@@ -60,6 +66,25 @@ impl CharacteristicImpl {
         self.inner.uuid().await.map_err(Into::into)
     }
 
+    /// The attribute handle of this GATT characteristic's value
+    ///
+    /// bluer does not expose attribute handles. Its `id` is parsed from the D-Bus object path, which BlueZ names
+    /// `charXXXX` after the handle of the characteristic declaration, so this relies on BlueZ's object path naming. The
+    /// characteristic value declaration always immediately follows the characteristic declaration (Bluetooth Core
+    /// Specification, Vol 3, Part G, §3.3).
+    pub fn handle(&self) -> Result<u16> {
+        Ok(self.inner.id().saturating_add(1))
+    }
+
+    /// The [`Service`] containing this characteristic
+    pub async fn service(&self) -> Result<Service> {
+        let session = session().await?;
+        let device = session
+            .adapter(self.inner.adapter_name())?
+            .device(self.inner.device_address())?;
+        Ok(Service::new(device.service(self.inner.service_id()).await?))
+    }
+
     /// The properties of this this GATT characteristic.
     ///
     /// Characteristic properties indicate which operations (e.g. read, write, notify, etc) may be performed on this
@@ -80,12 +105,39 @@ impl CharacteristicImpl {
         self.inner.read().await.map_err(Into::into)
     }
 
+    /// Read the value of this characteristic from the device, starting at `offset`, using the read blob procedure.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        self.inner
+            .read_ext(&CharacteristicReadRequest {
+                offset,
+                ..Default::default()
+            })
+            .await
+            .map_err(Into::into)
+    }
+
     /// Write the value of this descriptor on the device to `value` and request the device return a response indicating
     /// a successful write.
     pub async fn write(&self, value: &[u8]) -> Result<()> {
         self.inner.write(value).await.map_err(Into::into)
     }
 
+    /// Write `value` to the value of this characteristic on the device starting at `offset`, using the long write
+    /// procedure.
+    pub async fn write_at(&self, offset: u16, value: &[u8]) -> Result<()> {
+        self.inner
+            .write_ext(
+                value,
+                &CharacteristicWriteRequest {
+                    offset,
+                    op_type: WriteOp::Request,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(Into::into)
+    }
+
     /// Write the value of this descriptor on the device to `value` without requesting a response.
     pub async fn write_without_response(&self, value: &[u8]) {
         let _ = self
@@ -100,6 +152,24 @@ impl CharacteristicImpl {
             .await;
     }
 
+    /// The maximum number of bytes which may be written to this characteristic in a single call.
+    pub async fn max_write_len(&self, kind: WriteKind) -> Result<usize> {
+        match kind {
+            // BlueZ automatically uses the long write procedure for writes which do not fit in a single packet
+            WriteKind::WithResponse => Ok(MAX_ATTRIBUTE_VALUE_LEN),
+            WriteKind::WithoutResponse => Ok(usize::from(self.mtu().await?) - 3),
+        }
+    }
+
+    /// The ATT MTU of the connection, as reported by BlueZ when acquiring a write socket for this characteristic.
+    ///
+    /// Fails unless this characteristic supports write without response.
+    pub(super) async fn mtu(&self) -> Result<u16> {
+        let writer = self.inner.write_io().await?;
+        // bluer reduces the MTU reported by BlueZ by 5 bytes to work around a BlueZ bug, so add them back
+        Ok(u16::try_from(writer.mtu() + 5).unwrap_or(u16::MAX))
+    }
+
     /// Enables notification of value changes for this GATT characteristic.
     ///
     /// Returns a stream of values for the characteristic sent from the device.
@@ -112,6 +182,28 @@ impl CharacteristicImpl {
         self.inner.notifying().await.map_err(Into::into)
     }
 
+    /// Read the client characteristic configuration descriptor of this characteristic from the device.
+    pub async fn cccd(&self) -> Result<Cccd> {
+        for descriptor in self.inner.descriptors().await? {
+            if descriptor.uuid().await? == CLIENT_CHARACTERISTIC_CONFIGURATION {
+                return Ok(Cccd::decode(&descriptor.read().await?)?);
+            }
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            None,
+            "client characteristic configuration descriptor not found".to_string(),
+        ))
+    }
+
+    /// Write the client characteristic configuration descriptor of this characteristic on the device.
+    ///
+    /// BlueZ only configures the CCCD through `StartNotify`/`AcquireNotify`, which [`notify`][Self::notify] uses, and
+    /// resets it when the client stops, so a CCCD value cannot be set independently of a notification stream.
+    pub async fn set_cccd(&self, _cccd: Cccd) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
     /// Discover the descriptors associated with this characteristic.
     pub async fn discover_descriptors(&self) -> Result<Vec<Descriptor>> {
         self.descriptors().await
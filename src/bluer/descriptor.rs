@@ -1,4 +1,7 @@
-use crate::{Descriptor, Result, Uuid};
+use bluer::gatt::remote::DescriptorReadRequest;
+
+use super::adapter::session;
+use crate::{Characteristic, Descriptor, Result, Uuid};
 
 /// A Bluetooth GATT descriptor
 #[derive(Debug, Clone)]
@@ -48,6 +51,26 @@ impl DescriptorImpl {
         self.inner.uuid().await.map_err(Into::into)
     }
 
+    /// The attribute handle of this GATT descriptor
+    ///
+    /// bluer does not expose attribute handles. Its `id` is parsed from the D-Bus object path, which BlueZ names
+    /// `descXXXX` after the handle of the descriptor, so this relies on BlueZ's object path naming.
+    pub fn handle(&self) -> Result<u16> {
+        Ok(self.inner.id())
+    }
+
+    /// The [`Characteristic`] containing this descriptor
+    pub async fn characteristic(&self) -> Result<Characteristic> {
+        let session = session().await?;
+        let device = session
+            .adapter(self.inner.adapter_name())?
+            .device(self.inner.device_address())?;
+        let service = device.service(self.inner.service_id()).await?;
+        Ok(Characteristic::new(
+            service.characteristic(self.inner.characteristic_id()).await?,
+        ))
+    }
+
     /// The cached value of this descriptor
     ///
     /// If the value has not yet been read, this method may either return an error or perform a read of the value.
@@ -60,6 +83,17 @@ impl DescriptorImpl {
         self.inner.read().await.map_err(Into::into)
     }
 
+    /// Read the value of this descriptor from the device, starting at `offset`, using the read blob procedure.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        self.inner
+            .read_ext(&DescriptorReadRequest {
+                offset,
+                ..Default::default()
+            })
+            .await
+            .map_err(Into::into)
+    }
+
     /// Write the value of this descriptor on the device to `value`
     pub async fn write(&self, value: &[u8]) -> Result<()> {
         self.inner.write(value).await.map_err(Into::into)
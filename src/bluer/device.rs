@@ -1,11 +1,26 @@
-use futures_util::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures_util::future::{ready, select, Either};
+use futures_util::{Stream, StreamExt};
 use tokio::pin;
+use tokio::sync::mpsc;
 
-use super::adapter::session;
+use super::adapter::{blocking, bond_path, session, storage_error};
+use super::error::pairing_result;
+use super::reliable_write::ReliableWriteImpl;
 use super::DeviceId;
-use crate::error::ErrorKind;
-use crate::pairing::PairingAgent;
-use crate::{btuuid, AdvertisementData, Device, Error, ManufacturerData, Result, Service, Uuid};
+use super::{agent, hci, mgmt};
+use crate::error::{ErrorKind, PairingError};
+use crate::pairing::{
+    auto_pair_agent, pairing_events, report_pairing, send_pairing_event, BondInfo, OobData, PairingAgent, PairingEvent,
+    SecurityLevel,
+};
+#[cfg(any(test, feature = "testing"))]
+use crate::pairing::{testing::AssociationModel, IoCapability, PairingRejected, Passkey};
+use crate::{
+    btuuid, AdvertisementData, Characteristic, ConnectionParameters, ConnectionPhy, ConnectionPriority, Device, Error,
+    HandleRange, ManufacturerData, ReliableWrite, Result, Service, ServicesChangedEvent, Uuid,
+};
 
 /// A Bluetooth LE device
 #[derive(Debug, Clone)]
@@ -48,6 +63,31 @@ impl DeviceImpl {
         DeviceId(self.inner.address())
     }
 
+    /// The address of this device if it is a random address, in most significant octet first order
+    pub(crate) async fn random_address(&self) -> Option<[u8; 6]> {
+        let address_type = self.inner.address_type().await.ok()?;
+        (address_type == bluer::AddressType::LeRandom).then_some(self.inner.address().0)
+    }
+
+    /// Returns `true` if BlueZ makes a request to an agent with the IO capability `io_capability` when pairing with
+    /// the association model `model`.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn handles_agent_request(io_capability: IoCapability, model: AssociationModel) -> bool {
+        let (sender, _receiver) = mpsc::unbounded_channel::<agent::AgentRequest<()>>();
+        agent::Handlers::new(io_capability, sender, |_, _| async { Ok(()) }).handles(model)
+    }
+
+    /// Delivers the agent request made for `model` to `agent`, returning the passkey the agent entered, if any
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) async fn simulate_agent_request<T: PairingAgent + ?Sized>(
+        &self,
+        agent: &T,
+        model: AssociationModel,
+        passkey: Passkey,
+    ) -> std::result::Result<Option<Passkey>, PairingRejected> {
+        agent::simulate(agent, Device(self.clone()), model, passkey).await
+    }
+
     /// The local name for this device, if available
     ///
     /// This can either be a name advertised or read from the device, or a name assigned to the device by the OS.
@@ -90,69 +130,84 @@ impl DeviceImpl {
             return Ok(());
         }
 
-        self.inner.pair().await.map_err(Into::into)
+        report_pairing(&self.id(), async { pairing_result(self.inner.pair().await) }).await
     }
 
     /// Attempt to pair this device using the system default pairing UI
-    pub async fn pair_with_agent<T: PairingAgent + 'static>(&self, agent: &T) -> Result<()> {
+    pub async fn pair_with_agent<T: PairingAgent + ?Sized>(&self, agent: &T) -> Result<()> {
         if self.is_paired().await? {
             return Ok(());
         }
 
-        let agent = {
-            // Safety: This `bluer::agent::Agent`, including the encapsulated closures and async blocks will be dropped
-            // when the `_handle` below is dropped. Therefore, the lifetime of the captures of `agent` will not
-            // out-live the lifetime of `agent`. Unfortunately, the compiler has no way to prove this, so we must cast
-            // `agent` to the static lifetime.
-            let agent: &'static T = unsafe { std::mem::transmute(agent) };
-
-            async fn req_device(adapter: &str, addr: bluer::Address) -> Result<Device, bluer::agent::ReqError> {
-                let session = session().await.map_err(|_| bluer::agent::ReqError::Rejected)?;
-                let adapter = session.adapter(adapter).map_err(|_| bluer::agent::ReqError::Rejected)?;
-                let device = adapter.device(addr).map_err(|_| bluer::agent::ReqError::Rejected)?;
-                Ok(Device(DeviceImpl { inner: device }))
-            }
-
-            bluer::agent::Agent {
-                request_passkey: Some(Box::new(move |req: bluer::agent::RequestPasskey| {
-                    Box::pin(async move {
-                        let device = req_device(&req.adapter, req.device).await?;
-                        match agent.request_passkey(&device).await {
-                            Ok(passkey) => Ok(passkey.into()),
-                            Err(_) => Err(bluer::agent::ReqError::Rejected),
-                        }
-                    })
-                })),
-                display_passkey: Some(Box::new(move |req: bluer::agent::DisplayPasskey| {
-                    Box::pin(async move {
-                        let device = req_device(&req.adapter, req.device).await?;
-                        if let Ok(passkey) = req.passkey.try_into() {
-                            agent.display_passkey(&device, passkey);
-                            Ok(())
-                        } else {
-                            Err(bluer::agent::ReqError::Rejected)
-                        }
-                    })
-                })),
-                request_confirmation: Some(Box::new(move |req: bluer::agent::RequestConfirmation| {
-                    Box::pin(async move {
-                        let device = req_device(&req.adapter, req.device).await?;
-                        if let Ok(passkey) = req.passkey.try_into() {
-                            agent
-                                .confirm_passkey(&device, passkey)
-                                .await
-                                .map_err(|_| bluer::agent::ReqError::Rejected)
-                        } else {
-                            Err(bluer::agent::ReqError::Rejected)
-                        }
-                    })
-                })),
-                ..Default::default()
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let session = session().await?;
+        let _handle = session
+            .register_agent(agent::agent(agent.io_capability(), sender))
+            .await?;
+
+        let rejected = AtomicBool::new(false);
+        let fut = async {
+            let pairing = async {
+                let pairing = self.inner.pair();
+                let serving = agent::serve(agent, receiver, &rejected);
+                pin!(pairing, serving);
+
+                match select(pairing, serving).await {
+                    // BlueZ reports a request rejected by the agent as an authentication failure
+                    Either::Left((Err(err), _)) if rejected.load(Ordering::Relaxed) => Err(Error::new(
+                        ErrorKind::PairingFailed(PairingError::Canceled),
+                        Some(Box::new(err)),
+                        "rejected by the pairing agent".to_owned(),
+                    )),
+                    Either::Left((res, _)) => pairing_result(res),
+                    Either::Right(((), _)) => Err(Error::new(
+                        ErrorKind::Other,
+                        None,
+                        "Pairing agent terminated unexpectedly".to_owned(),
+                    )),
+                }
+            };
+            let canceled = agent.canceled();
+            pin!(pairing, canceled);
+
+            match select(pairing, canceled).await {
+                Either::Left((res, _)) => res,
+                // Dropping the pairing future tells BlueZ to cancel pairing
+                Either::Right(((), _)) => Err(Error::new(
+                    ErrorKind::PairingFailed(PairingError::Canceled),
+                    None,
+                    "canceled by the pairing agent".to_owned(),
+                )),
             }
         };
+        report_pairing(&self.id(), fut).await
+    }
 
-        let session = session().await?;
-        let _handle = session.register_agent(agent).await?;
+    /// Attempt to pair this device using LE Secure Connections out-of-band pairing data received from the device.
+    pub async fn pair_with_oob_data(&self, oob: &OobData) -> Result<()> {
+        if self.is_paired().await? {
+            return Ok(());
+        }
+
+        let address = self.inner.address();
+        let address_type = self.inner.address_type().await?;
+        if oob.address != address.0 || oob.random_address != (address_type == bluer::AddressType::LeRandom) {
+            return Err(Error::new(
+                ErrorKind::InvalidParameter,
+                None,
+                "OOB data is for a different device".to_string(),
+            ));
+        }
+
+        // The kernel uses the stored OOB data when pairing with the device
+        let mut params = Vec::with_capacity(71);
+        params.extend(address.0.iter().rev());
+        params.push(mgmt::address_type(address_type));
+        params.extend_from_slice(&[0; 32]); // P-192 values, which are not used by LE
+        params.extend_from_slice(&oob.confirm);
+        params.extend_from_slice(&oob.random);
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        mgmt::command(index, mgmt::ADD_REMOTE_OOB_DATA, params).await?;
 
         self.pair().await
     }
@@ -165,7 +220,114 @@ impl DeviceImpl {
 
         let session = session().await?;
         let adapter = session.adapter(self.inner.adapter_name())?;
-        adapter.remove_device(self.inner.address()).await.map_err(Into::into)
+        adapter.remove_device(self.inner.address()).await?;
+        send_pairing_event(&self.id(), PairingEvent::Unpaired);
+        Ok(())
+    }
+
+    /// Deletes the bond with this device without disconnecting it.
+    pub async fn remove_bond(&self) -> Result<()> {
+        let address = self.inner.address();
+        let mut params = Vec::with_capacity(8);
+        params.extend(address.0.iter().rev());
+        params.push(mgmt::address_type(self.inner.address_type().await?));
+        params.push(0); // Do not disconnect
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        mgmt::command(index, mgmt::UNPAIR_DEVICE, params).await?;
+        send_pairing_event(&self.id(), PairingEvent::Unpaired);
+        Ok(())
+    }
+
+    /// Information about the stored bond with this device.
+    pub async fn bond_info(&self) -> Result<BondInfo> {
+        let session = session().await?;
+        let adapter = session.adapter(self.inner.adapter_name())?;
+        let path = bond_path(&adapter, self.inner.address()).await?;
+        let info = blocking(move || std::fs::read_to_string(&path).map_err(|err| storage_error(err, &path))).await?;
+        parse_bond_info(&info).ok_or_else(|| Error::new(ErrorKind::NotFound, None, "device is not bonded".to_string()))
+    }
+
+    /// Monitors the pairing state of this device.
+    ///
+    /// Pairing performed by bluest is reported by [`report_pairing`], and changes to the paired state made by the OS or
+    /// other applications by the device's `Paired` property.
+    pub async fn pairing_events(&self) -> Result<impl Stream<Item = PairingEvent> + '_> {
+        let reported = pairing_events(self.id());
+        let changes = self.inner.events().await?.filter_map(|ev| {
+            ready(match ev {
+                bluer::DeviceEvent::PropertyChanged(bluer::DeviceProperty::Paired(true)) => Some(PairingEvent::Paired),
+                bluer::DeviceEvent::PropertyChanged(bluer::DeviceProperty::Paired(false)) => {
+                    Some(PairingEvent::Unpaired)
+                }
+                _ => None,
+            })
+        });
+
+        // Pairing performed by bluest is reported by both sources, so skip events which do not change the state
+        let mut paired = self.inner.is_paired().await?;
+        Ok(futures_util::stream::select(reported, changes).filter(move |ev| {
+            ready(match ev {
+                PairingEvent::Paired => !std::mem::replace(&mut paired, true),
+                PairingEvent::Unpaired => std::mem::replace(&mut paired, false),
+                _ => true,
+            })
+        }))
+    }
+
+    /// Whether this device is trusted
+    pub async fn is_trusted(&self) -> Result<bool> {
+        self.inner.is_trusted().await.map_err(Into::into)
+    }
+
+    /// Sets whether this device is trusted.
+    pub async fn set_trusted(&self, trusted: bool) -> Result<()> {
+        self.inner.set_trusted(trusted).await.map_err(Into::into)
+    }
+
+    /// Whether this device is blocked
+    pub async fn is_blocked(&self) -> Result<bool> {
+        self.inner.is_blocked().await.map_err(Into::into)
+    }
+
+    /// Sets whether this device is blocked.
+    pub async fn set_blocked(&self, blocked: bool) -> Result<()> {
+        self.inner.set_blocked(blocked).await.map_err(Into::into)
+    }
+
+    /// The security level of the connection to this device
+    pub async fn security_level(&self) -> Result<SecurityLevel> {
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        hci::connection_security(index, self.inner.address()).await
+    }
+
+    /// Pairs with this device if necessary to raise the security of the connection to at least `level`.
+    pub async fn require_security(&self, level: SecurityLevel) -> Result<()> {
+        if level == SecurityLevel::None {
+            return Ok(());
+        }
+
+        let current = self.security_level().await?;
+        if current >= level {
+            return Ok(());
+        } else if current > SecurityLevel::None {
+            // BlueZ only pairs with a paired device again once its bond has been removed
+            self.remove_bond().await?;
+        }
+
+        match auto_pair_agent(&self.id()) {
+            Some(agent) => self.pair_with_agent(&*agent).await?,
+            None => self.pair().await?,
+        }
+
+        if self.security_level().await? >= level {
+            Ok(())
+        } else {
+            Err(Error::new(
+                ErrorKind::NotAuthorized,
+                None,
+                "pairing did not reach the required security level".to_string(),
+            ))
+        }
     }
 
     /// Discover the primary services of this device.
@@ -187,22 +349,44 @@ impl DeviceImpl {
 
     /// Asynchronously blocks until a GATT services changed packet is received
     pub async fn services_changed(&self) -> Result<()> {
-        let services = self.services().await?;
-        for service in services {
-            if service.uuid_async().await? == btuuid::services::GENERIC_ATTRIBUTE {
+        self.services_changed_range().await.map(|_| ())
+    }
+
+    /// Asynchronously blocks until a GATT services changed packet is received and returns the affected handle range
+    pub async fn services_changed_range(&self) -> Result<HandleRange> {
+        let ranges = self.service_changed_ranges().await?;
+        pin!(ranges);
+        match ranges.next().await {
+            Some(res) => res,
+            None => Err(Error::new(
+                ErrorKind::Internal,
+                None,
+                "service changed notifications ended unexpectedly".to_string(),
+            )),
+        }
+    }
+
+    /// Monitors this device for GATT services changed packets.
+    pub async fn service_changes(&self) -> Result<impl Stream<Item = Result<ServicesChangedEvent>> + '_> {
+        let ranges = self.service_changed_ranges().await?;
+        Ok(ranges.map(|res| {
+            res.map(|range| ServicesChangedEvent {
+                range: Some(range),
+                invalidated_services: Vec::new(),
+            })
+        }))
+    }
+
+    /// Subscribes to the Service Changed characteristic of the device's Generic Attribute service.
+    async fn service_changed_ranges(&self) -> Result<impl Stream<Item = Result<HandleRange>>> {
+        for service in self.inner.services().await? {
+            if service.uuid().await? == btuuid::services::GENERIC_ATTRIBUTE {
                 for characteristic in service.characteristics().await? {
-                    if characteristic.uuid_async().await? == btuuid::characteristics::SERVICE_CHANGED {
+                    if characteristic.uuid().await? == btuuid::characteristics::SERVICE_CHANGED {
                         let notifications = characteristic.notify().await?;
-                        pin!(notifications);
-                        return match notifications.next().await {
-                            Some(Ok(_)) => Ok(()),
-                            Some(Err(err)) => Err(err),
-                            None => Err(Error::new(
-                                ErrorKind::Internal,
-                                None,
-                                "service changed notifications ended unexpectedly".to_string(),
-                            )),
-                        };
+                        return Ok(Box::pin(
+                            notifications.map(|value| HandleRange::from_service_changed(&value)),
+                        ));
                     }
                 }
             }
@@ -211,6 +395,31 @@ impl DeviceImpl {
         Err(ErrorKind::NotFound.into())
     }
 
+    /// Begin a [`ReliableWrite`] transaction on this device.
+    pub fn reliable_write(&self) -> Result<ReliableWrite> {
+        Ok(ReliableWrite(ReliableWriteImpl::new()))
+    }
+
+    /// The ATT MTU negotiated for the connection to this device.
+    ///
+    /// BlueZ only reports the MTU when acquiring a write socket, so this requires the device to expose at least one
+    /// characteristic which supports write without response.
+    pub async fn mtu(&self) -> Result<u16> {
+        for service in self.inner.services().await? {
+            for characteristic in service.characteristics().await? {
+                if characteristic.flags().await?.write_without_response {
+                    return Characteristic::new(characteristic).0.mtu().await;
+                }
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::NotSupported,
+            None,
+            "the device has no characteristics supporting write without response".to_string(),
+        ))
+    }
+
     /// Get the current signal strength from the device in dBm.
     ///
     /// # Platform specific
@@ -220,6 +429,108 @@ impl DeviceImpl {
         Err(ErrorKind::NotSupported.into())
     }
 
+    /// The LE connection parameters of the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported]. The controller only reports the parameters in the HCI events for connection
+    /// creation and updates, which BlueZ and the mgmt API do not forward, and has no command to read them.
+    pub async fn connection_parameters(&self) -> Result<ConnectionParameters> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Monitors the LE connection parameters of the connection to this device.
+    ///
+    /// Updates are read from the controller's events on a raw HCI socket. Failed updates leave the parameters
+    /// unchanged and are skipped. The stream ends when the device disconnects.
+    pub async fn connection_parameter_changes(&self) -> Result<impl Stream<Item = Result<ConnectionParameters>>> {
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        let events = hci::connection_events(index, self.inner.address()).await?;
+        Ok(events.filter_map(|event| {
+            ready(match event {
+                Ok(hci::ConnectionEvent::ParametersUpdated { status: 0, parameters }) => Some(Ok(parameters)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+        }))
+    }
+
+    /// The PHYs used by the connection to this device.
+    pub async fn connection_phy(&self) -> Result<ConnectionPhy> {
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        let reply = hci::connection_command(index, self.inner.address(), hci::LE_READ_PHY, Vec::new()).await?;
+        match reply[..] {
+            [_, _, tx, rx, ..] => Ok(ConnectionPhy {
+                tx: hci::phy(tx)?,
+                rx: hci::phy(rx)?,
+            }),
+            _ => Err(Error::new(
+                ErrorKind::Other,
+                None,
+                "invalid reply to LE Read PHY".to_string(),
+            )),
+        }
+    }
+
+    /// Monitors the PHYs used by the connection to this device.
+    ///
+    /// Updates are read from the controller's events on a raw HCI socket. Failed updates leave the PHYs unchanged
+    /// and are skipped. The stream ends when the device disconnects.
+    pub async fn connection_phy_changes(&self) -> Result<impl Stream<Item = Result<ConnectionPhy>>> {
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        let events = hci::connection_events(index, self.inner.address()).await?;
+        Ok(events.filter_map(|event| {
+            ready(match event {
+                Ok(hci::ConnectionEvent::PhyUpdated { status: 0, tx, rx }) => {
+                    Some(hci::phy(tx).and_then(|tx| Ok(ConnectionPhy { tx, rx: hci::phy(rx)? })))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+        }))
+    }
+
+    /// Asks the controller to use `phy` for the connection to this device.
+    pub async fn set_preferred_phy(&self, phy: ConnectionPhy) -> Result<()> {
+        let mask = |x| 1 << (hci::phy_value(x) - 1);
+        // All_PHYs, TX_PHYs, RX_PHYs and PHY_Options
+        let params = vec![0, mask(phy.tx), mask(phy.rx), 0, 0];
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        hci::connection_command(index, self.inner.address(), hci::LE_SET_PHY, params).await?;
+        Ok(())
+    }
+
+    /// Asks the OS to use connection parameters suited to `priority` for the connection to this device.
+    ///
+    /// The parameters are requested with the HCI LE Connection Update command on the existing connection, so they
+    /// last until the connection is updated again or closed and leave the parameters the kernel stores for this and
+    /// other devices unchanged. [`Balanced`][ConnectionPriority::Balanced] requests the parameters BlueZ stored for
+    /// the device, if any, and otherwise the kernel's defaults. Waits for the controller to apply the parameters.
+    pub async fn request_connection_priority(&self, priority: ConnectionPriority) -> Result<()> {
+        // Minimum and maximum interval in units of 1.25 ms, peripheral latency and supervision timeout in units of
+        // 10 ms
+        let parameters = match priority {
+            // The kernel's default parameters
+            ConnectionPriority::Balanced => self.stored_connection_parameters().await.unwrap_or([24, 40, 0, 42]),
+            ConnectionPriority::HighThroughput => [9, 12, 0, 500],
+            ConnectionPriority::LowPower => [80, 100, 2, 500],
+        };
+
+        let index = mgmt::controller_index(self.inner.adapter_name())?;
+        hci::update_connection(index, self.inner.address(), parameters).await
+    }
+
+    /// The connection parameters BlueZ stored for this device, which are only readable with sufficient privileges
+    async fn stored_connection_parameters(&self) -> Option<[u16; 4]> {
+        let session = session().await.ok()?;
+        let adapter = session.adapter(self.inner.adapter_name()).ok()?;
+        let path = bond_path(&adapter, self.inner.address()).await.ok()?;
+        let info = blocking(move || std::fs::read_to_string(&path).map_err(|err| storage_error(err, &path)))
+            .await
+            .ok()?;
+        parse_connection_parameters(&info)
+    }
+
     pub(super) async fn adv_data(&self) -> AdvertisementData {
         let device = &self.inner;
 
@@ -245,6 +556,12 @@ impl DeviceImpl {
             .unwrap_or_default()
             .map_or(Vec::new(), |x| x.into_iter().collect());
 
+        let encrypted_data = device
+            .advertising_data()
+            .await
+            .unwrap_or_default()
+            .and_then(|mut data| data.remove(&0x31));
+
         AdvertisementData {
             local_name,
             manufacturer_data,
@@ -252,6 +569,166 @@ impl DeviceImpl {
             services,
             tx_power_level,
             is_connectable,
+            encrypted_data,
+        }
+    }
+}
+
+/// Parses BlueZ's storage file for a device, returning `None` if it contains no bond keys
+fn parse_bond_info(info: &str) -> Option<BondInfo> {
+    let mut bond = BondInfo {
+        long_term_key: false,
+        identity_resolving_key: false,
+        authenticated: false,
+        key_size: None,
+    };
+
+    let mut section = "";
+    for line in info.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (section, key) {
+            ("LongTermKey" | "PeripheralLongTermKey" | "SlaveLongTermKey", "Key") => bond.long_term_key = true,
+            // BlueZ stores the mgmt key type, where 1 and 3 are authenticated legacy and Secure Connections keys
+            ("LongTermKey" | "PeripheralLongTermKey" | "SlaveLongTermKey", "Authenticated") => {
+                bond.authenticated |= matches!(value, "1" | "3")
+            }
+            ("LongTermKey" | "PeripheralLongTermKey" | "SlaveLongTermKey", "EncSize") => {
+                bond.key_size = value.parse().ok()
+            }
+            ("IdentityResolvingKey", "Key") => bond.identity_resolving_key = true,
+            _ => (),
+        }
+    }
+
+    (bond.long_term_key || bond.identity_resolving_key).then_some(bond)
+}
+
+/// Parses the connection parameters in the `info` file BlueZ stores for a device, in HCI units
+fn parse_connection_parameters(info: &str) -> Option<[u16; 4]> {
+    let mut parameters = [None; 4];
+
+    let mut section = "";
+    for line in info.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+            section = name;
+            continue;
         }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let index = match (section, key) {
+            ("ConnectionParameters", "MinInterval") => 0,
+            ("ConnectionParameters", "MaxInterval") => 1,
+            ("ConnectionParameters", "Latency") => 2,
+            ("ConnectionParameters", "Timeout") => 3,
+            _ => continue,
+        };
+        parameters[index] = value.parse().ok();
+    }
+
+    let [Some(min_interval), Some(max_interval), Some(latency), Some(timeout)] = parameters else {
+        return None;
+    };
+    Some([min_interval, max_interval, latency, timeout])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &str = "\
+[General]
+Name=Sensor
+AddressType=static
+SupportedTechnologies=LE;
+Trusted=true
+Blocked=false
+Services=00001800-0000-1000-8000-00805f9b34fb;0000180f-0000-1000-8000-00805f9b34fb;
+
+[IdentityResolvingKey]
+Key=9A6A9BE7B2A1C9E5F1DCD1B32A6D7A40
+
+[LocalSignatureKey]
+Key=0F1E2D3C4B5A69788796A5B4C3D2E1F0
+Counter=0
+Authenticated=false
+
+[LongTermKey]
+Key=3F0C8B2E4D1A6B7C8D9E0F1A2B3C4D5E
+Authenticated=3
+EncSize=16
+EDiv=0
+Rand=0
+
+[ConnectionParameters]
+MinInterval=6
+MaxInterval=6
+Latency=0
+Timeout=500
+";
+
+    #[test]
+    fn bond_info() {
+        assert_eq!(
+            parse_bond_info(INFO),
+            Some(BondInfo {
+                long_term_key: true,
+                identity_resolving_key: true,
+                authenticated: true,
+                key_size: Some(16),
+            })
+        );
+    }
+
+    #[test]
+    fn unauthenticated_bond_info() {
+        // An unauthenticated LE Secure Connections key, stored by older versions of BlueZ as a peripheral key
+        let info = INFO
+            .replace("[LongTermKey]", "[SlaveLongTermKey]")
+            .replace("Authenticated=3", "Authenticated=2")
+            .replace("EncSize=16", "EncSize=7");
+        let bond = parse_bond_info(&info).unwrap();
+        assert!(bond.long_term_key);
+        assert!(!bond.authenticated);
+        assert_eq!(bond.key_size, Some(7));
+
+        // `Authenticated` in other sections does not apply to the long term key
+        let info = INFO
+            .replace("Authenticated=false", "Authenticated=1")
+            .replace("Authenticated=3", "Authenticated=0");
+        assert!(!parse_bond_info(&info).unwrap().authenticated);
+    }
+
+    #[test]
+    fn connection_parameters() {
+        assert_eq!(parse_connection_parameters(INFO), Some([6, 6, 0, 500]));
+
+        // All four parameters are required
+        let partial = INFO.replace("Latency=0\n", "");
+        assert_eq!(parse_connection_parameters(&partial), None);
+        let invalid = INFO.replace("Timeout=500", "Timeout=-1");
+        assert_eq!(parse_connection_parameters(&invalid), None);
+        let general = INFO.split("\n\n").next().unwrap();
+        assert_eq!(parse_connection_parameters(general), None);
+    }
+
+    #[test]
+    fn not_bonded() {
+        let general = INFO.split("\n\n").next().unwrap();
+        assert_eq!(parse_bond_info(general), None);
+        assert_eq!(parse_bond_info(""), None);
+
+        let irk_only = format!("{general}\n\n[IdentityResolvingKey]\nKey=00\n");
+        let bond = parse_bond_info(&irk_only).unwrap();
+        assert!(!bond.long_term_key && bond.identity_resolving_key);
+        assert_eq!(bond.key_size, None);
     }
 }
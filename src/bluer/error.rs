@@ -1,4 +1,4 @@
-use crate::error::ErrorKind;
+use crate::error::{ErrorKind, PairingError};
 
 impl From<bluer::Error> for crate::Error {
     fn from(err: bluer::Error) -> Self {
@@ -21,6 +21,29 @@ fn kind_from_bluer(err: &bluer::Error) -> ErrorKind {
         bluer::ErrorKind::InvalidName(_) => ErrorKind::InvalidParameter,
         bluer::ErrorKind::ServicesUnresolved => ErrorKind::NotReady,
         bluer::ErrorKind::NotFound => ErrorKind::NotFound,
+        bluer::ErrorKind::AuthenticationCanceled => ErrorKind::PairingFailed(PairingError::Canceled),
+        bluer::ErrorKind::AuthenticationFailed => ErrorKind::PairingFailed(PairingError::AuthenticationFailed),
+        bluer::ErrorKind::AuthenticationRejected => ErrorKind::PairingFailed(PairingError::RejectedByDevice),
+        bluer::ErrorKind::AuthenticationTimeout => ErrorKind::PairingFailed(PairingError::Timeout),
         _ => ErrorKind::Other,
     }
 }
+
+/// Converts the result of `bluer::Device::pair`, where some errors have pairing specific meanings.
+///
+/// A device which became paired after the caller checked is reported as paired, as it is on Windows.
+pub(super) fn pairing_result(res: bluer::Result<()>) -> crate::Result<()> {
+    match res {
+        Err(err) if matches!(err.kind, bluer::ErrorKind::AlreadyExists) => Ok(()),
+        res => res.map_err(pairing_error),
+    }
+}
+
+fn pairing_error(err: bluer::Error) -> crate::Error {
+    let kind = match err.kind {
+        bluer::ErrorKind::InProgress => ErrorKind::PairingFailed(PairingError::InProgress),
+        bluer::ErrorKind::Failed => ErrorKind::PairingFailed(PairingError::Other),
+        _ => kind_from_bluer(&err),
+    };
+    crate::Error::new(kind, Some(Box::new(err)), String::new())
+}
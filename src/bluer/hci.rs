@@ -0,0 +1,515 @@
+//! A minimal client for raw HCI commands and connection information, used for the per-connection operations which
+//! neither BlueZ nor the mgmt API expose.
+//!
+//! Sending commands and receiving connection events on a raw HCI socket requires the `CAP_NET_RAW` capability.
+//! Reading connection information does not.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use futures_channel::mpsc;
+
+use super::adapter::blocking;
+use super::mgmt::{io_error, open};
+use crate::error::ErrorKind;
+use crate::pairing::SecurityLevel;
+use crate::{ConnectionParameters, Error, Phy, Result};
+
+pub(super) const LE_READ_PHY: u16 = 0x2030;
+pub(super) const LE_SET_PHY: u16 = 0x2032;
+const LE_CONNECTION_UPDATE: u16 = 0x2013;
+
+const HCI_CHANNEL_RAW: u16 = 0;
+const SOL_HCI: libc::c_int = 0;
+const HCI_FILTER: libc::c_int = 2;
+
+const HCI_COMMAND_PKT: u8 = 0x01;
+const HCI_EVENT_PKT: u8 = 0x04;
+const EVT_DISCONN_COMPLETE: u8 = 0x05;
+const EVT_CMD_COMPLETE: u8 = 0x0e;
+const EVT_CMD_STATUS: u8 = 0x0f;
+const EVT_LE_META: u8 = 0x3e;
+const LE_CONN_UPDATE_COMPLETE: u8 = 0x03;
+const LE_PHY_UPDATE_COMPLETE: u8 = 0x0c;
+
+/// The events received by sockets sending commands
+const COMMAND_EVENTS: [u32; 2] = [(1 << EVT_CMD_COMPLETE) | (1 << EVT_CMD_STATUS), 0];
+/// The events received by sockets monitoring connections
+const CONNECTION_EVENTS: [u32; 2] = [
+    (1 << EVT_DISCONN_COMPLETE) | (1 << EVT_CMD_STATUS),
+    1 << (EVT_LE_META - 32),
+];
+
+/// How long to wait for the controller to apply new connection parameters, which it does at an instant chosen several
+/// connection events in the future
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+const LE_LINK: u8 = 0x80;
+/// `_IOR('H', 213, int)`
+const HCIGETCONNINFO: u32 = 0x800448d5;
+
+const HCI_LM_AUTH: u32 = 0x0002;
+const HCI_LM_ENCRYPT: u32 = 0x0004;
+const HCI_LM_SECURE: u32 = 0x0020;
+const HCI_LM_FIPS: u32 = 0x0040;
+
+#[repr(C)]
+struct HciFilter {
+    type_mask: u32,
+    event_mask: [u32; 2],
+    opcode: u16,
+}
+
+/// `struct hci_conn_info_req` followed by the `struct hci_conn_info` the kernel fills in
+#[repr(C)]
+struct ConnInfoRequest {
+    bdaddr: [u8; 6],
+    link_type: u8,
+    _pad: u8,
+    handle: u16,
+    _bdaddr: [u8; 6],
+    _link_type: u8,
+    _out: u8,
+    _state: u16,
+    link_mode: u32,
+}
+
+impl ConnInfoRequest {
+    fn new(bdaddr: [u8; 6]) -> Self {
+        ConnInfoRequest {
+            bdaddr,
+            link_type: LE_LINK,
+            _pad: 0,
+            handle: 0,
+            _bdaddr: [0; 6],
+            _link_type: 0,
+            _out: 0,
+            _state: 0,
+            link_mode: 0,
+        }
+    }
+}
+
+fn status_error(opcode: u16, status: u8) -> Error {
+    let kind = match status {
+        0x01 | 0x11 | 0x1a => ErrorKind::NotSupported,
+        0x02 => ErrorKind::NotConnected,
+        0x12 => ErrorKind::InvalidParameter,
+        _ => ErrorKind::Other,
+    };
+    Error::new(
+        kind,
+        None,
+        format!("HCI command {opcode:#06x} failed with status {status:#04x}"),
+    )
+}
+
+/// An event for a connection, as parsed by [`parse_event`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ConnectionEvent {
+    /// LE Connection Update Complete, with the status and the parameters in effect
+    ParametersUpdated {
+        status: u8,
+        parameters: ConnectionParameters,
+    },
+    /// LE PHY Update Complete, with the status and the HCI encodings of the PHYs in effect
+    PhyUpdated { status: u8, tx: u8, rx: u8 },
+    /// Disconnection Complete
+    Disconnected,
+}
+
+/// Parses the event packet `buf`, returning `None` if it is not a [`ConnectionEvent`] for the connection `handle`
+fn parse_event(buf: &[u8], handle: u16) -> Option<ConnectionEvent> {
+    // The upper bits of the handle fields are reserved
+    let is_handle = |lo: u8, hi: u8| u16::from_le_bytes([lo, hi]) & 0x0fff == handle;
+    match *buf {
+        [HCI_EVENT_PKT, EVT_LE_META, _, LE_CONN_UPDATE_COMPLETE, status, h0, h1, i0, i1, l0, l1, t0, t1, ..]
+            if is_handle(h0, h1) =>
+        {
+            Some(ConnectionEvent::ParametersUpdated {
+                status,
+                parameters: ConnectionParameters {
+                    interval: Duration::from_micros(u64::from(u16::from_le_bytes([i0, i1])) * 1250),
+                    peripheral_latency: u16::from_le_bytes([l0, l1]),
+                    supervision_timeout: Duration::from_millis(u64::from(u16::from_le_bytes([t0, t1])) * 10),
+                },
+            })
+        }
+        [HCI_EVENT_PKT, EVT_LE_META, _, LE_PHY_UPDATE_COMPLETE, status, h0, h1, tx, rx, ..] if is_handle(h0, h1) => {
+            Some(ConnectionEvent::PhyUpdated { status, tx, rx })
+        }
+        [HCI_EVENT_PKT, EVT_DISCONN_COMPLETE, _, 0, h0, h1, ..] if is_handle(h0, h1) => {
+            Some(ConnectionEvent::Disconnected)
+        }
+        _ => None,
+    }
+}
+
+/// The security level of a connection with the link mode `link_mode`, as reported by `HCIGETCONNINFO`
+pub(super) fn security_level(link_mode: u32) -> SecurityLevel {
+    if link_mode & HCI_LM_ENCRYPT == 0 {
+        SecurityLevel::None
+    } else if link_mode & (HCI_LM_SECURE | HCI_LM_FIPS) != 0 {
+        SecurityLevel::SecureConnections
+    } else if link_mode & HCI_LM_AUTH != 0 {
+        SecurityLevel::Authenticated
+    } else {
+        SecurityLevel::Encrypted
+    }
+}
+
+/// The HCI encoding of `phy`
+pub(super) fn phy_value(phy: Phy) -> u8 {
+    match phy {
+        Phy::Le1M => 1,
+        Phy::Le2M => 2,
+        Phy::LeCoded => 3,
+    }
+}
+
+/// The [`Phy`] with the HCI encoding `value`
+pub(super) fn phy(value: u8) -> Result<Phy> {
+    match value {
+        1 => Ok(Phy::Le1M),
+        2 => Ok(Phy::Le2M),
+        3 => Ok(Phy::LeCoded),
+        _ => Err(Error::new(ErrorKind::Other, None, format!("unknown PHY {value}"))),
+    }
+}
+
+/// Opens a raw HCI socket receiving the events in `event_mask`
+fn open_raw(index: u16, event_mask: [u32; 2]) -> Result<File> {
+    let socket = open(index, HCI_CHANNEL_RAW)?;
+
+    // Raw sockets receive nothing until a filter is set
+    let filter = HciFilter {
+        type_mask: 1 << HCI_EVENT_PKT,
+        event_mask,
+        opcode: 0,
+    };
+    // Safety: `filter` is a valid `hci_filter` and the length passed matches its size.
+    let res = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            SOL_HCI,
+            HCI_FILTER,
+            &filter as *const HciFilter as *const libc::c_void,
+            std::mem::size_of::<HciFilter>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+
+    Ok(socket)
+}
+
+/// Returns the handle and link mode of the LE connection to `address`
+fn connection_handle(socket: &File, address: bluer::Address) -> Result<(u16, u32)> {
+    let mut bdaddr = address.0;
+    bdaddr.reverse();
+    let mut req = ConnInfoRequest::new(bdaddr);
+    // Safety: `req` has room for the `hci_conn_info` the kernel writes after the request.
+    let res = unsafe {
+        libc::ioctl(
+            socket.as_raw_fd(),
+            HCIGETCONNINFO as _,
+            &mut req as *mut ConnInfoRequest,
+        )
+    };
+    if res < 0 {
+        let err = std::io::Error::last_os_error();
+        return Err(match err.raw_os_error() {
+            Some(libc::ENOENT) => ErrorKind::NotConnected.into(),
+            _ => io_error(err),
+        });
+    }
+    Ok((req.handle, req.link_mode))
+}
+
+/// Sends the command `opcode` for the connection `handle`, with the handle prepended to `params`
+fn send_command(socket: &mut File, opcode: u16, handle: u16, params: &[u8]) -> Result<()> {
+    let mut packet = Vec::with_capacity(6 + params.len());
+    packet.push(HCI_COMMAND_PKT);
+    packet.extend_from_slice(&opcode.to_le_bytes());
+    packet.push(u8::try_from(params.len() + 2).map_err(|_| ErrorKind::InvalidParameter)?);
+    packet.extend_from_slice(&handle.to_le_bytes());
+    packet.extend_from_slice(params);
+    socket.write_all(&packet).map_err(io_error)
+}
+
+fn command_blocking(index: u16, address: bluer::Address, opcode: u16, params: &[u8]) -> Result<Vec<u8>> {
+    let mut socket = open_raw(index, COMMAND_EVENTS)?;
+    let (handle, _) = connection_handle(&socket, address)?;
+    send_command(&mut socket, opcode, handle, params)?;
+
+    // The socket also receives the results of commands sent by the kernel and other clients, which are skipped
+    let mut buf = [0u8; 260];
+    loop {
+        let n = socket.read(&mut buf).map_err(io_error)?;
+        if n < 7 || buf[0] != HCI_EVENT_PKT {
+            continue;
+        }
+
+        match buf[1] {
+            EVT_CMD_COMPLETE if u16::from_le_bytes([buf[4], buf[5]]) == opcode => {
+                return match buf[6] {
+                    0 => Ok(buf[7..n].to_vec()),
+                    status => Err(status_error(opcode, status)),
+                };
+            }
+            EVT_CMD_STATUS if u16::from_le_bytes([buf[5], buf[6]]) == opcode => {
+                return match buf[3] {
+                    0 => Ok(Vec::new()),
+                    status => Err(status_error(opcode, status)),
+                };
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Sends the command `opcode` for the LE connection to `address` on the controller with the given `index` and returns
+/// the parameters of its reply after the status.
+///
+/// The connection handle is prepended to `params`. Commands answered with a command status event return no
+/// parameters.
+pub(super) async fn connection_command(
+    index: u16,
+    address: bluer::Address,
+    opcode: u16,
+    params: Vec<u8>,
+) -> Result<Vec<u8>> {
+    tokio::task::spawn_blocking(move || command_blocking(index, address, opcode, &params))
+        .await
+        .map_err(|err| Error::new(ErrorKind::Internal, Some(Box::new(err)), String::new()))?
+}
+
+fn update_connection_blocking(index: u16, address: bluer::Address, parameters: [u16; 4]) -> Result<()> {
+    // Subscribe to the connection's events before sending the command so the completion event is not missed
+    let mut socket = open_raw(index, CONNECTION_EVENTS)?;
+    let (handle, _) = connection_handle(&socket, address)?;
+
+    let mut params = Vec::with_capacity(12);
+    for value in parameters {
+        params.extend_from_slice(&value.to_le_bytes());
+    }
+    // Minimum and maximum connection event length, which the controller chooses
+    params.extend_from_slice(&[0; 4]);
+    send_command(&mut socket, LE_CONNECTION_UPDATE, handle, &params)?;
+
+    let deadline = Instant::now() + UPDATE_TIMEOUT;
+    let mut buf = [0u8; 260];
+    loop {
+        let n = match socket.read(&mut buf) {
+            Ok(n) => n,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) && Instant::now() < deadline =>
+            {
+                continue
+            }
+            Err(err) => return Err(io_error(err)),
+        };
+
+        if let [HCI_EVENT_PKT, EVT_CMD_STATUS, _, status, _, o0, o1, ..] = buf[..n] {
+            if status != 0 && u16::from_le_bytes([o0, o1]) == LE_CONNECTION_UPDATE {
+                return Err(status_error(LE_CONNECTION_UPDATE, status));
+            }
+        }
+        match parse_event(&buf[..n], handle) {
+            Some(ConnectionEvent::ParametersUpdated { status: 0, .. }) => return Ok(()),
+            Some(ConnectionEvent::ParametersUpdated { status, .. }) => {
+                return Err(status_error(LE_CONNECTION_UPDATE, status))
+            }
+            Some(ConnectionEvent::Disconnected) => return Err(ErrorKind::NotConnected.into()),
+            _ => (),
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::new(
+                ErrorKind::Timeout,
+                None,
+                "the controller did not complete the connection update".to_string(),
+            ));
+        }
+    }
+}
+
+/// Asks the controller with the given `index` to update the LE connection to `address` with the minimum and maximum
+/// interval, peripheral latency and supervision timeout in `parameters`, in HCI units, and waits for the update to
+/// complete.
+pub(super) async fn update_connection(index: u16, address: bluer::Address, parameters: [u16; 4]) -> Result<()> {
+    tokio::task::spawn_blocking(move || update_connection_blocking(index, address, parameters))
+        .await
+        .map_err(|err| Error::new(ErrorKind::Internal, Some(Box::new(err)), String::new()))?
+}
+
+/// Monitors the LE connection to `address` on the controller with the given `index`.
+///
+/// The events are read on a dedicated thread, which exits when the receiver is dropped or the device disconnects.
+pub(super) async fn connection_events(
+    index: u16,
+    address: bluer::Address,
+) -> Result<mpsc::UnboundedReceiver<Result<ConnectionEvent>>> {
+    let (mut socket, handle) = blocking(move || {
+        let socket = open_raw(index, CONNECTION_EVENTS)?;
+        let (handle, _) = connection_handle(&socket, address)?;
+        Ok((socket, handle))
+    })
+    .await?;
+
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 260];
+        while !sender.is_closed() {
+            let n = match socket.read(&mut buf) {
+                Ok(n) => n,
+                // The socket's receive timeout lets the thread notice the receiver being dropped
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue
+                }
+                Err(err) => {
+                    let _ = sender.unbounded_send(Err(io_error(err)));
+                    break;
+                }
+            };
+
+            let event = match parse_event(&buf[..n], handle) {
+                Some(ConnectionEvent::Disconnected) => break,
+                Some(event) => event,
+                None => continue,
+            };
+            if sender.unbounded_send(Ok(event)).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+/// The security level of the LE connection to `address` on the controller with the given `index`
+pub(super) async fn connection_security(index: u16, address: bluer::Address) -> Result<SecurityLevel> {
+    blocking(move || {
+        // Reading connection information does not require a filter or any capabilities
+        let socket = open(index, HCI_CHANNEL_RAW)?;
+        let (_, link_mode) = connection_handle(&socket, address)?;
+        Ok(security_level(link_mode))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conn_info_layout() {
+        // `hci_conn_info_req` is 8 bytes including padding, followed by the 16 byte `hci_conn_info`
+        assert_eq!(std::mem::size_of::<ConnInfoRequest>(), 24);
+        let req = ConnInfoRequest::new([0; 6]);
+        let base = &req as *const ConnInfoRequest as usize;
+        assert_eq!(&req.handle as *const u16 as usize - base, 8);
+        assert_eq!(&req.link_mode as *const u32 as usize - base, 20);
+        assert_eq!(std::mem::size_of::<HciFilter>(), 16);
+    }
+
+    #[test]
+    fn link_mode_security_levels() {
+        // HCI_LM_MASTER
+        assert_eq!(security_level(0x0001), SecurityLevel::None);
+        assert_eq!(security_level(0), SecurityLevel::None);
+        // Authentication without encryption does not protect the link
+        assert_eq!(security_level(HCI_LM_AUTH), SecurityLevel::None);
+        assert_eq!(security_level(HCI_LM_ENCRYPT), SecurityLevel::Encrypted);
+        assert_eq!(security_level(HCI_LM_ENCRYPT | 0x0001), SecurityLevel::Encrypted);
+        assert_eq!(
+            security_level(HCI_LM_ENCRYPT | HCI_LM_AUTH),
+            SecurityLevel::Authenticated
+        );
+        assert_eq!(
+            security_level(HCI_LM_ENCRYPT | HCI_LM_AUTH | HCI_LM_SECURE),
+            SecurityLevel::SecureConnections
+        );
+        assert_eq!(
+            security_level(HCI_LM_ENCRYPT | HCI_LM_AUTH | HCI_LM_FIPS),
+            SecurityLevel::SecureConnections
+        );
+        assert_eq!(security_level(HCI_LM_SECURE | HCI_LM_FIPS), SecurityLevel::None);
+    }
+
+    #[test]
+    fn connection_events() {
+        // LE Connection Update Complete for handle 0x0040 with an interval of 7.5 ms, latency 2 and a timeout of 5 s
+        let update = [
+            0x04, 0x3e, 0x0a, 0x03, 0x00, 0x40, 0x00, 0x06, 0x00, 0x02, 0x00, 0xf4, 0x01,
+        ];
+        assert_eq!(
+            parse_event(&update, 0x0040),
+            Some(ConnectionEvent::ParametersUpdated {
+                status: 0,
+                parameters: ConnectionParameters {
+                    interval: Duration::from_micros(7500),
+                    peripheral_latency: 2,
+                    supervision_timeout: Duration::from_secs(5),
+                },
+            })
+        );
+        assert_eq!(parse_event(&update, 0x0041), None);
+        assert_eq!(parse_event(&update[..12], 0x0040), None);
+
+        // LE PHY Update Complete for handle 0x0040 (with reserved bits set) to 2M TX and Coded RX, and a failed update
+        let phy = [0x04, 0x3e, 0x06, 0x0c, 0x00, 0x40, 0x30, 0x02, 0x03];
+        assert_eq!(
+            parse_event(&phy, 0x0040),
+            Some(ConnectionEvent::PhyUpdated {
+                status: 0,
+                tx: 2,
+                rx: 3
+            })
+        );
+        let failed = [0x04, 0x3e, 0x06, 0x0c, 0x1a, 0x40, 0x00, 0x01, 0x01];
+        assert_eq!(
+            parse_event(&failed, 0x0040),
+            Some(ConnectionEvent::PhyUpdated {
+                status: 0x1a,
+                tx: 1,
+                rx: 1
+            })
+        );
+
+        // Disconnection Complete, which is only reported once it succeeds
+        let disconnected = [0x04, 0x05, 0x04, 0x00, 0x40, 0x00, 0x13];
+        assert_eq!(parse_event(&disconnected, 0x0040), Some(ConnectionEvent::Disconnected));
+        assert_eq!(parse_event(&[0x04, 0x05, 0x04, 0x0c, 0x40, 0x00, 0x13], 0x0040), None);
+
+        // Other LE meta events, such as LE Connection Complete, are skipped
+        assert_eq!(parse_event(&[0x04, 0x3e, 0x13, 0x01, 0x00, 0x40, 0x00], 0x0040), None);
+        assert_eq!(parse_event(&[], 0x0040), None);
+    }
+
+    #[test]
+    fn event_filters() {
+        assert_eq!(CONNECTION_EVENTS[1], 0x4000_0000);
+        assert_eq!(CONNECTION_EVENTS[0] & COMMAND_EVENTS[0], 1 << EVT_CMD_STATUS);
+    }
+
+    #[test]
+    fn phy_values() {
+        for x in [Phy::Le1M, Phy::Le2M, Phy::LeCoded] {
+            assert_eq!(phy(phy_value(x)).unwrap(), x);
+        }
+        assert!(phy(0).is_err());
+        assert!(phy(4).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! A minimal client for the Linux kernel's Bluetooth management (mgmt) API, used for the few operations BlueZ does not
+//! expose over D-Bus.
+//!
+//! See `doc/mgmt-api.txt` in the BlueZ source tree for the protocol.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::time::Duration;
+
+use crate::error::ErrorKind;
+use crate::{Error, Result};
+
+pub(super) const UNPAIR_DEVICE: u16 = 0x001b;
+pub(super) const ADD_REMOTE_OOB_DATA: u16 = 0x0021;
+pub(super) const READ_LOCAL_OOB_EXT_DATA: u16 = 0x003b;
+
+const BTPROTO_HCI: libc::c_int = 1;
+const HCI_DEV_NONE: u16 = 0xffff;
+const HCI_CHANNEL_CONTROL: u16 = 3;
+
+const EV_CMD_COMPLETE: u16 = 0x0001;
+const EV_CMD_STATUS: u16 = 0x0002;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[repr(C)]
+struct SockaddrHci {
+    hci_family: libc::sa_family_t,
+    hci_dev: u16,
+    hci_channel: u16,
+}
+
+/// The mgmt address type of a device
+pub(super) fn address_type(address_type: bluer::AddressType) -> u8 {
+    match address_type {
+        bluer::AddressType::BrEdr => 0,
+        bluer::AddressType::LePublic => 1,
+        bluer::AddressType::LeRandom => 2,
+    }
+}
+
+/// The mgmt controller index of the adapter named `name`, e.g. `hci0`
+pub(super) fn controller_index(name: &str) -> Result<u16> {
+    name.strip_prefix("hci")
+        .and_then(|x| x.parse().ok())
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, None, format!("invalid adapter name {name}")))
+}
+
+pub(super) fn io_error(err: std::io::Error) -> Error {
+    let kind = match err.kind() {
+        std::io::ErrorKind::PermissionDenied => ErrorKind::NotAuthorized,
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        _ => ErrorKind::Other,
+    };
+    Error::new(kind, Some(Box::new(err)), "mgmt socket".to_string())
+}
+
+fn status_error(opcode: u16, status: u8) -> Error {
+    let kind = match status {
+        0x01 | 0x0c => ErrorKind::NotSupported,
+        0x02 | 0x0e => ErrorKind::NotConnected,
+        0x08 => ErrorKind::Timeout,
+        0x0a => ErrorKind::NotReady,
+        0x0d => ErrorKind::InvalidParameter,
+        0x0f | 0x11 | 0x12 => ErrorKind::AdapterUnavailable,
+        0x0b | 0x14 => ErrorKind::NotAuthorized,
+        _ => ErrorKind::Other,
+    };
+    Error::new(
+        kind,
+        None,
+        format!("mgmt command {opcode:#06x} failed with status {status:#04x}"),
+    )
+}
+
+/// Opens an HCI socket bound to `channel` of the controller with the given `index`, with a receive timeout.
+pub(super) fn open(index: u16, channel: u16) -> Result<File> {
+    // Safety: `socket` has no memory safety requirements, and the returned descriptor is owned by the `OwnedFd`.
+    let fd = unsafe { libc::socket(libc::AF_BLUETOOTH, libc::SOCK_RAW | libc::SOCK_CLOEXEC, BTPROTO_HCI) };
+    if fd < 0 {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    let raw = std::os::fd::AsRawFd::as_raw_fd(&fd);
+
+    let addr = SockaddrHci {
+        hci_family: libc::AF_BLUETOOTH as libc::sa_family_t,
+        hci_dev: index,
+        hci_channel: channel,
+    };
+    // Safety: `addr` is a valid `sockaddr_hci` and the length passed matches its size.
+    let res = unsafe {
+        libc::bind(
+            raw,
+            &addr as *const SockaddrHci as *const libc::sockaddr,
+            std::mem::size_of::<SockaddrHci>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+
+    let timeout = libc::timeval {
+        tv_sec: TIMEOUT.as_secs() as libc::time_t,
+        tv_usec: 0,
+    };
+    // Safety: `timeout` is a valid `timeval` and the length passed matches its size.
+    let res = unsafe {
+        libc::setsockopt(
+            raw,
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeout as *const libc::timeval as *const libc::c_void,
+            std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+
+    Ok(File::from(fd))
+}
+
+fn command_blocking(index: u16, opcode: u16, params: &[u8]) -> Result<Vec<u8>> {
+    let mut socket = open(HCI_DEV_NONE, HCI_CHANNEL_CONTROL)?;
+
+    let len = u16::try_from(params.len()).map_err(|_| ErrorKind::InvalidParameter)?;
+    let mut packet = Vec::with_capacity(6 + params.len());
+    packet.extend_from_slice(&opcode.to_le_bytes());
+    packet.extend_from_slice(&index.to_le_bytes());
+    packet.extend_from_slice(&len.to_le_bytes());
+    packet.extend_from_slice(params);
+    socket.write_all(&packet).map_err(io_error)?;
+
+    // The socket also receives events for other commands and controllers, which are skipped
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = socket.read(&mut buf).map_err(io_error)?;
+        if n < 9 {
+            continue;
+        }
+
+        let event = u16::from_le_bytes([buf[0], buf[1]]);
+        let event_index = u16::from_le_bytes([buf[2], buf[3]]);
+        let event_opcode = u16::from_le_bytes([buf[6], buf[7]]);
+        let status = buf[8];
+        if event_index != index || event_opcode != opcode {
+            continue;
+        }
+
+        match (event, status) {
+            (EV_CMD_COMPLETE, 0) => return Ok(buf[9..n].to_vec()),
+            (EV_CMD_COMPLETE | EV_CMD_STATUS, _) => return Err(status_error(opcode, status)),
+            _ => (),
+        }
+    }
+}
+
+/// Sends a command to the controller with the given `index` and returns the parameters of its reply.
+///
+/// Most commands require the `CAP_NET_ADMIN` capability.
+pub(super) async fn command(index: u16, opcode: u16, params: Vec<u8>) -> Result<Vec<u8>> {
+    tokio::task::spawn_blocking(move || command_blocking(index, opcode, &params))
+        .await
+        .map_err(|err| Error::new(ErrorKind::Internal, Some(Box::new(err)), String::new()))?
+}
@@ -0,0 +1,49 @@
+use bluer::gatt::remote::CharacteristicWriteRequest;
+use bluer::gatt::WriteOp;
+
+use crate::error::ErrorKind;
+use crate::{Characteristic, Error, Result};
+
+/// A reliable write transaction
+#[derive(Debug)]
+pub struct ReliableWriteImpl {
+    writes: Vec<(Characteristic, Vec<u8>)>,
+}
+
+impl ReliableWriteImpl {
+    pub(super) fn new() -> Self {
+        ReliableWriteImpl { writes: Vec::new() }
+    }
+
+    /// Queue a write of `value` to `characteristic` in this transaction.
+    pub fn write(&mut self, characteristic: &Characteristic, value: &[u8]) -> Result<()> {
+        if !self.writes.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "BlueZ only supports reliable writes to a single characteristic".to_string(),
+            ));
+        }
+        self.writes.push((characteristic.clone(), value.to_vec()));
+        Ok(())
+    }
+
+    /// Execute all queued writes atomically.
+    pub async fn commit(self) -> Result<()> {
+        match self.writes.first() {
+            None => Ok(()),
+            Some((characteristic, value)) => characteristic
+                .0
+                .inner
+                .write_ext(
+                    value,
+                    &CharacteristicWriteRequest {
+                        op_type: WriteOp::Reliable,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(Into::into),
+        }
+    }
+}
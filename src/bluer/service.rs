@@ -54,6 +54,14 @@ impl ServiceImpl {
         self.inner.uuid().await.map_err(Into::into)
     }
 
+    /// The attribute handle of this GATT service
+    ///
+    /// bluer does not expose attribute handles. Its `id` is parsed from the D-Bus object path, which BlueZ names
+    /// `serviceXXXX` after the handle of the service declaration, so this relies on BlueZ's object path naming.
+    pub fn handle(&self) -> Result<u16> {
+        Ok(self.inner.id())
+    }
+
     /// Whether this is a primary service of the device.
     pub async fn is_primary(&self) -> Result<bool> {
         self.inner.primary().await.map_err(Into::into)
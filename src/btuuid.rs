@@ -509,6 +509,7 @@ pub mod characteristics {
     pub const COORDINATED_SET_SIZE: Uuid = bluetooth_uuid_from_u16(0x2B85);
     pub const SET_MEMBER_LOCK: Uuid = bluetooth_uuid_from_u16(0x2B86);
     pub const SET_MEMBER_RANK: Uuid = bluetooth_uuid_from_u16(0x2B87);
+    pub const ENCRYPTED_DATA_KEY_MATERIAL: Uuid = bluetooth_uuid_from_u16(0x2B88);
     pub const DEVICE_TIME_FEATURE: Uuid = bluetooth_uuid_from_u16(0x2B8E);
     pub const DEVICE_TIME_PARAMETERS: Uuid = bluetooth_uuid_from_u16(0x2B8F);
     pub const DEVICE_TIME: Uuid = bluetooth_uuid_from_u16(0x2B90);
@@ -1,6 +1,10 @@
 use futures_util::Stream;
 
-use crate::{sys, CharacteristicProperties, Descriptor, Result, Uuid};
+use crate::descriptors::Cccd;
+use crate::{sys, CacheMode, CharacteristicProperties, Descriptor, Result, Service, Uuid, WriteKind};
+
+/// The maximum length of an attribute value (Bluetooth Core Specification, Vol 3, Part F, §3.2.9)
+pub(crate) const MAX_ATTRIBUTE_VALUE_LEN: usize = 512;
 
 /// A Bluetooth GATT characteristic
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -25,6 +29,29 @@ impl Characteristic {
         self.0.uuid_async().await
     }
 
+    /// The attribute handle of this GATT characteristic's value
+    ///
+    /// This is the handle used in ATT requests and notifications for the characteristic, not the handle of the
+    /// characteristic declaration which precedes it. Handles distinguish multiple instances of characteristics with the
+    /// same [`Uuid`]. They are only stable for as long as the device's GATT database is unchanged.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    ///
+    /// On Linux, BlueZ does not expose attribute handles, so the handle is taken from the D-Bus object path, which
+    /// BlueZ names after the handle.
+    #[inline]
+    pub fn handle(&self) -> Result<u16> {
+        self.0.handle()
+    }
+
+    /// The [`Service`] containing this characteristic
+    #[inline]
+    pub async fn service(&self) -> Result<Service> {
+        self.0.service().await
+    }
+
     /// The properties of this this GATT characteristic.
     ///
     /// Characteristic properties indicate which operations (e.g. read, write, notify, etc) may be performed on this
@@ -48,6 +75,29 @@ impl Characteristic {
         self.0.read().await
     }
 
+    /// Read the value of this characteristic, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`value`][Self::value] for [`CacheMode::Cached`] and [`read`][Self::read] for
+    /// [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn read_with(&self, mode: CacheMode) -> Result<Vec<u8>> {
+        match mode {
+            CacheMode::Cached => self.0.value().await,
+            CacheMode::Uncached => self.0.read().await,
+        }
+    }
+
+    /// Read the value of this characteristic from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Windows if `offset` is not
+    /// zero. Reads at offset zero are equivalent to [`read`][Self::read].
+    #[inline]
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        self.0.read_at(offset).await
+    }
+
     /// Write the value of this descriptor on the device to `value` and request the device return a response indicating
     /// a successful write.
     #[inline]
@@ -55,12 +105,43 @@ impl Characteristic {
         self.0.write(value).await
     }
 
+    /// Write `value` to the value of this characteristic on the device starting at `offset`, using the long write
+    /// procedure.
+    ///
+    /// The value is sent using one or more prepared writes which are then executed together, so the device will
+    /// either apply the whole value or none of it.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Windows if `offset` is not
+    /// zero. Writes at offset zero are equivalent to [`write`][Self::write].
+    #[inline]
+    pub async fn write_at(&self, offset: u16, value: &[u8]) -> Result<()> {
+        self.0.write_at(offset, value).await
+    }
+
     /// Write the value of this descriptor on the device to `value` without requesting a response.
     #[inline]
     pub async fn write_without_response(&self, value: &[u8]) {
         self.0.write_without_response(value).await
     }
 
+    /// The maximum number of bytes which may be written to this characteristic in a single call to
+    /// [`write`][Self::write] or [`write_without_response`][Self::write_without_response], depending on `kind`.
+    ///
+    /// For [`WriteKind::WithoutResponse`] this is the negotiated ATT MTU less the 3 byte ATT header. Writes with a
+    /// response are split into multiple packets by the OS if necessary, so their limit is the maximum attribute value
+    /// length of 512 bytes.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, [`WriteKind::WithoutResponse`] is only supported for characteristics which support write without
+    /// response.
+    #[inline]
+    pub async fn max_write_len(&self, kind: WriteKind) -> Result<usize> {
+        self.0.max_write_len(kind).await
+    }
+
     /// Enables notification of value changes for this GATT characteristic.
     ///
     /// Returns a stream of values for the characteristic sent from the device.
@@ -75,6 +156,36 @@ impl Characteristic {
         self.0.is_notifying().await
     }
 
+    /// Read the client characteristic configuration descriptor (CCCD) of this characteristic from the device.
+    ///
+    /// Returns a [`NotFound`][crate::error::ErrorKind::NotFound] error if the characteristic has no CCCD.
+    #[inline]
+    pub async fn cccd(&self) -> Result<Cccd> {
+        self.0.cccd().await
+    }
+
+    /// Write the client characteristic configuration descriptor (CCCD) of this characteristic on the device.
+    ///
+    /// This configures whether the device sends notifications or indications without opening a stream with
+    /// [`notify`][Self::notify], e.g. so that the configuration is stored as part of a bond. Values sent by the device
+    /// are only delivered while a [`notify`][Self::notify] stream is open, and dropping that stream resets the CCCD.
+    ///
+    /// The CCCD cannot be written with [`Descriptor::write`] on any platform.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Linux, where the OS only
+    /// configures the CCCD through [`notify`][Self::notify].
+    ///
+    /// On Linux, BlueZ owns the CCCD: it rejects writes to it from clients, enables notifications or indications
+    /// while any client has called `StartNotify` or `AcquireNotify`, and disables them again when the last of those
+    /// clients stops or disconnects from D-Bus. Neither D-Bus nor the management API offers a way to set a CCCD value
+    /// which outlives the client.
+    #[inline]
+    pub async fn set_cccd(&self, cccd: Cccd) -> Result<()> {
+        self.0.set_cccd(cccd).await
+    }
+
     /// Discover the descriptors associated with this characteristic.
     #[inline]
     pub async fn discover_descriptors(&self) -> Result<Vec<Descriptor>> {
@@ -89,4 +200,16 @@ impl Characteristic {
     pub async fn descriptors(&self) -> Result<Vec<Descriptor>> {
         self.0.descriptors().await
     }
+
+    /// Discover the descriptors associated with this characteristic, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`descriptors`][Self::descriptors] for [`CacheMode::Cached`] and
+    /// [`discover_descriptors`][Self::discover_descriptors] for [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn discover_descriptors_with(&self, mode: CacheMode) -> Result<Vec<Descriptor>> {
+        match mode {
+            CacheMode::Cached => self.0.descriptors().await,
+            CacheMode::Uncached => self.0.discover_descriptors().await,
+        }
+    }
 }
@@ -0,0 +1,545 @@
+//! Encoding and decoding of typed GATT characteristic values.
+//!
+//! The [`GattDecode`] and [`GattEncode`] traits convert between raw characteristic values and Rust types using the
+//! formats defined in the Bluetooth GATT Specification Supplement (GSS). Implementations are provided for the
+//! little-endian fixed-width integers, `uint24`/`sint24`/`uint48` ([`Uint24`], [`Sint24`], [`Uint48`]), booleans,
+//! UTF-8 strings, and the IEEE-11073 16-bit and 32-bit floating point types ([`SFloat`], [`Float`]).
+//!
+//! ```rust
+//! use bluest::codec::{GattDecode, GattEncode, SFloat, Sint24};
+//!
+//! assert_eq!(i16::decode(&[0xfe, 0xff]), Ok(-2));
+//! assert_eq!(Sint24::decode(&[0xff, 0xff, 0xff]).map(i32::from), Ok(-1));
+//!
+//! // 36.4 encoded as 364 * 10^-1
+//! let temp = SFloat::new(364, -1).unwrap();
+//! assert_eq!(temp.encode(), vec![0x6c, 0xf1]);
+//! assert_eq!(SFloat::decode(&[0x6c, 0xf1]), Ok(temp));
+//! ```
+//!
+//! [`TypedCharacteristic`] wraps a [`Characteristic`] whose value is always of a single type.
+
+use std::marker::PhantomData;
+
+use futures_util::{Stream, StreamExt};
+
+use crate::error::ErrorKind;
+use crate::{Characteristic, Error, Result};
+
+/// An error returned when a characteristic value cannot be decoded as the requested type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidValue(pub(crate) ());
+
+impl std::fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid characteristic value")
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+impl From<InvalidValue> for Error {
+    fn from(err: InvalidValue) -> Self {
+        Error::new(
+            ErrorKind::InvalidParameter,
+            Some(Box::new(err)),
+            "characteristic value could not be decoded".to_string(),
+        )
+    }
+}
+
+/// A type which can be decoded from a GATT characteristic or descriptor value
+pub trait GattDecode: Sized {
+    /// Decodes `value`, which must contain exactly one encoded value of this type.
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue>;
+}
+
+/// A type which can be encoded as a GATT characteristic or descriptor value
+pub trait GattEncode {
+    /// Encodes `self` in the GATT format for this type.
+    fn encode(&self) -> Vec<u8>;
+}
+
+fn fixed<const N: usize>(value: &[u8]) -> Result<[u8; N], InvalidValue> {
+    value.try_into().map_err(|_| InvalidValue(()))
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {
+        $(
+            impl GattDecode for $ty {
+                fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+                    fixed(value).map(<$ty>::from_le_bytes)
+                }
+            }
+
+            impl GattEncode for $ty {
+                fn encode(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl GattDecode for bool {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        match value {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(InvalidValue(())),
+        }
+    }
+}
+
+impl GattEncode for bool {
+    fn encode(&self) -> Vec<u8> {
+        vec![u8::from(*self)]
+    }
+}
+
+/// UTF-8 string (`utf8s`). Trailing NUL characters, which some devices use as padding, are removed when decoding.
+impl GattDecode for String {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let len = value.iter().rposition(|&x| x != 0).map_or(0, |i| i + 1);
+        String::from_utf8(value[..len].to_vec()).map_err(|_| InvalidValue(()))
+    }
+}
+
+impl GattEncode for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+impl GattEncode for str {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+/// The raw value, unchanged
+impl GattDecode for Vec<u8> {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        Ok(value.to_vec())
+    }
+}
+
+impl GattEncode for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+}
+
+impl GattEncode for [u8] {
+    fn encode(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+impl<T: GattEncode + ?Sized> GattEncode for &T {
+    fn encode(&self) -> Vec<u8> {
+        (**self).encode()
+    }
+}
+
+/// An unsigned 24-bit integer (`uint24`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uint24(u32);
+
+impl Uint24 {
+    /// The largest value representable by a `Uint24`
+    pub const MAX: Uint24 = Uint24(0x00ff_ffff);
+
+    /// Creates a new `Uint24`, returning `None` if `n` is out of range
+    pub fn new(n: u32) -> Option<Self> {
+        (n <= Self::MAX.0).then_some(Uint24(n))
+    }
+}
+
+impl From<Uint24> for u32 {
+    fn from(val: Uint24) -> Self {
+        val.0
+    }
+}
+
+impl GattDecode for Uint24 {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let [a, b, c] = fixed(value)?;
+        Ok(Uint24(u32::from_le_bytes([a, b, c, 0])))
+    }
+}
+
+impl GattEncode for Uint24 {
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes()[..3].to_vec()
+    }
+}
+
+/// A signed 24-bit integer (`sint24`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sint24(i32);
+
+impl Sint24 {
+    /// The smallest value representable by a `Sint24`
+    pub const MIN: Sint24 = Sint24(-0x0080_0000);
+    /// The largest value representable by a `Sint24`
+    pub const MAX: Sint24 = Sint24(0x007f_ffff);
+
+    /// Creates a new `Sint24`, returning `None` if `n` is out of range
+    pub fn new(n: i32) -> Option<Self> {
+        (Self::MIN.0..=Self::MAX.0).contains(&n).then_some(Sint24(n))
+    }
+}
+
+impl From<Sint24> for i32 {
+    fn from(val: Sint24) -> Self {
+        val.0
+    }
+}
+
+impl GattDecode for Sint24 {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let [a, b, c] = fixed(value)?;
+        // Place the value in the upper bytes and shift back down to sign-extend it
+        Ok(Sint24(i32::from_le_bytes([0, a, b, c]) >> 8))
+    }
+}
+
+impl GattEncode for Sint24 {
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes()[..3].to_vec()
+    }
+}
+
+/// An unsigned 48-bit integer (`uint48`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uint48(u64);
+
+impl Uint48 {
+    /// The largest value representable by a `Uint48`
+    pub const MAX: Uint48 = Uint48(0xffff_ffff_ffff);
+
+    /// Creates a new `Uint48`, returning `None` if `n` is out of range
+    pub fn new(n: u64) -> Option<Self> {
+        (n <= Self::MAX.0).then_some(Uint48(n))
+    }
+}
+
+impl From<Uint48> for u64 {
+    fn from(val: Uint48) -> Self {
+        val.0
+    }
+}
+
+impl GattDecode for Uint48 {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let [a, b, c, d, e, f] = fixed(value)?;
+        Ok(Uint48(u64::from_le_bytes([a, b, c, d, e, f, 0, 0])))
+    }
+}
+
+impl GattEncode for Uint48 {
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes()[..6].to_vec()
+    }
+}
+
+macro_rules! impl_medfloat {
+    (
+        $(#[$attr:meta])*
+        $name:ident($mantissa:ty, $raw:ty), mantissa_bits = $mbits:expr, exponent_bits = $ebits:expr
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name {
+            mantissa: $mantissa,
+            exponent: i8,
+        }
+
+        impl $name {
+            const MANTISSA_MAX: $mantissa = (1 << ($mbits - 1)) - 1;
+            const MANTISSA_MIN: $mantissa = -(1 << ($mbits - 1));
+            const EXPONENT_MAX: i8 = (i8::MAX >> (8 - $ebits));
+            const EXPONENT_MIN: i8 = (i8::MIN >> (8 - $ebits));
+
+            /// Not a Number
+            pub const NAN: $name = $name { mantissa: Self::MANTISSA_MAX, exponent: 0 };
+            /// Not at this Resolution
+            pub const NRES: $name = $name { mantissa: Self::MANTISSA_MIN, exponent: 0 };
+            /// Positive infinity
+            pub const INFINITY: $name = $name { mantissa: Self::MANTISSA_MAX - 1, exponent: 0 };
+            /// Negative infinity
+            pub const NEG_INFINITY: $name = $name { mantissa: Self::MANTISSA_MIN + 2, exponent: 0 };
+
+            /// Creates the value `mantissa * 10^exponent`, returning `None` if `mantissa` or `exponent` do not fit in
+            /// the number of bits available for them
+            pub fn new(mantissa: $mantissa, exponent: i8) -> Option<Self> {
+                ((Self::MANTISSA_MIN..=Self::MANTISSA_MAX).contains(&mantissa)
+                    && (Self::EXPONENT_MIN..=Self::EXPONENT_MAX).contains(&exponent))
+                .then_some($name { mantissa, exponent })
+            }
+
+            /// The mantissa of this value
+            pub fn mantissa(&self) -> $mantissa {
+                self.mantissa
+            }
+
+            /// The base 10 exponent of this value
+            pub fn exponent(&self) -> i8 {
+                self.exponent
+            }
+
+            /// Whether this is one of the special values (NaN, NRes, infinities or the reserved value) rather than a
+            /// finite number
+            pub fn is_special(&self) -> bool {
+                self.exponent == 0
+                    && (self.mantissa >= Self::MANTISSA_MAX - 1 || self.mantissa <= Self::MANTISSA_MIN + 2)
+            }
+
+            /// Converts this value to an [`f64`].
+            ///
+            /// NaN, NRes and the reserved value are all converted to [`f64::NAN`].
+            pub fn to_f64(self) -> f64 {
+                if self == Self::INFINITY {
+                    f64::INFINITY
+                } else if self == Self::NEG_INFINITY {
+                    f64::NEG_INFINITY
+                } else if self.is_special() {
+                    f64::NAN
+                } else {
+                    f64::from(self.mantissa) * 10f64.powi(i32::from(self.exponent))
+                }
+            }
+
+            fn to_raw(self) -> $raw {
+                let mantissa = (self.mantissa as $raw) & ((1 << $mbits) - 1);
+                let exponent = ((self.exponent as $raw) & ((1 << $ebits) - 1)) << $mbits;
+                exponent | mantissa
+            }
+
+            fn from_raw(raw: $raw) -> Self {
+                // Shift each field to the top of the word and back down to sign-extend it
+                const BITS: u32 = <$raw>::BITS;
+                let mantissa = ((raw << (BITS - $mbits)) as <$raw as ToSigned>::Signed >> (BITS - $mbits)) as $mantissa;
+                let exponent = ((raw as <$raw as ToSigned>::Signed) >> $mbits) as i8;
+                $name { mantissa, exponent }
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(val: $name) -> Self {
+                val.to_f64()
+            }
+        }
+
+        impl GattDecode for $name {
+            fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+                <$raw>::decode(value).map(Self::from_raw)
+            }
+        }
+
+        impl GattEncode for $name {
+            fn encode(&self) -> Vec<u8> {
+                self.to_raw().encode()
+            }
+        }
+    };
+}
+
+trait ToSigned {
+    type Signed;
+}
+
+impl ToSigned for u16 {
+    type Signed = i16;
+}
+
+impl ToSigned for u32 {
+    type Signed = i32;
+}
+
+impl_medfloat!(
+    /// An IEEE-11073 16-bit floating point value (`medfloat16` or `SFLOAT`)
+    ///
+    /// The value is `mantissa * 10^exponent`, with a 12-bit signed mantissa and a 4-bit signed exponent.
+    SFloat(i16, u16), mantissa_bits = 12, exponent_bits = 4
+);
+
+impl_medfloat!(
+    /// An IEEE-11073 32-bit floating point value (`medfloat32` or `FLOAT`)
+    ///
+    /// The value is `mantissa * 10^exponent`, with a 24-bit signed mantissa and an 8-bit signed exponent.
+    Float(i32, u32), mantissa_bits = 24, exponent_bits = 8
+);
+
+impl Characteristic {
+    /// Read the value of this characteristic from the device and decode it as a `T`
+    pub async fn read_typed<T: GattDecode>(&self) -> Result<T> {
+        let value = self.read().await?;
+        T::decode(&value).map_err(Into::into)
+    }
+
+    /// Encode `value` and write it to this characteristic on the device, requesting a response
+    pub async fn write_typed<T: GattEncode + ?Sized>(&self, value: &T) -> Result<()> {
+        self.write(&value.encode()).await
+    }
+
+    /// Enables notification of value changes for this GATT characteristic, decoding each value as a `T`
+    pub async fn notify_typed<T: GattDecode>(&self) -> Result<impl Stream<Item = Result<T>> + '_> {
+        let notifications = self.notify().await?;
+        Ok(notifications.map(|x| x.and_then(|value| T::decode(&value).map_err(Into::into))))
+    }
+}
+
+/// A [`Characteristic`] whose value is always of type `T`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypedCharacteristic<T> {
+    characteristic: Characteristic,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedCharacteristic<T> {
+    /// Creates a `TypedCharacteristic` from an untyped `characteristic`
+    pub fn new(characteristic: Characteristic) -> Self {
+        TypedCharacteristic {
+            characteristic,
+            marker: PhantomData,
+        }
+    }
+
+    /// The underlying untyped characteristic
+    pub fn characteristic(&self) -> &Characteristic {
+        &self.characteristic
+    }
+
+    /// Converts this into the underlying untyped characteristic
+    pub fn into_inner(self) -> Characteristic {
+        self.characteristic
+    }
+}
+
+impl<T> From<Characteristic> for TypedCharacteristic<T> {
+    fn from(characteristic: Characteristic) -> Self {
+        TypedCharacteristic::new(characteristic)
+    }
+}
+
+impl<T: GattDecode> TypedCharacteristic<T> {
+    /// Read the value of this characteristic from the device
+    pub async fn read_typed(&self) -> Result<T> {
+        self.characteristic.read_typed().await
+    }
+
+    /// Enables notification of value changes for this GATT characteristic.
+    pub async fn notify_typed(&self) -> Result<impl Stream<Item = Result<T>> + '_> {
+        self.characteristic.notify_typed().await
+    }
+}
+
+impl<T: GattEncode> TypedCharacteristic<T> {
+    /// Write `value` to this characteristic on the device, requesting a response
+    pub async fn write_typed(&self, value: &T) -> Result<()> {
+        self.characteristic.write_typed(value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: GattDecode + GattEncode + PartialEq + std::fmt::Debug>(value: T, encoded: &[u8]) {
+        assert_eq!(value.encode(), encoded);
+        assert_eq!(T::decode(encoded), Ok(value));
+    }
+
+    #[test]
+    fn integers() {
+        round_trip(0x12u8, &[0x12]);
+        round_trip(0x1234u16, &[0x34, 0x12]);
+        round_trip(-2i16, &[0xfe, 0xff]);
+        round_trip(0x1234_5678u32, &[0x78, 0x56, 0x34, 0x12]);
+        round_trip(-1i64, &[0xff; 8]);
+        round_trip(1u128, &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(u16::decode(&[0x34]), Err(InvalidValue(())));
+        assert_eq!(u16::decode(&[0x34, 0x12, 0x00]), Err(InvalidValue(())));
+    }
+
+    #[test]
+    fn odd_width_integers() {
+        round_trip(Uint24::new(0x12_3456).unwrap(), &[0x56, 0x34, 0x12]);
+        round_trip(Uint24::MAX, &[0xff, 0xff, 0xff]);
+        assert_eq!(Uint24::new(0x100_0000), None);
+
+        round_trip(Sint24::new(-1).unwrap(), &[0xff, 0xff, 0xff]);
+        round_trip(Sint24::MIN, &[0x00, 0x00, 0x80]);
+        round_trip(Sint24::MAX, &[0xff, 0xff, 0x7f]);
+        assert_eq!(Sint24::new(0x80_0000), None);
+        assert_eq!(Sint24::new(-0x80_0001), None);
+
+        round_trip(
+            Uint48::new(0x1234_5678_9abc).unwrap(),
+            &[0xbc, 0x9a, 0x78, 0x56, 0x34, 0x12],
+        );
+        assert_eq!(Uint48::new(0x1_0000_0000_0000), None);
+        assert_eq!(Uint48::decode(&[0; 5]), Err(InvalidValue(())));
+    }
+
+    #[test]
+    fn bool_and_strings() {
+        round_trip(false, &[0]);
+        round_trip(true, &[1]);
+        assert_eq!(bool::decode(&[2]), Err(InvalidValue(())));
+        assert_eq!(bool::decode(&[]), Err(InvalidValue(())));
+
+        round_trip(String::from("Bluest"), b"Bluest");
+        assert_eq!(String::decode(b"abc\0\0"), Ok(String::from("abc")));
+        assert_eq!(String::decode(&[0xff]), Err(InvalidValue(())));
+
+        round_trip(vec![1u8, 2, 3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn sfloat() {
+        round_trip(SFloat::new(364, -1).unwrap(), &[0x6c, 0xf1]);
+        round_trip(SFloat::new(-1, 2).unwrap(), &[0xff, 0x2f]);
+        round_trip(SFloat::new(-2048, -8).unwrap(), &[0x00, 0x88]);
+        round_trip(SFloat::NAN, &[0xff, 0x07]);
+        round_trip(SFloat::NRES, &[0x00, 0x08]);
+        round_trip(SFloat::INFINITY, &[0xfe, 0x07]);
+        round_trip(SFloat::NEG_INFINITY, &[0x02, 0x08]);
+
+        assert_eq!(SFloat::new(2048, 0), None);
+        assert_eq!(SFloat::new(-2049, 0), None);
+        assert_eq!(SFloat::new(0, 8), None);
+        assert_eq!(SFloat::new(0, -9), None);
+
+        assert_eq!(SFloat::new(364, -1).unwrap().to_f64(), 36.4);
+        assert!(SFloat::NAN.to_f64().is_nan());
+        assert!(SFloat::NRES.to_f64().is_nan());
+        assert_eq!(SFloat::INFINITY.to_f64(), f64::INFINITY);
+        assert_eq!(SFloat::NEG_INFINITY.to_f64(), f64::NEG_INFINITY);
+        assert!(!SFloat::new(0, 1).unwrap().is_special());
+    }
+
+    #[test]
+    fn float() {
+        round_trip(Float::new(3640, -2).unwrap(), &[0x38, 0x0e, 0x00, 0xfe]);
+        round_trip(Float::new(-1, 127).unwrap(), &[0xff, 0xff, 0xff, 0x7f]);
+        round_trip(Float::NAN, &[0xff, 0xff, 0x7f, 0x00]);
+        round_trip(Float::NEG_INFINITY, &[0x02, 0x00, 0x80, 0x00]);
+
+        assert_eq!(Float::new(0x80_0000, 0), None);
+        assert_eq!(Float::new(-0x80_0001, 0), None);
+        assert_eq!(Float::new(0x7f_ffff, -128).map(|x| x.exponent()), Some(-128));
+        assert_eq!(Float::decode(&[0; 3]), Err(InvalidValue(())));
+    }
+
+    #[test]
+    fn invalid_value_error() {
+        let err = Error::from(InvalidValue(()));
+        assert_eq!(err.kind(), ErrorKind::InvalidParameter);
+    }
+}
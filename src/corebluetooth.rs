@@ -5,6 +5,7 @@ pub mod characteristic;
 pub mod descriptor;
 pub mod device;
 pub mod error;
+pub mod reliable_write;
 pub mod service;
 
 mod delegates;
@@ -20,3 +21,11 @@ impl std::fmt::Display for DeviceId {
         std::fmt::Display::fmt(&self.0, f)
     }
 }
+
+#[cfg(test)]
+impl DeviceId {
+    /// A device identifier for use in tests, distinct for each value of `n`
+    pub(crate) fn test(n: u8) -> Self {
+        DeviceId(Uuid::from_u128(n.into()))
+    }
+}
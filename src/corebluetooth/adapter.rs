@@ -14,6 +14,7 @@ use super::delegates::{self, CentralDelegate};
 use super::types::{CBCentralManager, CBManagerAuthorization, CBManagerState, CBUUID, NSUUID};
 use crate::corebluetooth::types::{dispatch_get_global_queue, QOS_CLASS_UTILITY};
 use crate::error::ErrorKind;
+use crate::pairing::{BondData, OobData, PairingAgent};
 use crate::util::defer;
 use crate::{
     AdapterEvent, AdvertisementData, AdvertisingDevice, ConnectionEvent, Device, DeviceId, Error, Result, Uuid,
@@ -139,6 +140,15 @@ impl AdapterImpl {
             .await
     }
 
+    /// Finds all devices bonded with this adapter
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn bonded_devices(&self) -> Result<Vec<Device>> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
     /// Finds all connected devices providing any service in `services`
     ///
     /// # Panics
@@ -374,4 +384,46 @@ impl AdapterImpl {
                 })
             }))
     }
+
+    /// Registers `agent` as the system's default pairing agent.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn register_default_agent(&self, _agent: Arc<dyn PairingAgent>) -> Result<DefaultAgentImpl> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Creates new LE Secure Connections out-of-band pairing data for this adapter.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn local_oob_data(&self) -> Result<OobData> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Exports the stored bond with the device identified by `id`, including its keys.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn export_bond(&self, _id: &DeviceId) -> Result<BondData> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Stores a bond previously exported with [`export_bond`][Self::export_bond].
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn import_bond(&self, _bond: &BondData) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
 }
+
+/// A default pairing agent
+///
+/// Pairing requests are always handled by the OS, so this type cannot be constructed.
+#[derive(Debug)]
+pub enum DefaultAgentImpl {}
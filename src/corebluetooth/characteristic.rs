@@ -7,9 +7,12 @@ use tokio_stream::wrappers::BroadcastStream;
 
 use super::delegates::{PeripheralDelegate, PeripheralEvent};
 use super::types::{CBCharacteristic, CBCharacteristicWriteType, CBPeripheralState};
+use crate::btuuid::descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION;
+use crate::codec::GattDecode;
+use crate::descriptors::Cccd;
 use crate::error::ErrorKind;
 use crate::util::defer;
-use crate::{Characteristic, CharacteristicProperties, Descriptor, Error, Result, Uuid};
+use crate::{Characteristic, CharacteristicProperties, Descriptor, Error, Result, Service, Uuid, WriteKind};
 
 /// A Bluetooth GATT characteristic
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -44,6 +47,20 @@ impl CharacteristicImpl {
         Ok(self.uuid())
     }
 
+    /// The attribute handle of this GATT characteristic
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub fn handle(&self) -> Result<u16> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The [`Service`] containing this characteristic
+    pub async fn service(&self) -> Result<Service> {
+        Ok(Service::new(&self.inner.service()))
+    }
+
     /// The properties of this this GATT characteristic.
     ///
     /// Characteristic properties indicate which operations (e.g. read, write, notify, etc) may be performed on this
@@ -100,6 +117,21 @@ impl CharacteristicImpl {
         }
     }
 
+    /// Read the value of this characteristic from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "reading at a non-zero offset is not supported".to_string(),
+            ));
+        }
+
+        self.read().await
+    }
+
     /// Write the value of this descriptor on the device to `value` and request the device return a response indicating
     /// a successful write.
     pub async fn write(&self, value: &[u8]) -> Result<()> {
@@ -135,6 +167,22 @@ impl CharacteristicImpl {
         }
     }
 
+    /// Write `value` to the value of this characteristic on the device starting at `offset`, using the long write
+    /// procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn write_at(&self, offset: u16, value: &[u8]) -> Result<()> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "writing at a non-zero offset is not supported".to_string(),
+            ));
+        }
+
+        self.write(value).await
+    }
+
     /// Write the value of this descriptor on the device to `value` without requesting a response.
     pub async fn write_without_response(&self, value: &[u8]) {
         let data = INSData::from_vec(value.to_vec());
@@ -145,6 +193,19 @@ impl CharacteristicImpl {
         );
     }
 
+    /// The maximum number of bytes which may be written to this characteristic in a single call.
+    pub async fn max_write_len(&self, kind: WriteKind) -> Result<usize> {
+        let write_type = match kind {
+            WriteKind::WithResponse => CBCharacteristicWriteType::WithResponse,
+            WriteKind::WithoutResponse => CBCharacteristicWriteType::WithoutResponse,
+        };
+        Ok(self
+            .inner
+            .service()
+            .peripheral()
+            .maximum_write_value_length_for_type(write_type))
+    }
+
     /// Enables notification of value changes for this GATT characteristic.
     ///
     /// Returns a stream of values for the characteristic sent from the device.
@@ -232,6 +293,27 @@ impl CharacteristicImpl {
         Ok(self.inner.is_notifying())
     }
 
+    /// Read the client characteristic configuration descriptor of this characteristic from the device.
+    pub async fn cccd(&self) -> Result<Cccd> {
+        for descriptor in self.discover_descriptors().await? {
+            if descriptor.uuid() == CLIENT_CHARACTERISTIC_CONFIGURATION {
+                return Ok(Cccd::decode(&descriptor.read().await?)?);
+            }
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            None,
+            "client characteristic configuration descriptor not found".to_string(),
+        ))
+    }
+
+    /// Write the client characteristic configuration descriptor of this characteristic on the device.
+    ///
+    /// CoreBluetooth only configures the CCCD through [`notify`][Self::notify], so this method is not supported.
+    pub async fn set_cccd(&self, _cccd: Cccd) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
     /// Discover the descriptors associated with this characteristic.
     pub async fn discover_descriptors(&self) -> Result<Vec<Descriptor>> {
         let service = self.inner.service();
@@ -4,8 +4,9 @@ use objc_id::ShareId;
 
 use super::delegates::{PeripheralDelegate, PeripheralEvent};
 use super::types::{CBDescriptor, CBPeripheralState, NSUInteger};
+use crate::btuuid::descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION;
 use crate::error::ErrorKind;
-use crate::{Descriptor, Error, Result, Uuid};
+use crate::{Characteristic, Descriptor, Error, Result, Uuid};
 
 /// A Bluetooth GATT descriptor
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -71,6 +72,20 @@ impl DescriptorImpl {
         Ok(self.uuid())
     }
 
+    /// The attribute handle of this GATT descriptor
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub fn handle(&self) -> Result<u16> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The [`Characteristic`] containing this descriptor
+    pub async fn characteristic(&self) -> Result<Characteristic> {
+        Ok(Characteristic::new(&self.inner.characteristic()))
+    }
+
     /// The cached value of this descriptor
     ///
     /// If the value has not yet been read, this method may either return an error or perform a read of the value.
@@ -116,8 +131,32 @@ impl DescriptorImpl {
         }
     }
 
+    /// Read the value of this descriptor from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "reading at a non-zero offset is not supported".to_string(),
+            ));
+        }
+
+        self.read().await
+    }
+
     /// Write the value of this descriptor on the device to `value`
     pub async fn write(&self, value: &[u8]) -> Result<()> {
+        // CoreBluetooth raises an exception for writes to the CCCD
+        if self.uuid() == CLIENT_CHARACTERISTIC_CONFIGURATION {
+            return Err(Error::new(
+                ErrorKind::NotAuthorized,
+                None,
+                "the client characteristic configuration descriptor cannot be written directly".to_string(),
+            ));
+        }
+
         let service = self.inner.characteristic().service();
         let peripheral = service.peripheral();
         let mut receiver = self.delegate.sender().subscribe();
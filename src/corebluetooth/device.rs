@@ -1,13 +1,23 @@
 #![allow(clippy::let_unit_value)]
 
+use std::future::ready;
+
+use futures_util::stream::Empty;
+use futures_util::{Stream, StreamExt};
 use objc_foundation::{INSArray, INSFastEnumeration, INSString, NSArray};
 use objc_id::ShareId;
+use tokio_stream::wrappers::BroadcastStream;
 
 use super::delegates::{PeripheralDelegate, PeripheralEvent};
-use super::types::{CBPeripheral, CBPeripheralState, CBUUID};
+use super::types::{CBCharacteristicWriteType, CBPeripheral, CBPeripheralState, CBUUID};
 use crate::error::ErrorKind;
-use crate::pairing::PairingAgent;
-use crate::{Device, DeviceId, Error, Result, Service, Uuid};
+#[cfg(any(test, feature = "testing"))]
+use crate::pairing::{testing::AssociationModel, IoCapability, PairingRejected, Passkey};
+use crate::pairing::{BondInfo, OobData, PairingAgent, PairingEvent, SecurityLevel};
+use crate::{
+    ConnectionParameters, ConnectionPhy, ConnectionPriority, Device, DeviceId, Error, HandleRange, ReliableWrite,
+    Result, Service, ServicesChangedEvent, Uuid,
+};
 
 /// A Bluetooth LE device
 #[derive(Clone)]
@@ -62,6 +72,44 @@ impl DeviceImpl {
         super::DeviceId(self.peripheral.identifier().to_uuid())
     }
 
+    /// The address of this device if it is a random address, in most significant octet first order
+    ///
+    /// CoreBluetooth does not expose device addresses, so this always returns `None`.
+    pub(crate) async fn random_address(&self) -> Option<[u8; 6]> {
+        None
+    }
+
+    /// Returns `true` if a request is made to an agent with the IO capability `io_capability` when pairing with the
+    /// association model `model`.
+    ///
+    /// CoreBluetooth does not use pairing agents, so every request is made.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn handles_agent_request(_io_capability: IoCapability, _model: AssociationModel) -> bool {
+        true
+    }
+
+    /// Delivers the request made for `model` to `agent`, returning the passkey the agent entered, if any
+    ///
+    /// CoreBluetooth does not use pairing agents, so the agent's methods are called directly.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) async fn simulate_agent_request<T: PairingAgent + ?Sized>(
+        &self,
+        agent: &T,
+        model: AssociationModel,
+        passkey: Passkey,
+    ) -> std::result::Result<Option<Passkey>, PairingRejected> {
+        let device = Device(self.clone());
+        match model {
+            AssociationModel::JustWorks => agent.request_authorization(&device).await.map(|()| None),
+            AssociationModel::NumericComparison => agent.confirm_passkey(&device, passkey).await.map(|()| None),
+            AssociationModel::PasskeyDisplay => {
+                agent.display_passkey(&device, passkey);
+                Ok(None)
+            }
+            AssociationModel::PasskeyInput => agent.request_passkey(&device).await.map(Some),
+        }
+    }
+
     /// The local name for this device, if available
     ///
     /// This can either be a name advertised or read from the device, or a name assigned to the device by the OS.
@@ -101,10 +149,19 @@ impl DeviceImpl {
     ///
     /// Device pairing is performed automatically by the OS when a characteristic requiring security is accessed. This
     /// method is a no-op.
-    pub async fn pair_with_agent<T: PairingAgent>(&self, _agent: &T) -> Result<()> {
+    pub async fn pair_with_agent<T: PairingAgent + ?Sized>(&self, _agent: &T) -> Result<()> {
         Ok(())
     }
 
+    /// Attempt to pair this device using LE Secure Connections out-of-band pairing data received from the device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn pair_with_oob_data(&self, _oob: &OobData) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
     /// Disconnect and unpair this device from the system
     ///
     /// # Platform specific
@@ -114,6 +171,84 @@ impl DeviceImpl {
         Err(ErrorKind::NotSupported.into())
     }
 
+    /// Deletes the bond with this device without disconnecting it.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn remove_bond(&self) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Information about the stored bond with this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn bond_info(&self) -> Result<BondInfo> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Monitors the pairing state of this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn pairing_events(&self) -> Result<Empty<PairingEvent>> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Whether this device is trusted
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn is_trusted(&self) -> Result<bool> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Sets whether this device is trusted.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_trusted(&self, _trusted: bool) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Whether this device is blocked
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn is_blocked(&self) -> Result<bool> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Sets whether this device is blocked.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_blocked(&self, _blocked: bool) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The security level of the connection to this device
+    ///
+    /// Not supported on MacOS/iOS.
+    pub async fn security_level(&self) -> Result<SecurityLevel> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Pairs with this device if necessary to raise the security of the connection to at least `level`.
+    ///
+    /// Device pairing is performed automatically by the OS when a characteristic requiring security is accessed. This
+    /// method is a no-op.
+    pub async fn require_security(&self, _level: SecurityLevel) -> Result<()> {
+        Ok(())
+    }
+
     /// Discover the primary services of this device.
     pub async fn discover_services(&self) -> Result<Vec<Service>> {
         self.discover_services_inner(None).await
@@ -190,6 +325,59 @@ impl DeviceImpl {
         Ok(())
     }
 
+    /// Monitors this device for GATT services changed packets.
+    pub async fn service_changes(&self) -> Result<impl Stream<Item = Result<ServicesChangedEvent>> + '_> {
+        let receiver = self.delegate.sender().subscribe();
+
+        if !self.is_connected().await {
+            return Err(ErrorKind::NotConnected.into());
+        }
+
+        Ok(BroadcastStream::new(receiver).filter_map(|x| {
+            ready(match x {
+                Ok(PeripheralEvent::ServicesChanged { invalidated_services }) => Some(Ok(ServicesChangedEvent {
+                    range: None,
+                    invalidated_services: invalidated_services.iter().map(|x| Service::new(x)).collect(),
+                })),
+                Ok(PeripheralEvent::Disconnected { error }) => {
+                    Some(Err(Error::from_kind_and_nserror(ErrorKind::NotConnected, error)))
+                }
+                Err(err) => Some(Err(Error::from_stream_recv_error(err))),
+                _ => None,
+            })
+        }))
+    }
+
+    /// Asynchronously blocks until a GATT services changed packet is received and returns the affected handle range
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn services_changed_range(&self) -> Result<HandleRange> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Begin a [`ReliableWrite`] transaction on this device.
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub fn reliable_write(&self) -> Result<ReliableWrite> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The ATT MTU negotiated for the connection to this device.
+    ///
+    /// CoreBluetooth does not expose the MTU directly, so it is derived from the maximum write without response length.
+    pub async fn mtu(&self) -> Result<u16> {
+        if !self.is_connected().await {
+            return Err(ErrorKind::NotConnected.into());
+        }
+
+        let len = self
+            .peripheral
+            .maximum_write_value_length_for_type(CBCharacteristicWriteType::WithoutResponse);
+        Ok(u16::try_from(len + 3).unwrap_or(u16::MAX))
+    }
+
     /// Get the current signal strength from the device in dBm.
     pub async fn rssi(&self) -> Result<i16> {
         let mut receiver = self.delegate.sender().subscribe();
@@ -204,4 +392,58 @@ impl DeviceImpl {
             }
         }
     }
+
+    /// The LE connection parameters of the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn connection_parameters(&self) -> Result<ConnectionParameters> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Monitors the LE connection parameters of the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn connection_parameter_changes(&self) -> Result<Empty<Result<ConnectionParameters>>> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The PHYs used by the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn connection_phy(&self) -> Result<ConnectionPhy> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Monitors the PHYs used by the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn connection_phy_changes(&self) -> Result<Empty<Result<ConnectionPhy>>> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Asks the controller to use `phy` for the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_preferred_phy(&self, _phy: ConnectionPhy) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Asks the OS to use connection parameters suited to `priority` for the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn request_connection_priority(&self, _priority: ConnectionPriority) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
 }
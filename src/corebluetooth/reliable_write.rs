@@ -0,0 +1,19 @@
+use crate::{Characteristic, Result};
+
+/// A reliable write transaction
+///
+/// CoreBluetooth does not support reliable writes, so this type cannot be constructed.
+#[derive(Debug)]
+pub enum ReliableWriteImpl {}
+
+impl ReliableWriteImpl {
+    /// Queue a write of `value` to `characteristic` in this transaction.
+    pub fn write(&mut self, _characteristic: &Characteristic, _value: &[u8]) -> Result<()> {
+        match *self {}
+    }
+
+    /// Execute all queued writes atomically.
+    pub async fn commit(self) -> Result<()> {
+        match self {}
+    }
+}
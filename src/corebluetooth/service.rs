@@ -38,6 +38,15 @@ impl ServiceImpl {
         Ok(self.uuid())
     }
 
+    /// The attribute handle of this GATT service
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub fn handle(&self) -> Result<u16> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
     /// Whether this is a primary service of the device.
     pub async fn is_primary(&self) -> Result<bool> {
         Ok(self.inner.is_primary())
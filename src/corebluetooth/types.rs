@@ -207,6 +207,7 @@ impl AdvertisementData {
             service_data,
             tx_power_level,
             is_connectable,
+            encrypted_data: None,
         }
     }
 }
@@ -471,6 +472,11 @@ impl CBPeripheral {
         unsafe { msg_send![self, writeValue: value forCharacteristic: characteristic type: write_type] }
     }
 
+    pub fn maximum_write_value_length_for_type(&self, write_type: CBCharacteristicWriteType) -> NSUInteger {
+        let write_type: isize = write_type as isize;
+        unsafe { msg_send![self, maximumWriteValueLengthForType: write_type] }
+    }
+
     pub fn write_descriptor_value(&self, descriptor: &CBDescriptor, value: &NSData) {
         unsafe { msg_send![self, writeValue: value forDescriptor: descriptor] }
     }
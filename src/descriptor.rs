@@ -1,4 +1,4 @@
-use crate::{sys, Result, Uuid};
+use crate::{sys, CacheMode, Characteristic, Result, Uuid};
 
 /// A Bluetooth GATT descriptor
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,6 +23,25 @@ impl Descriptor {
         self.0.uuid_async().await
     }
 
+    /// The attribute handle of this GATT descriptor
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    ///
+    /// On Linux, BlueZ does not expose attribute handles, so the handle is taken from the D-Bus object path, which
+    /// BlueZ names after the handle.
+    #[inline]
+    pub fn handle(&self) -> Result<u16> {
+        self.0.handle()
+    }
+
+    /// The [`Characteristic`] containing this descriptor
+    #[inline]
+    pub async fn characteristic(&self) -> Result<Characteristic> {
+        self.0.characteristic().await
+    }
+
     /// The cached value of this descriptor
     ///
     /// If the value has not yet been read, this method may either return an error or perform a read of the value.
@@ -37,7 +56,34 @@ impl Descriptor {
         self.0.read().await
     }
 
+    /// Read the value of this descriptor, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`value`][Self::value] for [`CacheMode::Cached`] and [`read`][Self::read] for
+    /// [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn read_with(&self, mode: CacheMode) -> Result<Vec<u8>> {
+        match mode {
+            CacheMode::Cached => self.0.value().await,
+            CacheMode::Uncached => self.0.read().await,
+        }
+    }
+
+    /// Read the value of this descriptor from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Windows if `offset` is not
+    /// zero. Reads at offset zero are equivalent to [`read`][Self::read].
+    #[inline]
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        self.0.read_at(offset).await
+    }
+
     /// Write the value of this descriptor on the device to `value`
+    ///
+    /// Client characteristic configuration descriptors cannot be written directly, and return a
+    /// [`NotAuthorized`][crate::error::ErrorKind::NotAuthorized] error. Use [`Characteristic::set_cccd`] or
+    /// [`Characteristic::notify`] instead.
     #[inline]
     pub async fn write(&self, value: &[u8]) -> Result<()> {
         self.0.write(value).await
@@ -0,0 +1,830 @@
+//! Decoding of the standard GATT characteristic descriptors.
+//!
+//! The descriptors defined in the Bluetooth Core Specification, Vol 3, Part G, §3.3.3 describe how a characteristic's
+//! value should be interpreted or configure how it is reported. This module provides [`GattDecode`] and
+//! [`GattEncode`] implementations for the [extended properties][ExtendedProperties],
+//! [client characteristic configuration][Cccd], [server characteristic configuration][Sccd],
+//! [presentation format][PresentationFormat] and [valid range][ValidRange] descriptors, and helpers on
+//! [`Characteristic`] to find and decode them. The user description descriptor is a plain UTF-8 string.
+//!
+//! ```rust
+//! use bluest::codec::GattDecode;
+//! use bluest::descriptors::{Format, PresentationFormat, Unit};
+//!
+//! // A sint16 in hundredths of a degree Celsius
+//! let format = PresentationFormat::decode(&[0x0e, 0xfe, 0x2f, 0x27, 0x01, 0x00, 0x00]).unwrap();
+//! assert_eq!(format.format, Format::Sint16);
+//! assert_eq!(format.unit, Unit::CelsiusTemperatureDegreeCelsius);
+//! assert_eq!(format.unit.symbol(), Some("°C"));
+//! assert_eq!(format.to_f64(&[0x5a, 0x09]), Ok(23.94));
+//! ```
+
+use crate::btuuid::{bluetooth_uuid_from_u16, descriptors};
+use crate::codec::{Float, GattDecode, GattEncode, InvalidValue, SFloat, Sint24, Uint24, Uint48};
+use crate::error::ErrorKind;
+use crate::{Characteristic, Descriptor, Error, Result, Uuid};
+
+/// The value of a Characteristic Extended Properties descriptor (Vol 3, Part G, §3.3.3.1)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendedProperties {
+    /// The characteristic may be written using reliable writes
+    pub reliable_write: bool,
+    /// The characteristic's user description descriptor is writable
+    pub writable_auxiliaries: bool,
+}
+
+impl GattDecode for ExtendedProperties {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let bits = u16::decode(value)?;
+        Ok(ExtendedProperties {
+            reliable_write: (bits & (1 << 0)) != 0,
+            writable_auxiliaries: (bits & (1 << 1)) != 0,
+        })
+    }
+}
+
+impl GattEncode for ExtendedProperties {
+    fn encode(&self) -> Vec<u8> {
+        (u16::from(self.reliable_write) | (u16::from(self.writable_auxiliaries) << 1)).encode()
+    }
+}
+
+/// The value of a Client Characteristic Configuration descriptor (CCCD) (Vol 3, Part G, §3.3.3.3)
+///
+/// See [`Characteristic::cccd`] and [`Characteristic::set_cccd`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cccd {
+    /// The device sends notifications of the characteristic value
+    pub notify: bool,
+    /// The device sends indications of the characteristic value
+    pub indicate: bool,
+}
+
+impl GattDecode for Cccd {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let bits = u16::decode(value)?;
+        Ok(Cccd {
+            notify: (bits & (1 << 0)) != 0,
+            indicate: (bits & (1 << 1)) != 0,
+        })
+    }
+}
+
+impl GattEncode for Cccd {
+    fn encode(&self) -> Vec<u8> {
+        (u16::from(self.notify) | (u16::from(self.indicate) << 1)).encode()
+    }
+}
+
+/// The value of a Server Characteristic Configuration descriptor (SCCD) (Vol 3, Part G, §3.3.3.4)
+///
+/// See [`Characteristic::sccd`] and [`Characteristic::set_sccd`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sccd {
+    /// The device includes the characteristic value in its advertising data
+    pub broadcast: bool,
+}
+
+impl GattDecode for Sccd {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let bits = u16::decode(value)?;
+        Ok(Sccd {
+            broadcast: (bits & (1 << 0)) != 0,
+        })
+    }
+}
+
+impl GattEncode for Sccd {
+    fn encode(&self) -> Vec<u8> {
+        u16::from(self.broadcast).encode()
+    }
+}
+
+/// The value of a Characteristic Presentation Format descriptor (Vol 3, Part G, §3.3.3.5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresentationFormat {
+    /// The format of the characteristic value
+    pub format: Format,
+    /// The base 10 exponent applied to integer values, so the actual value is `value * 10^exponent`
+    pub exponent: i8,
+    /// The unit of the characteristic value
+    pub unit: Unit,
+    /// The organization which defines [`description`][Self::description], `0x01` for the Bluetooth SIG
+    pub namespace: u8,
+    /// A description of the characteristic, as defined by [`namespace`][Self::namespace]
+    pub description: u16,
+}
+
+impl PresentationFormat {
+    /// Decodes a characteristic `value` with a numeric [`format`][Self::format] and applies the
+    /// [`exponent`][Self::exponent].
+    ///
+    /// The exponent only applies to integer formats. Floating point values are returned unchanged, and IEEE-11073
+    /// values carry their own exponent. Returns an error if the format is not numeric or `value` has the wrong length.
+    pub fn to_f64(&self, value: &[u8]) -> Result<f64, InvalidValue> {
+        // Divide by positive powers of ten rather than multiplying by negative ones, which are inexact
+        let int = |x: f64| match self.exponent {
+            e if e < 0 => x / 10f64.powi(-i32::from(e)),
+            e => x * 10f64.powi(i32::from(e)),
+        };
+        match self.format {
+            // Values narrower than a byte are stored in the low bits
+            Format::Boolean => u8::decode(value).map(|x| int((x & 0x01).into())),
+            Format::Uint2 => u8::decode(value).map(|x| int((x & 0x03).into())),
+            Format::Uint4 => u8::decode(value).map(|x| int((x & 0x0f).into())),
+            Format::Uint8 => u8::decode(value).map(|x| int(x.into())),
+            Format::Uint12 => u16::decode(value).map(|x| int((x & 0x0fff).into())),
+            Format::Uint16 => u16::decode(value).map(|x| int(x.into())),
+            Format::Uint24 => Uint24::decode(value).map(|x| int(u32::from(x).into())),
+            Format::Uint32 => u32::decode(value).map(|x| int(x.into())),
+            Format::Uint48 => Uint48::decode(value).map(|x| int(u64::from(x) as f64)),
+            Format::Uint64 => u64::decode(value).map(|x| int(x as f64)),
+            Format::Uint128 => u128::decode(value).map(|x| int(x as f64)),
+            Format::Sint8 => i8::decode(value).map(|x| int(x.into())),
+            // Shift the 12-bit value to the top of the word and back down to sign-extend it
+            Format::Sint12 => i16::decode(value).map(|x| int(((x << 4) >> 4).into())),
+            Format::Sint16 => i16::decode(value).map(|x| int(x.into())),
+            Format::Sint24 => Sint24::decode(value).map(|x| int(i32::from(x).into())),
+            Format::Sint32 => i32::decode(value).map(|x| int(x.into())),
+            Format::Sint48 => {
+                let [a, b, c, d, e, f]: [u8; 6] = value.try_into().map_err(|_| InvalidValue(()))?;
+                Ok(int((i64::from_le_bytes([0, 0, a, b, c, d, e, f]) >> 16) as f64))
+            }
+            Format::Sint64 => i64::decode(value).map(|x| int(x as f64)),
+            Format::Sint128 => i128::decode(value).map(|x| int(x as f64)),
+            Format::Float32 => u32::decode(value).map(|x| f32::from_bits(x).into()),
+            Format::Float64 => u64::decode(value).map(f64::from_bits),
+            Format::SFloat => SFloat::decode(value).map(f64::from),
+            Format::Float => Float::decode(value).map(f64::from),
+            Format::Duint16 | Format::Utf8 | Format::Utf16 | Format::Struct | Format::Unknown(_) => {
+                Err(InvalidValue(()))
+            }
+        }
+    }
+}
+
+impl GattDecode for PresentationFormat {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        match *value {
+            [format, exponent, u0, u1, namespace, d0, d1] => Ok(PresentationFormat {
+                format: format.into(),
+                exponent: exponent as i8,
+                unit: u16::from_le_bytes([u0, u1]).into(),
+                namespace,
+                description: u16::from_le_bytes([d0, d1]),
+            }),
+            _ => Err(InvalidValue(())),
+        }
+    }
+}
+
+impl GattEncode for PresentationFormat {
+    fn encode(&self) -> Vec<u8> {
+        let mut value = vec![self.format.into(), self.exponent as u8];
+        value.extend_from_slice(&u16::from(self.unit).to_le_bytes());
+        value.push(self.namespace);
+        value.extend_from_slice(&self.description.to_le_bytes());
+        value
+    }
+}
+
+/// The value of a Valid Range descriptor
+///
+/// The bounds are encoded in the same format as the characteristic value. `ValidRange<Vec<u8>>` holds the raw
+/// bounds, which can be decoded with [`decode_as`][ValidRange::decode_as] once the format is known.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidRange<T = Vec<u8>> {
+    /// The lower inclusive bound
+    pub lower: T,
+    /// The upper inclusive bound
+    pub upper: T,
+}
+
+impl ValidRange {
+    /// Decodes the raw bounds as a `T`
+    pub fn decode_as<T: GattDecode>(&self) -> Result<ValidRange<T>, InvalidValue> {
+        Ok(ValidRange {
+            lower: T::decode(&self.lower)?,
+            upper: T::decode(&self.upper)?,
+        })
+    }
+}
+
+impl<T: GattDecode> GattDecode for ValidRange<T> {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        let (lower, upper) = value.split_at(value.len() / 2);
+        if lower.len() != upper.len() {
+            return Err(InvalidValue(()));
+        }
+        Ok(ValidRange {
+            lower: T::decode(lower)?,
+            upper: T::decode(upper)?,
+        })
+    }
+}
+
+impl<T: GattEncode> GattEncode for ValidRange<T> {
+    fn encode(&self) -> Vec<u8> {
+        let mut value = self.lower.encode();
+        value.extend(self.upper.encode());
+        value
+    }
+}
+
+/// The format of a characteristic value, as used by [`PresentationFormat`]
+///
+/// The names of the formats match the GATT Specification Supplement.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+    /// Unsigned 1-bit, `0` is false and `1` is true
+    Boolean,
+    /// Unsigned 2-bit integer
+    Uint2,
+    /// Unsigned 4-bit integer
+    Uint4,
+    /// Unsigned 8-bit integer
+    Uint8,
+    /// Unsigned 12-bit integer
+    Uint12,
+    /// Unsigned 16-bit integer
+    Uint16,
+    /// Unsigned 24-bit integer
+    Uint24,
+    /// Unsigned 32-bit integer
+    Uint32,
+    /// Unsigned 48-bit integer
+    Uint48,
+    /// Unsigned 64-bit integer
+    Uint64,
+    /// Unsigned 128-bit integer
+    Uint128,
+    /// Signed 8-bit integer
+    Sint8,
+    /// Signed 12-bit integer
+    Sint12,
+    /// Signed 16-bit integer
+    Sint16,
+    /// Signed 24-bit integer
+    Sint24,
+    /// Signed 32-bit integer
+    Sint32,
+    /// Signed 48-bit integer
+    Sint48,
+    /// Signed 64-bit integer
+    Sint64,
+    /// Signed 128-bit integer
+    Sint128,
+    /// IEEE-754 32-bit floating point
+    Float32,
+    /// IEEE-754 64-bit floating point
+    Float64,
+    /// IEEE-11073 16-bit floating point, see [`SFloat`]
+    SFloat,
+    /// IEEE-11073 32-bit floating point, see [`Float`]
+    Float,
+    /// Two unsigned 16-bit integers
+    Duint16,
+    /// UTF-8 string
+    Utf8,
+    /// UTF-16 string
+    Utf16,
+    /// Opaque structure
+    Struct,
+    /// A reserved format value
+    Unknown(u8),
+}
+
+impl Format {
+    /// The length in bytes of a value in this format, or `None` for variable length formats
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Format::Boolean | Format::Uint2 | Format::Uint4 | Format::Uint8 | Format::Sint8 => Some(1),
+            Format::Uint12 | Format::Uint16 | Format::Sint12 | Format::Sint16 | Format::SFloat => Some(2),
+            Format::Uint24 | Format::Sint24 => Some(3),
+            Format::Uint32 | Format::Sint32 | Format::Float32 | Format::Float | Format::Duint16 => Some(4),
+            Format::Uint48 | Format::Sint48 => Some(6),
+            Format::Uint64 | Format::Sint64 | Format::Float64 => Some(8),
+            Format::Uint128 | Format::Sint128 => Some(16),
+            Format::Utf8 | Format::Utf16 | Format::Struct | Format::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<u8> for Format {
+    fn from(val: u8) -> Self {
+        match val {
+            0x01 => Format::Boolean,
+            0x02 => Format::Uint2,
+            0x03 => Format::Uint4,
+            0x04 => Format::Uint8,
+            0x05 => Format::Uint12,
+            0x06 => Format::Uint16,
+            0x07 => Format::Uint24,
+            0x08 => Format::Uint32,
+            0x09 => Format::Uint48,
+            0x0a => Format::Uint64,
+            0x0b => Format::Uint128,
+            0x0c => Format::Sint8,
+            0x0d => Format::Sint12,
+            0x0e => Format::Sint16,
+            0x0f => Format::Sint24,
+            0x10 => Format::Sint32,
+            0x11 => Format::Sint48,
+            0x12 => Format::Sint64,
+            0x13 => Format::Sint128,
+            0x14 => Format::Float32,
+            0x15 => Format::Float64,
+            0x16 => Format::SFloat,
+            0x17 => Format::Float,
+            0x18 => Format::Duint16,
+            0x19 => Format::Utf8,
+            0x1a => Format::Utf16,
+            0x1b => Format::Struct,
+            _ => Format::Unknown(val),
+        }
+    }
+}
+
+impl From<Format> for u8 {
+    fn from(val: Format) -> Self {
+        match val {
+            Format::Boolean => 0x01,
+            Format::Uint2 => 0x02,
+            Format::Uint4 => 0x03,
+            Format::Uint8 => 0x04,
+            Format::Uint12 => 0x05,
+            Format::Uint16 => 0x06,
+            Format::Uint24 => 0x07,
+            Format::Uint32 => 0x08,
+            Format::Uint48 => 0x09,
+            Format::Uint64 => 0x0a,
+            Format::Uint128 => 0x0b,
+            Format::Sint8 => 0x0c,
+            Format::Sint12 => 0x0d,
+            Format::Sint16 => 0x0e,
+            Format::Sint24 => 0x0f,
+            Format::Sint32 => 0x10,
+            Format::Sint48 => 0x11,
+            Format::Sint64 => 0x12,
+            Format::Sint128 => 0x13,
+            Format::Float32 => 0x14,
+            Format::Float64 => 0x15,
+            Format::SFloat => 0x16,
+            Format::Float => 0x17,
+            Format::Duint16 => 0x18,
+            Format::Utf8 => 0x19,
+            Format::Utf16 => 0x1a,
+            Format::Struct => 0x1b,
+            Format::Unknown(x) => x,
+        }
+    }
+}
+
+macro_rules! units {
+    ($($variant:ident = $value:literal, $name:literal, $symbol:literal;)*) => {
+        /// The unit of a characteristic value, as used by [`PresentationFormat`]
+        ///
+        /// Units are assigned 16-bit UUIDs in the Bluetooth Assigned Numbers document. The variants are named after
+        /// the quantity and unit they represent.
+        #[non_exhaustive]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum Unit {
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+            /// A unit not known to this crate
+            Unknown(u16),
+        }
+
+        impl Unit {
+            /// The name of this unit from the Bluetooth Assigned Numbers document, or `None` if it is unknown
+            pub fn name(&self) -> Option<&'static str> {
+                match self {
+                    $(Unit::$variant => Some($name),)*
+                    Unit::Unknown(_) => None,
+                }
+            }
+
+            /// The symbol used when displaying a value in this unit, or `None` if it is unknown
+            ///
+            /// Dimensionless units such as [`Unitless`][Unit::Unitless] have an empty symbol.
+            pub fn symbol(&self) -> Option<&'static str> {
+                match self {
+                    $(Unit::$variant => Some($symbol),)*
+                    Unit::Unknown(_) => None,
+                }
+            }
+        }
+
+        impl From<u16> for Unit {
+            fn from(val: u16) -> Self {
+                match val {
+                    $($value => Unit::$variant,)*
+                    _ => Unit::Unknown(val),
+                }
+            }
+        }
+
+        impl From<Unit> for u16 {
+            fn from(val: Unit) -> Self {
+                match val {
+                    $(Unit::$variant => $value,)*
+                    Unit::Unknown(x) => x,
+                }
+            }
+        }
+    };
+}
+
+units! {
+    Unitless = 0x2700, "unitless", "";
+    LengthMetre = 0x2701, "length (metre)", "m";
+    MassKilogram = 0x2702, "mass (kilogram)", "kg";
+    TimeSecond = 0x2703, "time (second)", "s";
+    ElectricCurrentAmpere = 0x2704, "electric current (ampere)", "A";
+    ThermodynamicTemperatureKelvin = 0x2705, "thermodynamic temperature (kelvin)", "K";
+    AmountOfSubstanceMole = 0x2706, "amount of substance (mole)", "mol";
+    LuminousIntensityCandela = 0x2707, "luminous intensity (candela)", "cd";
+    AreaSquareMetres = 0x2710, "area (square metres)", "m²";
+    VolumeCubicMetres = 0x2711, "volume (cubic metres)", "m³";
+    VelocityMetresPerSecond = 0x2712, "velocity (metres per second)", "m/s";
+    AccelerationMetresPerSecondSquared = 0x2713, "acceleration (metres per second squared)", "m/s²";
+    WavenumberReciprocalMetre = 0x2714, "wavenumber (reciprocal metre)", "m⁻¹";
+    DensityKilogramPerCubicMetre = 0x2715, "density (kilogram per cubic metre)", "kg/m³";
+    SurfaceDensityKilogramPerSquareMetre = 0x2716, "surface density (kilogram per square metre)", "kg/m²";
+    SpecificVolumeCubicMetrePerKilogram = 0x2717, "specific volume (cubic metre per kilogram)", "m³/kg";
+    CurrentDensityAmperePerSquareMetre = 0x2718, "current density (ampere per square metre)", "A/m²";
+    MagneticFieldStrengthAmperePerMetre = 0x2719, "magnetic field strength (ampere per metre)", "A/m";
+    AmountConcentrationMolePerCubicMetre = 0x271a, "amount concentration (mole per cubic metre)", "mol/m³";
+    MassConcentrationKilogramPerCubicMetre = 0x271b, "mass concentration (kilogram per cubic metre)", "kg/m³";
+    LuminanceCandelaPerSquareMetre = 0x271c, "luminance (candela per square metre)", "cd/m²";
+    RefractiveIndex = 0x271d, "refractive index", "";
+    RelativePermeability = 0x271e, "relative permeability", "";
+    PlaneAngleRadian = 0x2720, "plane angle (radian)", "rad";
+    SolidAngleSteradian = 0x2721, "solid angle (steradian)", "sr";
+    FrequencyHertz = 0x2722, "frequency (hertz)", "Hz";
+    ForceNewton = 0x2723, "force (newton)", "N";
+    PressurePascal = 0x2724, "pressure (pascal)", "Pa";
+    EnergyJoule = 0x2725, "energy (joule)", "J";
+    PowerWatt = 0x2726, "power (watt)", "W";
+    ElectricChargeCoulomb = 0x2727, "electric charge (coulomb)", "C";
+    ElectricPotentialDifferenceVolt = 0x2728, "electric potential difference (volt)", "V";
+    CapacitanceFarad = 0x2729, "capacitance (farad)", "F";
+    ElectricResistanceOhm = 0x272a, "electric resistance (ohm)", "Ω";
+    ElectricConductanceSiemens = 0x272b, "electric conductance (siemens)", "S";
+    MagneticFluxWeber = 0x272c, "magnetic flux (weber)", "Wb";
+    MagneticFluxDensityTesla = 0x272d, "magnetic flux density (tesla)", "T";
+    InductanceHenry = 0x272e, "inductance (henry)", "H";
+    CelsiusTemperatureDegreeCelsius = 0x272f, "Celsius temperature (degree Celsius)", "°C";
+    LuminousFluxLumen = 0x2730, "luminous flux (lumen)", "lm";
+    IlluminanceLux = 0x2731, "illuminance (lux)", "lx";
+    ActivityReferredToARadionuclideBecquerel = 0x2732, "activity referred to a radionuclide (becquerel)", "Bq";
+    AbsorbedDoseGray = 0x2733, "absorbed dose (gray)", "Gy";
+    DoseEquivalentSievert = 0x2734, "dose equivalent (sievert)", "Sv";
+    CatalyticActivityKatal = 0x2735, "catalytic activity (katal)", "kat";
+    DynamicViscosityPascalSecond = 0x2740, "dynamic viscosity (pascal second)", "Pa·s";
+    MomentOfForceNewtonMetre = 0x2741, "moment of force (newton metre)", "N·m";
+    SurfaceTensionNewtonPerMetre = 0x2742, "surface tension (newton per metre)", "N/m";
+    AngularVelocityRadianPerSecond = 0x2743, "angular velocity (radian per second)", "rad/s";
+    AngularAccelerationRadianPerSecondSquared = 0x2744, "angular acceleration (radian per second squared)", "rad/s²";
+    HeatFluxDensityWattPerSquareMetre = 0x2745, "heat flux density (watt per square metre)", "W/m²";
+    HeatCapacityJoulePerKelvin = 0x2746, "heat capacity (joule per kelvin)", "J/K";
+    SpecificHeatCapacityJoulePerKilogramKelvin = 0x2747, "specific heat capacity (joule per kilogram kelvin)", "J/(kg·K)";
+    SpecificEnergyJoulePerKilogram = 0x2748, "specific energy (joule per kilogram)", "J/kg";
+    ThermalConductivityWattPerMetreKelvin = 0x2749, "thermal conductivity (watt per metre kelvin)", "W/(m·K)";
+    EnergyDensityJoulePerCubicMetre = 0x274a, "energy density (joule per cubic metre)", "J/m³";
+    ElectricFieldStrengthVoltPerMetre = 0x274b, "electric field strength (volt per metre)", "V/m";
+    ElectricChargeDensityCoulombPerCubicMetre = 0x274c, "electric charge density (coulomb per cubic metre)", "C/m³";
+    SurfaceChargeDensityCoulombPerSquareMetre = 0x274d, "surface charge density (coulomb per square metre)", "C/m²";
+    ElectricFluxDensityCoulombPerSquareMetre = 0x274e, "electric flux density (coulomb per square metre)", "C/m²";
+    PermittivityFaradPerMetre = 0x274f, "permittivity (farad per metre)", "F/m";
+    PermeabilityHenryPerMetre = 0x2750, "permeability (henry per metre)", "H/m";
+    MolarEnergyJoulePerMole = 0x2751, "molar energy (joule per mole)", "J/mol";
+    MolarEntropyJoulePerMoleKelvin = 0x2752, "molar entropy (joule per mole kelvin)", "J/(mol·K)";
+    ExposureCoulombPerKilogram = 0x2753, "exposure (coulomb per kilogram)", "C/kg";
+    AbsorbedDoseRateGrayPerSecond = 0x2754, "absorbed dose rate (gray per second)", "Gy/s";
+    RadiantIntensityWattPerSteradian = 0x2755, "radiant intensity (watt per steradian)", "W/sr";
+    RadianceWattPerSquareMetreSteradian = 0x2756, "radiance (watt per square metre steradian)", "W/(m²·sr)";
+    CatalyticActivityConcentrationKatalPerCubicMetre = 0x2757, "catalytic activity concentration (katal per cubic metre)", "kat/m³";
+    TimeMinute = 0x2760, "time (minute)", "min";
+    TimeHour = 0x2761, "time (hour)", "h";
+    TimeDay = 0x2762, "time (day)", "d";
+    PlaneAngleDegree = 0x2763, "plane angle (degree)", "°";
+    PlaneAngleMinute = 0x2764, "plane angle (minute)", "′";
+    PlaneAngleSecond = 0x2765, "plane angle (second)", "″";
+    AreaHectare = 0x2766, "area (hectare)", "ha";
+    VolumeLitre = 0x2767, "volume (litre)", "L";
+    MassTonne = 0x2768, "mass (tonne)", "t";
+    PressureBar = 0x2780, "pressure (bar)", "bar";
+    PressureMillimetreOfMercury = 0x2781, "pressure (millimetre of mercury)", "mmHg";
+    LengthAngstrom = 0x2782, "length (ångström)", "Å";
+    LengthNauticalMile = 0x2783, "length (nautical mile)", "NM";
+    AreaBarn = 0x2784, "area (barn)", "b";
+    VelocityKnot = 0x2785, "velocity (knot)", "kn";
+    LogarithmicRadioQuantityNeper = 0x2786, "logarithmic radio quantity (neper)", "Np";
+    LogarithmicRadioQuantityBel = 0x2787, "logarithmic radio quantity (bel)", "B";
+    LengthYard = 0x27a0, "length (yard)", "yd";
+    LengthParsec = 0x27a1, "length (parsec)", "pc";
+    LengthInch = 0x27a2, "length (inch)", "in";
+    LengthFoot = 0x27a3, "length (foot)", "ft";
+    LengthMile = 0x27a4, "length (mile)", "mi";
+    PressurePoundForcePerSquareInch = 0x27a5, "pressure (pound-force per square inch)", "psi";
+    VelocityKilometrePerHour = 0x27a6, "velocity (kilometre per hour)", "km/h";
+    VelocityMilePerHour = 0x27a7, "velocity (mile per hour)", "mph";
+    AngularVelocityRevolutionPerMinute = 0x27a8, "angular velocity (revolution per minute)", "rpm";
+    EnergyGramCalorie = 0x27a9, "energy (gram calorie)", "cal";
+    EnergyKilogramCalorie = 0x27aa, "energy (kilogram calorie)", "kcal";
+    EnergyKilowattHour = 0x27ab, "energy (kilowatt hour)", "kWh";
+    ThermodynamicTemperatureDegreeFahrenheit = 0x27ac, "thermodynamic temperature (degree Fahrenheit)", "°F";
+    Percentage = 0x27ad, "percentage", "%";
+    PerMille = 0x27ae, "per mille", "‰";
+    PeriodBeatsPerMinute = 0x27af, "period (beats per minute)", "bpm";
+    ElectricChargeAmpereHours = 0x27b0, "electric charge (ampere hours)", "Ah";
+    MassDensityMilligramPerDecilitre = 0x27b1, "mass density (milligram per decilitre)", "mg/dL";
+    MassDensityMillimolePerLitre = 0x27b2, "mass density (millimole per litre)", "mmol/L";
+    TimeYear = 0x27b3, "time (year)", "y";
+    TimeMonth = 0x27b4, "time (month)", "mo";
+    ConcentrationCountPerCubicMetre = 0x27b5, "concentration (count per cubic metre)", "m⁻³";
+    IrradianceWattPerSquareMetre = 0x27b6, "irradiance (watt per square metre)", "W/m²";
+    MillilitrePerKilogramPerMinute = 0x27b7, "milliliter (per kilogram per minute)", "mL/(kg·min)";
+    MassPound = 0x27b8, "mass (pound)", "lb";
+    MetabolicEquivalent = 0x27b9, "metabolic equivalent", "MET";
+    StepPerMinute = 0x27ba, "step (per minute)", "steps/min";
+    StrokePerMinute = 0x27bc, "stroke (per minute)", "strokes/min";
+    PaceKilometrePerMinute = 0x27bd, "pace (kilometre per minute)", "km/min";
+    LuminousEfficacyLumenPerWatt = 0x27be, "luminous efficacy (lumen per watt)", "lm/W";
+    LuminousEnergyLumenHour = 0x27bf, "luminous energy (lumen hour)", "lm·h";
+    LuminousExposureLuxHour = 0x27c0, "luminous exposure (lux hour)", "lx·h";
+    MassFlowGramPerSecond = 0x27c1, "mass flow (gram per second)", "g/s";
+    VolumeFlowLitrePerSecond = 0x27c2, "volume flow (litre per second)", "L/s";
+    SoundPressureDecibel = 0x27c3, "sound pressure (decibel)", "dB";
+    PartsPerMillion = 0x27c4, "parts per million", "ppm";
+    PartsPerBillion = 0x27c5, "parts per billion", "ppb";
+    MassDensityRateMilligramPerDecilitrePerMinute = 0x27c6, "mass density rate ((milligram per decilitre) per minute)", "mg/dL/min";
+    ElectricalApparentEnergyKilovoltAmpereHour = 0x27c7, "electrical apparent energy (kilovolt ampere hour)", "kVAh";
+    ElectricalApparentPowerVoltAmpere = 0x27c8, "electrical apparent power (volt ampere)", "VA";
+}
+
+impl Unit {
+    /// The [`Uuid`] assigned to this unit
+    pub fn uuid(&self) -> Uuid {
+        bluetooth_uuid_from_u16((*self).into())
+    }
+}
+
+impl Characteristic {
+    async fn find_descriptor(&self, uuid: Uuid) -> Result<Option<Descriptor>> {
+        for descriptor in self.discover_descriptors().await? {
+            if descriptor.uuid_async().await? == uuid {
+                return Ok(Some(descriptor));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads this characteristic's server characteristic configuration descriptor (SCCD), returning `None` if it has
+    /// none.
+    pub async fn sccd(&self) -> Result<Option<Sccd>> {
+        match self
+            .find_descriptor(descriptors::SERVER_CHARACTERISTIC_CONFIGURATION)
+            .await?
+        {
+            Some(descriptor) => Ok(Some(Sccd::decode(&descriptor.read().await?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes this characteristic's server characteristic configuration descriptor (SCCD).
+    ///
+    /// Returns a [`NotFound`][crate::error::ErrorKind::NotFound] error if the characteristic has no SCCD.
+    pub async fn set_sccd(&self, sccd: Sccd) -> Result<()> {
+        match self
+            .find_descriptor(descriptors::SERVER_CHARACTERISTIC_CONFIGURATION)
+            .await?
+        {
+            Some(descriptor) => descriptor.write(&sccd.encode()).await,
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                None,
+                "server characteristic configuration descriptor not found".to_string(),
+            )),
+        }
+    }
+
+    /// Reads and decodes this characteristic's presentation format descriptor, returning `None` if it has none.
+    ///
+    /// A characteristic whose value is an aggregate of several fields may have more than one presentation format
+    /// descriptor, in which case the first is returned.
+    pub async fn presentation_format(&self) -> Result<Option<PresentationFormat>> {
+        match self
+            .find_descriptor(descriptors::CHARACTERISTIC_PRESENTATION_FORMAT)
+            .await?
+        {
+            Some(descriptor) => Ok(Some(PresentationFormat::decode(&descriptor.read().await?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads this characteristic's user description descriptor, returning `None` if it has none.
+    pub async fn user_description(&self) -> Result<Option<String>> {
+        match self
+            .find_descriptor(descriptors::CHARACTERISTIC_USER_DESCRIPTION)
+            .await?
+        {
+            Some(descriptor) => Ok(Some(String::decode(&descriptor.read().await?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads this characteristic's valid range descriptor, returning `None` if it has none.
+    ///
+    /// The bounds are returned undecoded. Use [`ValidRange::decode_as`] to decode them in the format of the
+    /// characteristic value.
+    pub async fn valid_range(&self) -> Result<Option<ValidRange>> {
+        match self.find_descriptor(descriptors::VALID_RANGE).await? {
+            Some(descriptor) => Ok(Some(ValidRange::decode(&descriptor.read().await?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(format: Format, exponent: i8) -> PresentationFormat {
+        PresentationFormat {
+            format,
+            exponent,
+            unit: Unit::Unitless,
+            namespace: 0x01,
+            description: 0x0000,
+        }
+    }
+
+    #[test]
+    fn presentation_format() {
+        let value = [0x06, 0xfe, 0xad, 0x27, 0x01, 0x0a, 0x01];
+        let decoded = PresentationFormat::decode(&value).unwrap();
+        assert_eq!(
+            decoded,
+            PresentationFormat {
+                format: Format::Uint16,
+                exponent: -2,
+                unit: Unit::Percentage,
+                namespace: 0x01,
+                description: 0x010a,
+            }
+        );
+        assert_eq!(decoded.encode(), value);
+
+        let unknown = PresentationFormat::decode(&[0xf0, 0x00, 0x34, 0x12, 0x00, 0x00, 0x00]).unwrap();
+        assert_eq!(unknown.format, Format::Unknown(0xf0));
+        assert_eq!(unknown.unit, Unit::Unknown(0x1234));
+        assert_eq!(unknown.unit.name(), None);
+
+        assert!(PresentationFormat::decode(&value[..6]).is_err());
+        assert!(PresentationFormat::decode(&[value.as_slice(), &[0]].concat()).is_err());
+    }
+
+    #[test]
+    fn format_values() {
+        for x in 0..=u8::MAX {
+            let format = Format::from(x);
+            assert_eq!(u8::from(format), x);
+            assert_eq!(matches!(format, Format::Unknown(_)), x == 0 || x > 0x1b);
+        }
+        assert_eq!(Format::Boolean.size(), Some(1));
+        assert_eq!(Format::Sint12.size(), Some(2));
+        assert_eq!(Format::Uint48.size(), Some(6));
+        assert_eq!(Format::Duint16.size(), Some(4));
+        assert_eq!(Format::Utf8.size(), None);
+    }
+
+    #[test]
+    fn unit_values() {
+        assert_eq!(Unit::from(0x272f), Unit::CelsiusTemperatureDegreeCelsius);
+        assert_eq!(u16::from(Unit::ElectricalApparentPowerVoltAmpere), 0x27c8);
+        assert_eq!(Unit::Percentage.symbol(), Some("%"));
+        assert_eq!(Unit::Unitless.symbol(), Some(""));
+        assert_eq!(Unit::PowerWatt.uuid(), bluetooth_uuid_from_u16(0x2726));
+        assert_eq!(Unit::from(0x2708), Unit::Unknown(0x2708));
+    }
+
+    #[test]
+    fn sub_byte_values_are_masked() {
+        assert_eq!(format(Format::Boolean, 0).to_f64(&[0xff]), Ok(1.0));
+        assert_eq!(format(Format::Boolean, 0).to_f64(&[0xfe]), Ok(0.0));
+        assert_eq!(format(Format::Uint2, 0).to_f64(&[0xff]), Ok(3.0));
+        assert_eq!(format(Format::Uint4, 0).to_f64(&[0xf5]), Ok(5.0));
+        assert_eq!(format(Format::Uint8, 0).to_f64(&[0xf5]), Ok(245.0));
+        assert_eq!(format(Format::Uint12, 0).to_f64(&[0xff, 0xff]), Ok(4095.0));
+    }
+
+    #[test]
+    fn integer_values() {
+        assert_eq!(format(Format::Uint16, -2).to_f64(&[0x5a, 0x09]), Ok(23.94));
+        assert_eq!(format(Format::Uint16, 2).to_f64(&[0x05, 0x00]), Ok(500.0));
+        assert_eq!(format(Format::Sint12, 0).to_f64(&[0xff, 0x0f]), Ok(-1.0));
+        assert_eq!(format(Format::Sint12, 0).to_f64(&[0xff, 0x07]), Ok(2047.0));
+        assert_eq!(format(Format::Sint24, 0).to_f64(&[0x00, 0x00, 0x80]), Ok(-8388608.0));
+        assert_eq!(
+            format(Format::Uint48, 0).to_f64(&[0, 0, 0, 0, 0, 0x01]),
+            Ok(1099511627776.0)
+        );
+        assert_eq!(
+            format(Format::Sint48, 0).to_f64(&[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Ok(-2.0)
+        );
+        assert_eq!(format(Format::Sint8, 1).to_f64(&[0xfb]), Ok(-50.0));
+        assert!(format(Format::Uint16, 0).to_f64(&[0x00]).is_err());
+        assert!(format(Format::Sint48, 0).to_f64(&[0; 5]).is_err());
+    }
+
+    #[test]
+    fn float_values() {
+        // The exponent does not apply to floating point formats
+        assert_eq!(format(Format::Float32, 3).to_f64(&1.5f32.to_le_bytes()), Ok(1.5));
+        assert_eq!(format(Format::Float64, 0).to_f64(&(-0.25f64).to_le_bytes()), Ok(-0.25));
+        assert_eq!(format(Format::SFloat, 3).to_f64(&[0x02, 0x10]), Ok(20.0));
+        assert_eq!(format(Format::Float, 0).to_f64(&[0x05, 0x00, 0x00, 0x00]), Ok(5.0));
+    }
+
+    #[test]
+    fn non_numeric_formats() {
+        for f in [
+            Format::Utf8,
+            Format::Utf16,
+            Format::Struct,
+            Format::Duint16,
+            Format::Unknown(0xff),
+        ] {
+            assert!(format(f, 0).to_f64(&[0, 0, 0, 0]).is_err());
+        }
+    }
+
+    #[test]
+    fn valid_range() {
+        let range = ValidRange::<u16>::decode(&[0x01, 0x00, 0xe8, 0x03]).unwrap();
+        assert_eq!(range, ValidRange { lower: 1, upper: 1000 });
+        assert_eq!(range.encode(), vec![0x01, 0x00, 0xe8, 0x03]);
+
+        let raw = ValidRange::<Vec<u8>>::decode(&[0xff, 0x7f, 0x00, 0x80]).unwrap();
+        assert_eq!(raw.lower, vec![0xff, 0x7f]);
+        assert_eq!(
+            raw.decode_as::<i16>(),
+            Ok(ValidRange {
+                lower: 0x7fff,
+                upper: -0x8000
+            })
+        );
+        assert!(raw.decode_as::<u8>().is_err());
+
+        assert!(ValidRange::<Vec<u8>>::decode(&[0x01, 0x02, 0x03]).is_err());
+        assert!(ValidRange::<u16>::decode(&[0x01, 0x02]).is_err());
+        assert_eq!(
+            ValidRange::decode(&[]),
+            Ok(ValidRange {
+                lower: Vec::new(),
+                upper: Vec::new()
+            })
+        );
+    }
+
+    #[test]
+    fn configuration_descriptors() {
+        assert_eq!(
+            Cccd::decode(&[0x03, 0x00]),
+            Ok(Cccd {
+                notify: true,
+                indicate: true
+            })
+        );
+        assert_eq!(
+            Cccd {
+                notify: false,
+                indicate: true
+            }
+            .encode(),
+            vec![0x02, 0x00]
+        );
+        assert_eq!(Sccd::decode(&[0x01, 0x00]), Ok(Sccd { broadcast: true }));
+        assert_eq!(
+            ExtendedProperties::decode(&[0x02, 0x00]),
+            Ok(ExtendedProperties {
+                reliable_write: false,
+                writable_auxiliaries: true
+            })
+        );
+        assert!(Cccd::decode(&[0x01]).is_err());
+    }
+}
@@ -1,7 +1,17 @@
 #![allow(clippy::let_unit_value)]
 
-use crate::pairing::PairingAgent;
-use crate::{sys, DeviceId, Result, Service, Uuid};
+use std::sync::Arc;
+
+use futures_util::future::try_join_all;
+use futures_util::Stream;
+
+use crate::error::ErrorKind;
+use crate::gatt_database::GattDatabase;
+use crate::pairing::{AutoPairGuard, BondInfo, OobData, PairingAgent, PairingEvent, SecurityLevel};
+use crate::{
+    btuuid, sys, CacheMode, Characteristic, ConnectionParameters, ConnectionPhy, ConnectionPriority, DeviceId, Error,
+    HandleRange, ReliableWrite, Result, Service, ServicesChangedEvent, Uuid,
+};
 
 /// A Bluetooth LE device
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -56,6 +66,11 @@ impl Device {
 
     /// Attempt to pair this device using the system default pairing UI
     ///
+    /// Pairing failures reported by the OS are returned as errors with the kind
+    /// [`PairingFailed`][crate::error::ErrorKind::PairingFailed], which describes the reason for the failure. Pairing
+    /// with a device which is already paired succeeds without pairing again; use
+    /// [`require_security`][Self::require_security] to raise the security level of an existing bond.
+    ///
     /// # Platform specific
     ///
     /// ## MacOS/iOS
@@ -73,15 +88,36 @@ impl Device {
 
     /// Attempt to pair this device using the system default pairing UI
     ///
+    /// Pairing failures and devices which are already paired are handled as for [`pair`][Self::pair].
+    ///
     /// # Platform specific
     ///
     /// On MacOS/iOS, device pairing is performed automatically by the OS when a characteristic requiring security is
     /// accessed. This method is a no-op.
     #[inline]
-    pub async fn pair_with_agent<T: PairingAgent + 'static>(&self, agent: &T) -> Result<()> {
+    pub async fn pair_with_agent<T: PairingAgent + ?Sized>(&self, agent: &T) -> Result<()> {
         self.0.pair_with_agent(agent).await
     }
 
+    /// Attempt to pair this device using LE Secure Connections out-of-band pairing data received from the device.
+    ///
+    /// The OOB data must have been obtained from the device over another channel, such as NFC or a QR code. If the
+    /// device has also received the adapter's [`local_oob_data`][crate::Adapter::local_oob_data], both sides are
+    /// authenticated.
+    ///
+    /// Returns an [`InvalidParameter`][crate::error::ErrorKind::InvalidParameter] error if the address in `oob` is not
+    /// the address of this device.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this uses the kernel's Bluetooth management API, which requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn pair_with_oob_data(&self, oob: &OobData) -> Result<()> {
+        self.0.pair_with_oob_data(oob).await
+    }
+
     /// Disconnect and unpair this device from the system
     ///
     /// # Platform specific
@@ -92,6 +128,154 @@ impl Device {
         self.0.unpair().await
     }
 
+    /// Deletes the bond with this device without disconnecting it.
+    ///
+    /// The keys shared with the device are deleted, so the current connection keeps its security but the device
+    /// must pair again after reconnecting.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this uses the kernel's Bluetooth management API, which requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows, where unpairing a device always
+    /// closes its connections, and on MacOS/iOS.
+    #[inline]
+    pub async fn remove_bond(&self) -> Result<()> {
+        self.0.remove_bond().await
+    }
+
+    /// Information about the stored bond with this device.
+    ///
+    /// Returns a [`NotFound`][crate::error::ErrorKind::NotFound] error if the device is not bonded.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, this reads BlueZ's storage for the device, which usually requires root permissions. Only LE keys are
+    /// reported.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn bond_info(&self) -> Result<BondInfo> {
+        self.0.bond_info().await
+    }
+
+    /// Whether this device is trusted
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn is_trusted(&self) -> Result<bool> {
+        self.0.is_trusted().await
+    }
+
+    /// Sets whether this device is trusted.
+    ///
+    /// BlueZ authorizes connections and service requests from trusted devices without asking a pairing agent.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn set_trusted(&self, trusted: bool) -> Result<()> {
+        self.0.set_trusted(trusted).await
+    }
+
+    /// Whether this device is blocked
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn is_blocked(&self) -> Result<bool> {
+        self.0.is_blocked().await
+    }
+
+    /// Sets whether this device is blocked.
+    ///
+    /// Incoming connections from a blocked device are rejected immediately. Blocking a device disconnects it.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on Windows and MacOS/iOS.
+    #[inline]
+    pub async fn set_blocked(&self, blocked: bool) -> Result<()> {
+        self.0.set_blocked(blocked).await
+    }
+
+    /// The security level of the connection to this device
+    ///
+    /// Returns a [`NotConnected`][crate::error::ErrorKind::NotConnected] error if the device is not connected.
+    ///
+    /// # Platform specific
+    ///
+    /// - On Linux, the level is read from the kernel's link mode for the connection.
+    /// - On Windows, the level is derived from the protection level of the pairing, and
+    ///   [`SecureConnections`][SecurityLevel::SecureConnections] is never reported.
+    /// - Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub async fn security_level(&self) -> Result<SecurityLevel> {
+        self.0.security_level().await
+    }
+
+    /// Pairs with this device if necessary to raise the security of the connection to at least `level`.
+    ///
+    /// The agent registered with [`set_auto_pair`][Self::set_auto_pair] is used for pairing if there is one,
+    /// otherwise the system default pairing UI is used. Returns a
+    /// [`NotAuthorized`][crate::error::ErrorKind::NotAuthorized] error if the device is already paired at a lower
+    /// level or pairing does not reach `level`.
+    ///
+    /// # Platform specific
+    ///
+    /// - On Linux, a device which is paired at a lower level has its bond removed and is paired again, as BlueZ does
+    ///   not pair with a paired device.
+    /// - On Windows, [`SecureConnections`][SecurityLevel::SecureConnections] cannot be verified and returns a
+    ///   [`NotSupported`][crate::error::ErrorKind::NotSupported] error.
+    /// - On MacOS/iOS, device pairing is performed automatically by the OS when a characteristic requiring security
+    ///   is accessed. This method is a no-op.
+    #[inline]
+    pub async fn require_security(&self, level: SecurityLevel) -> Result<()> {
+        self.0.require_security(level).await
+    }
+
+    /// Registers `agent` to pair with this device automatically until the returned guard is dropped.
+    ///
+    /// While an agent is registered, characteristic and descriptor operations which fail with an
+    /// [`INSUFFICIENT_AUTHENTICATION`][crate::error::AttError::INSUFFICIENT_AUTHENTICATION] or
+    /// [`INSUFFICIENT_ENCRYPTION`][crate::error::AttError::INSUFFICIENT_ENCRYPTION] error pair with the device using
+    /// the agent and are then retried once. The agent is also used by [`require_security`][Self::require_security].
+    /// Registrations apply to every `Device` with the same [`id`][Self::id]. If several agents are registered for a
+    /// device, the most recently registered one is used.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux and MacOS/iOS, the OS raises the security of the connection itself when an operation fails for these
+    /// reasons, using the system's pairing agent on Linux. The registered agent is only used by
+    /// [`require_security`][Self::require_security].
+    pub fn set_auto_pair(&self, agent: Arc<dyn PairingAgent>) -> AutoPairGuard {
+        crate::pairing::set_auto_pair_agent(self.id(), agent)
+    }
+
+    /// Monitors the pairing state of this device, yielding an event each time pairing with the device starts,
+    /// completes, fails, or the device is unpaired.
+    ///
+    /// Events are reported for pairing and unpairing performed by bluest, including pairing performed by
+    /// [`require_security`][Self::require_security] and automatic pairing, for every `Device` with the same
+    /// [`id`][Self::id].
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, the device being paired or unpaired by the OS or other applications is also reported, as
+    /// [`Paired`][PairingEvent::Paired] and [`Unpaired`][PairingEvent::Unpaired] events. On Windows, pairing
+    /// performed by the OS or other applications is not reported.
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS, where pairing is managed by the
+    /// OS.
+    #[inline]
+    pub async fn pairing_events(&self) -> Result<impl Stream<Item = PairingEvent> + '_> {
+        self.0.pairing_events().await
+    }
+
     /// Discover the primary services of this device.
     #[inline]
     pub async fn discover_services(&self) -> Result<Vec<Service>> {
@@ -112,12 +296,158 @@ impl Device {
         self.0.services().await
     }
 
+    /// Discover the primary services of this device, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`services`][Self::services] for [`CacheMode::Cached`] and
+    /// [`discover_services`][Self::discover_services] for [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn discover_services_with(&self, mode: CacheMode) -> Result<Vec<Service>> {
+        match mode {
+            CacheMode::Cached => self.0.services().await,
+            CacheMode::Uncached => self.0.discover_services().await,
+        }
+    }
+
     /// Asynchronously blocks until a GATT services changed packet is received
     #[inline]
     pub async fn services_changed(&self) -> Result<()> {
         self.0.services_changed().await
     }
 
+    /// Monitors this device for GATT services changed packets.
+    ///
+    /// Unlike [`Device::services_changed`], the returned stream holds a single subscription for its whole lifetime,
+    /// so no changes are missed between events.
+    ///
+    /// # Platform specific
+    ///
+    /// The [`ServicesChangedEvent::range`] is only reported on Linux and the
+    /// [`ServicesChangedEvent::invalidated_services`] are only reported on MacOS/iOS.
+    #[inline]
+    pub async fn service_changes(&self) -> Result<impl Stream<Item = Result<ServicesChangedEvent>> + '_> {
+        self.0.service_changes().await
+    }
+
+    /// Asynchronously blocks until a GATT services changed packet is received and returns the range of attribute
+    /// handles it affects.
+    ///
+    /// Any [`Service`], [`Characteristic`] or [`Descriptor`][crate::Descriptor] with a handle inside the returned
+    /// range should be considered invalid and rediscovered.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Windows.
+    #[inline]
+    pub async fn services_changed_range(&self) -> Result<HandleRange> {
+        self.0.services_changed_range().await
+    }
+
+    /// Take a snapshot of this device's GATT database.
+    ///
+    /// Discovers all primary services of the device along with their included services, characteristics and
+    /// descriptors, and records them in a [`GattDatabase`]. Discovery of each level of the tree is performed
+    /// concurrently. The cached values of readable characteristics and of descriptors are included where available;
+    /// no values are read from the device.
+    ///
+    /// # Platform specific
+    ///
+    /// Attribute handles are not available on MacOS/iOS.
+    pub async fn gatt_database(&self) -> Result<GattDatabase> {
+        GattDatabase::discover(self).await
+    }
+
+    /// Find the previously discovered characteristic whose value has the given attribute `handle`.
+    ///
+    /// Returns an error with a kind of [`NotFound`][crate::error::ErrorKind::NotFound] if no such characteristic
+    /// exists.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    pub async fn characteristic_by_handle(&self, handle: u16) -> Result<Characteristic> {
+        for service in self.services().await? {
+            for characteristic in service.characteristics().await? {
+                if characteristic.handle()? == handle {
+                    return Ok(characteristic);
+                }
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::NotFound,
+            None,
+            format!("no characteristic with handle {handle:#06x}"),
+        ))
+    }
+
+    /// Read the GATT Database Hash characteristic of this device.
+    ///
+    /// The hash changes whenever the layout of the device's GATT database changes, so it can be used to determine
+    /// whether a previously discovered layout is still valid. Devices which do not support GATT caching do not expose
+    /// this characteristic, in which case an error with a kind of [`NotFound`][crate::error::ErrorKind::NotFound] is
+    /// returned.
+    ///
+    /// # Platform specific
+    ///
+    /// MacOS/iOS and Windows may not allow applications to access the Generic Attribute service.
+    pub async fn database_hash(&self) -> Result<[u8; 16]> {
+        let gatt = btuuid::services::GENERIC_ATTRIBUTE;
+        let hash = btuuid::characteristics::DATABASE_HASH;
+        for service in self.discover_services_with_uuid(gatt).await? {
+            if service.uuid_async().await? != gatt {
+                continue;
+            }
+            for characteristic in service.discover_characteristics_with_uuid(hash).await? {
+                if characteristic.uuid_async().await? == hash {
+                    let value = characteristic.read().await?;
+                    return value.try_into().map_err(|value| {
+                        Error::new(ErrorKind::Other, None, format!("invalid database hash: {value:?}"))
+                    });
+                }
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::NotFound,
+            None,
+            "database hash characteristic not found".to_string(),
+        ))
+    }
+
+    /// Read the values of several characteristics of this device.
+    ///
+    /// The reads are issued concurrently so that the OS can queue them back-to-back instead of waiting for each
+    /// response before sending the next request. The values are returned in the same order as `characteristics`.
+    ///
+    /// None of the supported platforms expose the ATT Read Multiple procedure, so this still performs one ATT read
+    /// per characteristic.
+    pub async fn read_multiple(&self, characteristics: &[Characteristic]) -> Result<Vec<Vec<u8>>> {
+        try_join_all(characteristics.iter().map(|x| x.read())).await
+    }
+
+    /// Begin a [`ReliableWrite`] transaction on this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub fn reliable_write(&self) -> Result<ReliableWrite> {
+        self.0.reliable_write()
+    }
+
+    /// The ATT MTU negotiated for the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// On MacOS/iOS, the MTU is derived from the maximum write without response length reported by CoreBluetooth.
+    ///
+    /// On Linux, the device must expose at least one characteristic which supports write without response, otherwise
+    /// this method returns a [`NotSupported`][crate::error::ErrorKind::NotSupported] error.
+    #[inline]
+    pub async fn mtu(&self) -> Result<u16> {
+        self.0.mtu().await
+    }
+
     /// Get the current signal strength from the device in dBm.
     ///
     /// # Platform specific
@@ -127,4 +457,88 @@ impl Device {
     pub async fn rssi(&self) -> Result<i16> {
         self.0.rssi().await
     }
+
+    /// The LE connection parameters of the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Requires Windows 11 on Windows. Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS,
+    /// and on Linux, where neither BlueZ nor the kernel's management API report the parameters of a connection and
+    /// the controller has no command to read them. Use
+    /// [`connection_parameter_changes`][Self::connection_parameter_changes] to monitor updates on Linux.
+    #[inline]
+    pub async fn connection_parameters(&self) -> Result<ConnectionParameters> {
+        self.0.connection_parameters().await
+    }
+
+    /// Monitors the LE connection parameters of the connection to this device, yielding the new parameters each time
+    /// they are updated.
+    ///
+    /// # Platform specific
+    ///
+    /// Requires Windows 11 on Windows. On Linux, updates are read from the controller's events on a raw HCI socket,
+    /// which requires the `CAP_NET_RAW` capability, and the stream ends when the device disconnects. Returns
+    /// [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub async fn connection_parameter_changes(&self) -> Result<impl Stream<Item = Result<ConnectionParameters>> + '_> {
+        self.0.connection_parameter_changes().await
+    }
+
+    /// The PHYs used by the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Requires Windows 11 on Windows. On Linux, the PHYs are read from the controller with a raw HCI command, which
+    /// requires the `CAP_NET_RAW` capability. Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on
+    /// MacOS/iOS.
+    #[inline]
+    pub async fn connection_phy(&self) -> Result<ConnectionPhy> {
+        self.0.connection_phy().await
+    }
+
+    /// Monitors the PHYs used by the connection to this device, yielding the new PHYs each time they are updated.
+    ///
+    /// # Platform specific
+    ///
+    /// Requires Windows 11 on Windows. On Linux, updates are read from the controller's events on a raw HCI socket,
+    /// which requires the `CAP_NET_RAW` capability, and the stream ends when the device disconnects. Returns
+    /// [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub async fn connection_phy_changes(&self) -> Result<impl Stream<Item = Result<ConnectionPhy>> + '_> {
+        self.0.connection_phy_changes().await
+    }
+
+    /// Asks the controller to use `phy` for the connection to this device.
+    ///
+    /// The controller and the device may choose other PHYs; use [`connection_phy`][Self::connection_phy] to check the
+    /// PHYs in effect.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, the request is sent to the controller with a raw HCI command, which requires the `CAP_NET_RAW`
+    /// capability. Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS and Windows, which do
+    /// not allow applications to choose the PHY.
+    #[inline]
+    pub async fn set_preferred_phy(&self, phy: ConnectionPhy) -> Result<()> {
+        self.0.set_preferred_phy(phy).await
+    }
+
+    /// Asks the OS to use connection parameters suited to `priority` for the connection to this device.
+    ///
+    /// The request applies to every `Device` with the same [`id`][Self::id] and lasts until it is replaced.
+    /// Requesting [`Balanced`][ConnectionPriority::Balanced] restores the OS defaults. The OS may choose not to honor
+    /// the request; use [`connection_parameters`][Self::connection_parameters] to check the parameters in effect.
+    ///
+    /// # Platform specific
+    ///
+    /// Requires Windows 11 on Windows. On Linux, the parameters are requested from the controller for the current
+    /// connection with a raw HCI command, which requires the `CAP_NET_RAW` capability, and the call waits until the
+    /// controller applies them. The request lasts until the connection is updated again or closed and does not affect
+    /// later connections. [`Balanced`][ConnectionPriority::Balanced] requests the parameters BlueZ stored for the
+    /// device, if readable, and otherwise the kernel's defaults. Returns
+    /// [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    #[inline]
+    pub async fn request_connection_priority(&self, priority: ConnectionPriority) -> Result<()> {
+        self.0.request_connection_priority(priority).await
+    }
 }
@@ -0,0 +1,379 @@
+//! Encrypted Advertising Data (EAD).
+//!
+//! A device can encrypt some of its advertising data and send it in an Encrypted Data AD structure, which is
+//! available in [`AdvertisementData::encrypted_data`]. The data is encrypted with AES-CCM using key material the device
+//! shares with authorized clients, usually through the Encrypted Data Key Material characteristic
+//! ([`ENCRYPTED_DATA_KEY_MATERIAL`][crate::btuuid::characteristics::ENCRYPTED_DATA_KEY_MATERIAL]).
+//!
+//! See the Bluetooth Core Specification Supplement, Part A, §1.23.
+//!
+//! ```rust,no_run
+//!# use bluest::Adapter;
+//!# use bluest::codec::GattDecode;
+//!# use bluest::ead::KeyMaterial;
+//!# use futures_util::StreamExt;
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!# let key_material_value = [0; 24];
+//!let key = KeyMaterial::decode(&key_material_value)?;
+//!
+//!let adapter = Adapter::default().await.ok_or("Bluetooth adapter not found")?;
+//!adapter.wait_available().await?;
+//!let mut scan = adapter.scan(&[]).await?;
+//!while let Some(discovered_device) = scan.next().await {
+//!    if let Ok(Some(adv_data)) = discovered_device.adv_data.decrypt(&key) {
+//!        println!("{}: {:?}", discovered_device.device.id(), adv_data);
+//!    }
+//!}
+//!# Ok(())
+//!# }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::aes::Aes128;
+use crate::codec::{GattDecode, GattEncode, InvalidValue};
+use crate::error::ErrorKind;
+use crate::{AdvertisementData, BluetoothUuidExt, Error, ManufacturerData, Uuid};
+
+/// The additional authenticated data, which is the value of the Encrypted Data AD type
+const AAD: [u8; 1] = [0xea];
+const RANDOMIZER_LEN: usize = 5;
+const MIC_LEN: usize = 4;
+
+/// An error returned when encrypted advertising data cannot be decrypted or does not contain valid AD structures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidEncryptedData(());
+
+impl std::fmt::Display for InvalidEncryptedData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid encrypted advertising data")
+    }
+}
+
+impl std::error::Error for InvalidEncryptedData {}
+
+impl From<InvalidEncryptedData> for Error {
+    fn from(err: InvalidEncryptedData) -> Self {
+        Error::new(ErrorKind::Other, Some(Box::new(err)), String::new())
+    }
+}
+
+/// The key material used to encrypt advertising data
+///
+/// This is the value of the Encrypted Data Key Material characteristic, so it can be read with
+/// [`TypedCharacteristic`][crate::codec::TypedCharacteristic].
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyMaterial {
+    /// The session key
+    pub session_key: [u8; 16],
+    /// The initialization vector
+    pub iv: [u8; 8],
+}
+
+impl std::fmt::Debug for KeyMaterial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyMaterial").finish_non_exhaustive()
+    }
+}
+
+impl GattDecode for KeyMaterial {
+    fn decode(value: &[u8]) -> Result<Self, InvalidValue> {
+        if value.len() != 24 {
+            return Err(InvalidValue(()));
+        }
+        let (session_key, iv) = value.split_at(16);
+        Ok(KeyMaterial {
+            session_key: session_key.try_into().unwrap(),
+            iv: iv.try_into().unwrap(),
+        })
+    }
+}
+
+impl GattEncode for KeyMaterial {
+    fn encode(&self) -> Vec<u8> {
+        [&self.session_key[..], &self.iv[..]].concat()
+    }
+}
+
+impl KeyMaterial {
+    fn nonce(&self, randomizer: &[u8]) -> [u8; 13] {
+        let mut nonce = [0; 13];
+        nonce[..RANDOMIZER_LEN].copy_from_slice(randomizer);
+        nonce[RANDOMIZER_LEN..].copy_from_slice(&self.iv);
+        nonce
+    }
+
+    /// Decrypts the payload of an Encrypted Data AD structure, returning the AD structures it contains.
+    ///
+    /// ```
+    /// use bluest::ead::KeyMaterial;
+    ///
+    /// let key = KeyMaterial {
+    ///     session_key: [
+    ///         0x5f, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6, 0x07, 0x18, 0x29, 0x3a, 0x4b, 0x5c, 0x6d, 0x7e, 0x8f,
+    ///     ],
+    ///     iv: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+    /// };
+    /// let data = b"\x06\x09Probe\x05\xff\xff\xff\x2a\x01";
+    /// let encrypted_data = key.encrypt([0x1a, 0x2b, 0x3c, 0x4d, 0x0e], data);
+    ///
+    /// assert_eq!(key.decrypt(&encrypted_data).unwrap(), data);
+    /// ```
+    pub fn decrypt(&self, encrypted_data: &[u8]) -> Result<Vec<u8>, InvalidEncryptedData> {
+        if encrypted_data.len() < RANDOMIZER_LEN + MIC_LEN {
+            return Err(InvalidEncryptedData(()));
+        }
+        let (randomizer, payload) = encrypted_data.split_at(RANDOMIZER_LEN);
+        Aes128::new(&self.session_key)
+            .ccm_decrypt(&self.nonce(randomizer), &AAD, payload, MIC_LEN)
+            .ok_or(InvalidEncryptedData(()))
+    }
+
+    /// Encrypts the AD structures in `data`, returning the payload of an Encrypted Data AD structure.
+    ///
+    /// `randomizer` must be a new random value for each encryption. The most significant bit of its last octet is
+    /// replaced by the direction bit.
+    pub fn encrypt(&self, randomizer: [u8; 5], data: &[u8]) -> Vec<u8> {
+        let mut randomizer = randomizer;
+        randomizer[RANDOMIZER_LEN - 1] |= 0x80;
+        let payload = Aes128::new(&self.session_key).ccm_encrypt(&self.nonce(&randomizer), &AAD, data, MIC_LEN);
+        [&randomizer[..], &payload].concat()
+    }
+}
+
+impl AdvertisementData {
+    /// Decrypts [`encrypted_data`][Self::encrypted_data] with `key` and parses the AD structures it contains.
+    ///
+    /// Returns `Ok(None)` if there is no encrypted data. The returned data has the same
+    /// [`is_connectable`][Self::is_connectable] value as `self`.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use bluest::ead::KeyMaterial;
+    /// use bluest::{AdvertisementData, ManufacturerData};
+    ///
+    /// let key = KeyMaterial {
+    ///     session_key: [
+    ///         0x5f, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6, 0x07, 0x18, 0x29, 0x3a, 0x4b, 0x5c, 0x6d, 0x7e, 0x8f,
+    ///     ],
+    ///     iv: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+    /// };
+    /// let adv_data = AdvertisementData {
+    ///     local_name: None,
+    ///     manufacturer_data: None,
+    ///     services: Vec::new(),
+    ///     service_data: HashMap::new(),
+    ///     tx_power_level: None,
+    ///     is_connectable: false,
+    ///     encrypted_data: Some(key.encrypt([0; 5], b"\x06\x09Probe\x05\xff\xff\xff\x2a\x01")),
+    /// };
+    ///
+    /// let decrypted = adv_data.decrypt(&key).unwrap().unwrap();
+    /// assert_eq!(decrypted.local_name.as_deref(), Some("Probe"));
+    /// assert_eq!(
+    ///     decrypted.manufacturer_data,
+    ///     Some(ManufacturerData {
+    ///         company_id: 0xffff,
+    ///         data: vec![0x2a, 0x01],
+    ///     })
+    /// );
+    /// ```
+    pub fn decrypt(&self, key: &KeyMaterial) -> Result<Option<AdvertisementData>, InvalidEncryptedData> {
+        let Some(encrypted_data) = &self.encrypted_data else {
+            return Ok(None);
+        };
+        let data = key.decrypt(encrypted_data)?;
+        parse_ad_structures(&data, self.is_connectable)
+            .map(Some)
+            .ok_or(InvalidEncryptedData(()))
+    }
+}
+
+fn parse_uuids(data: &[u8], len: usize, uuids: &mut Vec<Uuid>) -> Option<()> {
+    let chunks = data.chunks_exact(len);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    uuids.extend(chunks.map(|x| parse_uuid(x).unwrap()));
+    Some(())
+}
+
+/// Parses a little-endian 16, 32 or 128-bit UUID
+fn parse_uuid(data: &[u8]) -> Option<Uuid> {
+    match *data {
+        [a, b] => Some(Uuid::from_u16(u16::from_le_bytes([a, b]))),
+        [a, b, c, d] => Some(Uuid::from_u32(u32::from_le_bytes([a, b, c, d]))),
+        _ => {
+            let bytes: [u8; 16] = data.try_into().ok()?;
+            Some(Uuid::from_u128(u128::from_le_bytes(bytes)))
+        }
+    }
+}
+
+/// Parses a sequence of AD structures
+///
+/// See the Bluetooth Core Specification, Vol 3, Part C, §11 and the Bluetooth Core Specification Supplement, Part A.
+fn parse_ad_structures(mut data: &[u8], is_connectable: bool) -> Option<AdvertisementData> {
+    let mut adv_data = AdvertisementData {
+        local_name: None,
+        manufacturer_data: None,
+        services: Vec::new(),
+        service_data: HashMap::new(),
+        tx_power_level: None,
+        is_connectable,
+        encrypted_data: None,
+    };
+
+    while let Some((&len, rest)) = data.split_first() {
+        // A zero length field ends the significant part of the data
+        if len == 0 {
+            break;
+        }
+        let (structure, rest) = (rest.len() >= len as usize).then(|| rest.split_at(len as usize))?;
+        let (&kind, value) = structure.split_first()?;
+        data = rest;
+
+        match kind {
+            0x02 | 0x03 => parse_uuids(value, 2, &mut adv_data.services)?,
+            0x04 | 0x05 => parse_uuids(value, 4, &mut adv_data.services)?,
+            0x06 | 0x07 => parse_uuids(value, 16, &mut adv_data.services)?,
+            // A shortened local name does not replace a complete one
+            0x08 if adv_data.local_name.is_some() => (),
+            0x08 | 0x09 => adv_data.local_name = Some(String::from_utf8_lossy(value).into_owned()),
+            0x0a => adv_data.tx_power_level = Some(i8::from_le_bytes([*value.first()?]).into()),
+            0x16 | 0x20 | 0x21 => {
+                let uuid_len = match kind {
+                    0x16 => 2,
+                    0x20 => 4,
+                    _ => 16,
+                };
+                let uuid = parse_uuid(value.get(..uuid_len)?)?;
+                adv_data.service_data.insert(uuid, value[uuid_len..].to_vec());
+            }
+            0x31 if adv_data.encrypted_data.is_none() => adv_data.encrypted_data = Some(value.to_vec()),
+            0xff if adv_data.manufacturer_data.is_none() => {
+                let (company_id, data) = (value.len() >= 2).then(|| value.split_at(2))?;
+                adv_data.manufacturer_data = Some(ManufacturerData {
+                    company_id: u16::from_le_bytes([company_id[0], company_id[1]]),
+                    data: data.to_vec(),
+                });
+            }
+            _ => (),
+        }
+    }
+
+    Some(adv_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> KeyMaterial {
+        KeyMaterial {
+            session_key: [
+                0x5f, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6, 0x07, 0x18, 0x29, 0x3a, 0x4b, 0x5c, 0x6d, 0x7e, 0x8f,
+            ],
+            iv: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+        }
+    }
+
+    const PLAINTEXT: &[u8] = b"\x06\x09Probe\x05\xff\xff\xff\x2a\x01";
+    const RANDOMIZER: [u8; 5] = [0x1a, 0x2b, 0x3c, 0x4d, 0x8e];
+
+    #[test]
+    fn nonce_layout() {
+        // The nonce is the randomizer followed by the IV, both in the order they are sent
+        let key = key();
+        assert_eq!(
+            key.nonce(&RANDOMIZER),
+            [0x1a, 0x2b, 0x3c, 0x4d, 0x8e, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]
+        );
+
+        // The Encrypted Data Key Material characteristic holds the session key followed by the IV
+        let value: Vec<u8> = (0..24).collect();
+        let key = KeyMaterial::decode(&value).unwrap();
+        assert_eq!(key.session_key, value[..16]);
+        assert_eq!(key.iv, value[16..]);
+    }
+
+    #[test]
+    fn encrypted_data_layout() {
+        // The encrypted data is the randomizer followed by the AES-CCM ciphertext of the AD structures, with the
+        // Encrypted Data AD type as the additional authenticated data and a 4 octet MIC
+        let key = key();
+        let encrypted = key.encrypt(RANDOMIZER, PLAINTEXT);
+        assert_eq!(encrypted.len(), RANDOMIZER_LEN + PLAINTEXT.len() + MIC_LEN);
+        assert_eq!(encrypted[..RANDOMIZER_LEN], RANDOMIZER);
+
+        let nonce = [&RANDOMIZER[..], &key.iv[..]].concat().try_into().unwrap();
+        let payload = Aes128::new(&key.session_key).ccm_encrypt(&nonce, &[0xea], PLAINTEXT, 4);
+        assert_eq!(encrypted[RANDOMIZER_LEN..], payload);
+        assert_eq!(key.decrypt(&encrypted).as_deref(), Ok(PLAINTEXT));
+    }
+
+    #[test]
+    fn encrypt_sets_direction_bit() {
+        let key = key();
+        let encrypted = key.encrypt([0x1a, 0x2b, 0x3c, 0x4d, 0x0e], PLAINTEXT);
+        assert_eq!(encrypted[..RANDOMIZER_LEN], RANDOMIZER);
+        assert_eq!(key.encrypt(RANDOMIZER, PLAINTEXT), encrypted);
+    }
+
+    #[test]
+    fn decrypt_rejects_invalid_data() {
+        let key = key();
+        let encrypted = key.encrypt(RANDOMIZER, PLAINTEXT);
+        assert!(key.decrypt(&encrypted[..RANDOMIZER_LEN + MIC_LEN - 1]).is_err());
+        assert!(key.decrypt(&[]).is_err());
+
+        for i in [0, RANDOMIZER_LEN, encrypted.len() - 1] {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 0x01;
+            assert!(key.decrypt(&tampered).is_err());
+        }
+
+        let mut wrong_iv = key.clone();
+        wrong_iv.iv[0] ^= 0x01;
+        assert!(wrong_iv.decrypt(&encrypted).is_err());
+
+        let mut wrong_key = key.clone();
+        wrong_key.session_key[15] ^= 0x01;
+        assert!(wrong_key.decrypt(&encrypted).is_err());
+    }
+
+    #[test]
+    fn key_material_encoding() {
+        let key = key();
+        let value = key.encode();
+        assert_eq!(value.len(), 24);
+        assert_eq!(KeyMaterial::decode(&value), Ok(key));
+        assert!(KeyMaterial::decode(&value[..23]).is_err());
+    }
+
+    #[test]
+    fn parse_encrypted_ad_structures() {
+        let data = [
+            &[0x03, 0x03, 0x0f, 0x18][..],
+            &[0x04, 0x16, 0x0f, 0x18, 0x64],
+            &[0x02, 0x0a, 0xf4],
+            &[0x03, 0x08, b'P', b'r'],
+            &[0x06, 0x09],
+            b"Probe",
+            &[0x00, 0xff],
+        ]
+        .concat();
+        let adv_data = parse_ad_structures(&data, true).unwrap();
+        assert_eq!(adv_data.services, vec![Uuid::from_u16(0x180f)]);
+        assert_eq!(adv_data.service_data.get(&Uuid::from_u16(0x180f)), Some(&vec![0x64]));
+        assert_eq!(adv_data.tx_power_level, Some(-12));
+        assert_eq!(adv_data.local_name.as_deref(), Some("Probe"));
+        assert!(adv_data.is_connectable);
+
+        assert!(parse_ad_structures(&[0x05, 0x09, b'P'], false).is_none());
+        assert!(parse_ad_structures(&[0x04, 0x03, 0x0f, 0x18, 0x00], false).is_none());
+    }
+}
@@ -78,6 +78,8 @@ pub enum ErrorKind {
     Internal,
     /// the service changed and is no longer valid
     ServiceChanged,
+    /// pairing failed: {0}
+    PairingFailed(PairingError),
     /// error
     Other,
 }
@@ -98,6 +100,7 @@ impl std::fmt::Display for ErrorKind {
             ErrorKind::Protocol(err) => write!(f, "protocol error: {}", err),
             ErrorKind::Internal => f.write_str("an internal error has occured"),
             ErrorKind::ServiceChanged => f.write_str("the service changed and is no longer valid"),
+            ErrorKind::PairingFailed(reason) => write!(f, "pairing failed: {}", reason),
             ErrorKind::Other => f.write_str("error"),
         }
     }
@@ -113,6 +116,52 @@ impl From<ErrorKind> for Error {
     }
 }
 
+/// The reason pairing with a device failed
+///
+/// # Platform specific
+///
+/// The reasons are derived from the errors reported by the OS, which do not distinguish every failure:
+///
+/// - Neither Linux nor Windows reports which step of authentication failed, so a mistyped passkey, a rejected numeric
+///   comparison on the remote device and a confirm value mismatch are all reported as
+///   [`AuthenticationFailed`][Self::AuthenticationFailed].
+/// - On Linux, a request rejected by a [`PairingAgent`][crate::pairing::PairingAgent] passed to
+///   [`Device::pair_with_agent`][crate::Device::pair_with_agent] is reported as [`Canceled`][Self::Canceled].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PairingError {
+    /// authentication failed
+    AuthenticationFailed,
+    /// the device rejected pairing
+    RejectedByDevice,
+    /// pairing was canceled or rejected locally
+    Canceled,
+    /// pairing timed out
+    Timeout,
+    /// pairing is already in progress
+    InProgress,
+    /// the required security level could not be met
+    InsufficientSecurity,
+    /// pairing failed
+    Other,
+}
+
+impl std::fmt::Display for PairingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PairingError::AuthenticationFailed => f.write_str("authentication failed"),
+            PairingError::RejectedByDevice => f.write_str("the device rejected pairing"),
+            PairingError::Canceled => f.write_str("pairing was canceled or rejected locally"),
+            PairingError::Timeout => f.write_str("pairing timed out"),
+            PairingError::InProgress => f.write_str("pairing is already in progress"),
+            PairingError::InsufficientSecurity => f.write_str("the required security level could not be met"),
+            PairingError::Other => f.write_str("pairing failed"),
+        }
+    }
+}
+
+impl std::error::Error for PairingError {}
+
 /// Bluetooth Attribute Protocol error. See the Bluetooth Core Specification, Vol 3, Part F, §3.4.1.1
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AttError(u8);
@@ -0,0 +1,542 @@
+//! Plain-data snapshots of a device's GATT database.
+//!
+//! A [`GattDatabase`] is created with [`Device::gatt_database`][crate::Device::gatt_database]. It records the
+//! layout of the device's services, characteristics and descriptors at that time and does not hold references to the
+//! platform objects, so it can be stored, compared or (with the `serde` feature) serialized.
+
+use std::collections::HashMap;
+
+use futures_util::future::try_join_all;
+
+use crate::{Characteristic, CharacteristicProperties, Descriptor, Device, Result, Service, Uuid};
+
+/// A snapshot of the GATT services, characteristics and descriptors of a device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattDatabase {
+    /// The primary services of the device
+    pub services: Vec<GattService>,
+}
+
+/// A GATT service in a [`GattDatabase`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattService {
+    /// The [`Uuid`] identifying the type of this service
+    pub uuid: Uuid,
+    /// The attribute handle of this service, if supported by the platform
+    pub handle: Option<u16>,
+    /// Whether this is a primary service, if supported by the platform
+    pub is_primary: Option<bool>,
+    /// The services included by this service
+    pub included_services: Vec<GattIncludedService>,
+    /// The characteristics of this service
+    pub characteristics: Vec<GattCharacteristic>,
+}
+
+/// A reference to an included service in a [`GattDatabase`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattIncludedService {
+    /// The [`Uuid`] identifying the type of the included service
+    pub uuid: Uuid,
+    /// The attribute handle of the included service, if supported by the platform
+    pub handle: Option<u16>,
+}
+
+/// A GATT characteristic in a [`GattDatabase`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattCharacteristic {
+    /// The [`Uuid`] identifying the type of this characteristic
+    pub uuid: Uuid,
+    /// The attribute handle of this characteristic's value, if supported by the platform
+    pub handle: Option<u16>,
+    /// The properties of this characteristic
+    pub properties: CharacteristicProperties,
+    /// The cached value of this characteristic, if it is readable and a value was available
+    pub value: Option<Vec<u8>>,
+    /// The descriptors of this characteristic
+    pub descriptors: Vec<GattDescriptor>,
+}
+
+/// A GATT descriptor in a [`GattDatabase`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattDescriptor {
+    /// The [`Uuid`] identifying the type of this descriptor
+    pub uuid: Uuid,
+    /// The attribute handle of this descriptor, if supported by the platform
+    pub handle: Option<u16>,
+    /// The cached value of this descriptor, if a value was available
+    pub value: Option<Vec<u8>>,
+}
+
+/// Identifies a service, characteristic or descriptor within its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeId {
+    /// The [`Uuid`] identifying the type of the attribute
+    pub uuid: Uuid,
+    /// The attribute handle, if supported by the platform
+    pub handle: Option<u16>,
+}
+
+/// The location of a service, characteristic or descriptor in a [`GattDatabase`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GattAttribute {
+    /// A service
+    Service(AttributeId),
+    /// A characteristic of `service`
+    Characteristic {
+        /// The service containing the characteristic
+        service: AttributeId,
+        /// The characteristic
+        characteristic: AttributeId,
+    },
+    /// A descriptor of `characteristic` in `service`
+    Descriptor {
+        /// The service containing the descriptor
+        service: AttributeId,
+        /// The characteristic containing the descriptor
+        characteristic: AttributeId,
+        /// The descriptor
+        descriptor: AttributeId,
+    },
+}
+
+/// The differences between two [`GattDatabase`] snapshots, as returned by [`GattDatabase::diff`]
+///
+/// Only the children of attributes present in both snapshots are compared, so the characteristics of an added or
+/// removed service are not listed separately.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GattDatabaseDiff {
+    /// Attributes present only in the new snapshot
+    pub added: Vec<GattAttribute>,
+    /// Attributes present only in the old snapshot
+    pub removed: Vec<GattAttribute>,
+    /// Attributes present in both snapshots whose definitions differ
+    pub modified: Vec<GattAttribute>,
+}
+
+impl GattDatabaseDiff {
+    /// Returns `true` if the two snapshots had the same layout
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl GattService {
+    /// The [`AttributeId`] of this service
+    pub fn id(&self) -> AttributeId {
+        AttributeId {
+            uuid: self.uuid,
+            handle: self.handle,
+        }
+    }
+}
+
+impl GattCharacteristic {
+    /// The [`AttributeId`] of this characteristic
+    pub fn id(&self) -> AttributeId {
+        AttributeId {
+            uuid: self.uuid,
+            handle: self.handle,
+        }
+    }
+}
+
+impl GattDescriptor {
+    /// The [`AttributeId`] of this descriptor
+    pub fn id(&self) -> AttributeId {
+        AttributeId {
+            uuid: self.uuid,
+            handle: self.handle,
+        }
+    }
+}
+
+/// The result of matching the children of an attribute in two snapshots
+struct Matched<'a, T> {
+    added: Vec<&'a T>,
+    removed: Vec<&'a T>,
+    both: Vec<(&'a T, &'a T)>,
+}
+
+/// Matches attributes by UUID and handle. Where handles are not available, attributes with the same UUID are matched
+/// in the order they appear.
+fn match_attributes<'a, T>(old: &'a [T], new: &'a [T], id: impl Fn(&T) -> AttributeId) -> Matched<'a, T> {
+    let keys = |items: &'a [T]| {
+        let mut counts = HashMap::new();
+        items
+            .iter()
+            .map(|item| {
+                let id = id(item);
+                let count = counts.entry(id).or_insert(0usize);
+                *count += 1;
+                ((id, *count), item)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let old = keys(old);
+    let mut new: HashMap<_, _> = keys(new)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (k, v))| (k, (i, v)))
+        .collect();
+
+    let mut removed = Vec::new();
+    let mut both = Vec::new();
+    for (key, item) in old {
+        match new.remove(&key) {
+            Some((_, new_item)) => both.push((item, new_item)),
+            None => removed.push(item),
+        }
+    }
+
+    let mut added: Vec<_> = new.into_values().collect();
+    added.sort_by_key(|(i, _)| *i);
+
+    Matched {
+        added: added.into_iter().map(|(_, x)| x).collect(),
+        removed,
+        both,
+    }
+}
+
+impl GattDatabase {
+    /// Compares this snapshot with a `new` snapshot of the same device.
+    ///
+    /// Services, characteristics and descriptors are matched by UUID and handle, so an attribute which has moved to a
+    /// different handle is reported as removed and re-added. A matched service is modified if its primary status or
+    /// included services differ, and a matched characteristic is modified if its properties differ. Cached values
+    /// are not compared.
+    pub fn diff(&self, new: &GattDatabase) -> GattDatabaseDiff {
+        let mut diff = GattDatabaseDiff::default();
+
+        let services = match_attributes(&self.services, &new.services, GattService::id);
+        diff.added
+            .extend(services.added.iter().map(|x| GattAttribute::Service(x.id())));
+        diff.removed
+            .extend(services.removed.iter().map(|x| GattAttribute::Service(x.id())));
+
+        for (old_service, new_service) in services.both {
+            let service = old_service.id();
+            if old_service.is_primary != new_service.is_primary
+                || old_service.included_services != new_service.included_services
+            {
+                diff.modified.push(GattAttribute::Service(service));
+            }
+
+            let characteristics = match_attributes(
+                &old_service.characteristics,
+                &new_service.characteristics,
+                GattCharacteristic::id,
+            );
+            let characteristic_attr = |x: &GattCharacteristic| GattAttribute::Characteristic {
+                service,
+                characteristic: x.id(),
+            };
+            diff.added
+                .extend(characteristics.added.into_iter().map(characteristic_attr));
+            diff.removed
+                .extend(characteristics.removed.into_iter().map(characteristic_attr));
+
+            for (old_characteristic, new_characteristic) in characteristics.both {
+                let characteristic = old_characteristic.id();
+                if old_characteristic.properties != new_characteristic.properties {
+                    diff.modified.push(characteristic_attr(old_characteristic));
+                }
+
+                let descriptors = match_attributes(
+                    &old_characteristic.descriptors,
+                    &new_characteristic.descriptors,
+                    GattDescriptor::id,
+                );
+                let descriptor_attr = |x: &GattDescriptor| GattAttribute::Descriptor {
+                    service,
+                    characteristic,
+                    descriptor: x.id(),
+                };
+                diff.added.extend(descriptors.added.into_iter().map(descriptor_attr));
+                diff.removed
+                    .extend(descriptors.removed.into_iter().map(descriptor_attr));
+            }
+        }
+
+        diff
+    }
+
+    pub(crate) async fn discover(device: &Device) -> Result<Self> {
+        let services = device.discover_services().await?;
+        let services = try_join_all(services.iter().map(GattService::discover)).await?;
+        Ok(GattDatabase { services })
+    }
+}
+
+impl GattService {
+    async fn discover(service: &Service) -> Result<Self> {
+        let included = service.discover_included_services().await?;
+        let included_services = try_join_all(included.iter().map(|x| async move {
+            Ok::<_, crate::Error>(GattIncludedService {
+                uuid: x.uuid_async().await?,
+                handle: x.0.handle().ok(),
+            })
+        }))
+        .await?;
+
+        let characteristics = service.discover_characteristics().await?;
+        let characteristics = try_join_all(characteristics.iter().map(GattCharacteristic::discover)).await?;
+
+        Ok(GattService {
+            uuid: service.uuid_async().await?,
+            handle: service.0.handle().ok(),
+            is_primary: service.is_primary().await.ok(),
+            included_services,
+            characteristics,
+        })
+    }
+}
+
+impl GattCharacteristic {
+    async fn discover(characteristic: &Characteristic) -> Result<Self> {
+        let properties = characteristic.properties().await?;
+        let value = if properties.read {
+            characteristic.value().await.ok()
+        } else {
+            None
+        };
+
+        let descriptors = characteristic.discover_descriptors().await?;
+        let descriptors = try_join_all(descriptors.iter().map(GattDescriptor::discover)).await?;
+
+        Ok(GattCharacteristic {
+            uuid: characteristic.uuid_async().await?,
+            handle: characteristic.0.handle().ok(),
+            properties,
+            value,
+            descriptors,
+        })
+    }
+}
+
+impl GattDescriptor {
+    async fn discover(descriptor: &Descriptor) -> Result<Self> {
+        Ok(GattDescriptor {
+            uuid: descriptor.uuid_async().await?,
+            handle: descriptor.0.handle().ok(),
+            value: descriptor.value().await.ok(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btuuid::{characteristics, descriptors, services};
+
+    fn descriptor(uuid: Uuid, handle: u16) -> GattDescriptor {
+        GattDescriptor {
+            uuid,
+            handle: Some(handle),
+            value: None,
+        }
+    }
+
+    fn characteristic(uuid: Uuid, handle: u16, descriptors: Vec<GattDescriptor>) -> GattCharacteristic {
+        GattCharacteristic {
+            uuid,
+            handle: Some(handle),
+            properties: CharacteristicProperties::from_bits(0x12),
+            value: None,
+            descriptors,
+        }
+    }
+
+    fn service(uuid: Uuid, handle: u16, characteristics: Vec<GattCharacteristic>) -> GattService {
+        GattService {
+            uuid,
+            handle: Some(handle),
+            is_primary: Some(true),
+            included_services: Vec::new(),
+            characteristics,
+        }
+    }
+
+    fn database() -> GattDatabase {
+        GattDatabase {
+            services: vec![
+                service(
+                    services::GENERIC_ACCESS,
+                    0x0001,
+                    vec![characteristic(characteristics::DEVICE_NAME, 0x0002, Vec::new())],
+                ),
+                service(
+                    services::BATTERY,
+                    0x0010,
+                    vec![characteristic(
+                        characteristics::BATTERY_LEVEL,
+                        0x0011,
+                        vec![descriptor(descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION, 0x0013)],
+                    )],
+                ),
+            ],
+        }
+    }
+
+    fn id(uuid: Uuid, handle: u16) -> AttributeId {
+        AttributeId {
+            uuid,
+            handle: Some(handle),
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        let diff = database().diff(&database());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn values_are_not_compared() {
+        let mut new = database();
+        new.services[1].characteristics[0].value = Some(vec![50]);
+        new.services[1].characteristics[0].descriptors[0].value = Some(vec![1, 0]);
+        assert!(database().diff(&new).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_services() {
+        let mut new = database();
+        let battery = new.services.remove(1);
+        new.services
+            .push(service(services::HEART_RATE, 0x0010, battery.characteristics));
+
+        let diff = database().diff(&new);
+        assert_eq!(
+            diff.added,
+            vec![GattAttribute::Service(id(services::HEART_RATE, 0x0010))]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![GattAttribute::Service(id(services::BATTERY, 0x0010))]
+        );
+        assert!(diff.modified.is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_descendants() {
+        let mut new = database();
+        new.services[0].characteristics.clear();
+        new.services[1].characteristics[0]
+            .descriptors
+            .push(descriptor(descriptors::CHARACTERISTIC_PRESENTATION_FORMAT, 0x0014));
+
+        let diff = database().diff(&new);
+        assert_eq!(
+            diff.added,
+            vec![GattAttribute::Descriptor {
+                service: id(services::BATTERY, 0x0010),
+                characteristic: id(characteristics::BATTERY_LEVEL, 0x0011),
+                descriptor: id(descriptors::CHARACTERISTIC_PRESENTATION_FORMAT, 0x0014),
+            }]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![GattAttribute::Characteristic {
+                service: id(services::GENERIC_ACCESS, 0x0001),
+                characteristic: id(characteristics::DEVICE_NAME, 0x0002),
+            }]
+        );
+        assert!(diff.modified.is_empty());
+    }
+
+    #[test]
+    fn moved_attributes() {
+        let mut new = database();
+        new.services[1].handle = Some(0x0020);
+
+        let diff = database().diff(&new);
+        assert_eq!(diff.added, vec![GattAttribute::Service(id(services::BATTERY, 0x0020))]);
+        assert_eq!(
+            diff.removed,
+            vec![GattAttribute::Service(id(services::BATTERY, 0x0010))]
+        );
+        assert!(diff.modified.is_empty());
+
+        let mut new = database();
+        new.services[1].characteristics[0].descriptors[0].handle = Some(0x0014);
+
+        let diff = database().diff(&new);
+        let descriptor = |handle| GattAttribute::Descriptor {
+            service: id(services::BATTERY, 0x0010),
+            characteristic: id(characteristics::BATTERY_LEVEL, 0x0011),
+            descriptor: id(descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION, handle),
+        };
+        assert_eq!(diff.added, vec![descriptor(0x0014)]);
+        assert_eq!(diff.removed, vec![descriptor(0x0013)]);
+    }
+
+    #[test]
+    fn changed_attributes() {
+        let mut new = database();
+        new.services[0].is_primary = Some(false);
+        new.services[1].characteristics[0].properties = CharacteristicProperties::from_bits(0x02);
+
+        let diff = database().diff(&new);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(
+            diff.modified,
+            vec![
+                GattAttribute::Service(id(services::GENERIC_ACCESS, 0x0001)),
+                GattAttribute::Characteristic {
+                    service: id(services::BATTERY, 0x0010),
+                    characteristic: id(characteristics::BATTERY_LEVEL, 0x0011),
+                },
+            ]
+        );
+
+        let mut new = database();
+        new.services[1].included_services.push(GattIncludedService {
+            uuid: services::GENERIC_ACCESS,
+            handle: Some(0x0001),
+        });
+        let diff = database().diff(&new);
+        assert_eq!(
+            diff.modified,
+            vec![GattAttribute::Service(id(services::BATTERY, 0x0010))]
+        );
+    }
+
+    #[test]
+    fn attributes_without_handles_match_in_order() {
+        let characteristic = |value| GattCharacteristic {
+            uuid: characteristics::BATTERY_LEVEL,
+            handle: None,
+            properties: CharacteristicProperties::default(),
+            value,
+            descriptors: Vec::new(),
+        };
+        let old = [characteristic(None), characteristic(None)];
+        let new = [
+            characteristic(Some(vec![1])),
+            characteristic(None),
+            characteristic(None),
+        ];
+
+        let matched = match_attributes(&old, &new, GattCharacteristic::id);
+        assert_eq!(matched.both.len(), 2);
+        assert!(matched.both[0].1.value.is_some());
+        assert!(matched.removed.is_empty());
+        assert_eq!(matched.added.len(), 1);
+        assert!(std::ptr::eq(matched.added[0], &new[2]));
+
+        let matched = match_attributes(&new, &old, GattCharacteristic::id);
+        assert_eq!(matched.removed.len(), 1);
+        assert!(std::ptr::eq(matched.removed[0], &new[2]));
+    }
+}
@@ -0,0 +1,235 @@
+//! A persistent store of GATT database snapshots.
+//!
+//! Walking a device's GATT database with [`Device::gatt_database`] requires many calls into the platform's Bluetooth
+//! stack. A [`GattSnapshotStore`] saves the resulting [`GattDatabase`] on disk along with the device's database hash
+//! ([`Device::database_hash`]) so that later connections only need to read the hash to know whether the saved layout
+//! is still valid.
+//!
+//! Snapshots are plain data describing the layout, for example to decide which profiles a device supports. They are
+//! not a GATT cache and do not skip service discovery: the platform [`Service`], [`Characteristic`] and
+//! [`Descriptor`] objects used to access the device can only be obtained by discovering them. When the saved layout
+//! is still valid, discovering with [`CacheMode::Cached`] lets the OS answer from its own attribute cache where it
+//! has one.
+//!
+//! This module is only available when the `snapshot` feature is enabled.
+//!
+//! [`Service`]: crate::Service
+//! [`Characteristic`]: crate::Characteristic
+//! [`Descriptor`]: crate::Descriptor
+//! [`CacheMode::Cached`]: crate::CacheMode::Cached
+
+use std::path::{Path, PathBuf};
+
+use crate::error::ErrorKind;
+use crate::gatt_database::GattDatabase;
+use crate::{Device, DeviceId, Error, Result};
+
+/// A directory of GATT database snapshots, with one file per device
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GattSnapshotStore {
+    dir: PathBuf,
+}
+
+/// A GATT database snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GattSnapshot {
+    /// The device the snapshot was taken from
+    pub device: DeviceId,
+    /// The device's database hash when the snapshot was taken
+    pub hash: [u8; 16],
+    /// The snapshot
+    pub database: GattDatabase,
+}
+
+fn io_error(err: std::io::Error, path: &Path) -> Error {
+    Error::new(
+        ErrorKind::Other,
+        Some(Box::new(err)),
+        format!("accessing {}", path.display()),
+    )
+}
+
+impl GattSnapshotStore {
+    /// Creates a store which keeps its files in `dir`. The directory is created when the first snapshot is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        GattSnapshotStore { dir: dir.into() }
+    }
+
+    /// The directory containing the snapshot files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, device: &DeviceId) -> PathBuf {
+        let name: String = device
+            .to_string()
+            .chars()
+            .map(|x| if x.is_ascii_alphanumeric() || x == '-' { x } else { '_' })
+            .collect();
+        self.dir.join(name).with_extension("json")
+    }
+
+    /// Loads the snapshot for `device`, returning `None` if there is no valid snapshot.
+    pub fn load(&self, device: &DeviceId) -> Result<Option<GattSnapshot>> {
+        let path = self.path(device);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(err, &path)),
+        };
+
+        // A corrupt or outdated file is treated as missing and will be overwritten by the next `store`
+        Ok(serde_json::from_slice::<GattSnapshot>(&data)
+            .ok()
+            .filter(|x| x.device == *device))
+    }
+
+    /// Stores `snapshot`, replacing any existing snapshot for the same device.
+    pub fn store(&self, snapshot: &GattSnapshot) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).map_err(|err| io_error(err, &self.dir))?;
+
+        let path = self.path(&snapshot.device);
+        let data = serde_json::to_vec(snapshot)
+            .map_err(|err| Error::new(ErrorKind::Internal, Some(Box::new(err)), String::new()))?;
+
+        // Write to a temporary file and rename it so that readers never see a partially written snapshot
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data).map_err(|err| io_error(err, &tmp))?;
+        std::fs::rename(&tmp, &path).map_err(|err| io_error(err, &path))
+    }
+
+    /// Removes the snapshot for `device`, if any.
+    pub fn remove(&self, device: &DeviceId) -> Result<()> {
+        let path = self.path(device);
+        match std::fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(io_error(err, &path)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the GATT database of `device`, using the stored snapshot if the device's database hash is unchanged.
+    ///
+    /// If the hash does not match, the database is discovered with [`Device::gatt_database`] and the snapshot
+    /// is replaced. If the device or platform does not expose a database hash (an error with a kind of
+    /// [`NotFound`][ErrorKind::NotFound] or [`NotSupported`][ErrorKind::NotSupported]), the database is always
+    /// discovered and nothing is stored. Other errors reading the hash are returned.
+    pub async fn gatt_database(&self, device: &Device) -> Result<GattDatabase> {
+        let hash = match device.database_hash().await {
+            Ok(hash) => hash,
+            Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::NotSupported) => {
+                return device.gatt_database().await
+            }
+            Err(err) => return Err(err),
+        };
+
+        let id = device.id();
+        if let Some(snapshot) = self.load(&id)? {
+            if snapshot.hash == hash {
+                return Ok(snapshot.database);
+            }
+        }
+
+        let snapshot = GattSnapshot {
+            device: id,
+            hash,
+            database: device.gatt_database().await?,
+        };
+        self.store(&snapshot)?;
+        Ok(snapshot.database)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btuuid::services;
+    use crate::gatt_database::GattService;
+
+    /// A store in a new temporary directory, which is removed when dropped
+    struct TempStore(GattSnapshotStore);
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("bluest-gatt-snapshot-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            TempStore(GattSnapshotStore::new(dir))
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(self.0.dir());
+        }
+    }
+
+    fn snapshot(device: DeviceId, hash: u8) -> GattSnapshot {
+        GattSnapshot {
+            device,
+            hash: [hash; 16],
+            database: GattDatabase {
+                services: vec![GattService {
+                    uuid: services::BATTERY,
+                    handle: Some(0x0010),
+                    is_primary: Some(true),
+                    included_services: Vec::new(),
+                    characteristics: Vec::new(),
+                }],
+            },
+        }
+    }
+
+    /// Creates a `DeviceId` through its serialized form, which is the only public way to construct one
+    fn device_id(n: u8) -> DeviceId {
+        #[cfg(target_os = "linux")]
+        let value = serde_json::json!(format!("00:11:22:33:44:{n:02X}"));
+        #[cfg(target_os = "windows")]
+        let value = serde_json::json!({ "Windows": format!("BluetoothLE#{n}").encode_utf16().collect::<Vec<_>>() });
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        let value = serde_json::json!(format!("00000000-0000-0000-0000-0000000000{n:02x}"));
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn load_missing() {
+        let store = TempStore::new("missing");
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), None);
+        store.0.remove(&device_id(1)).unwrap();
+    }
+
+    #[test]
+    fn store_load_remove() {
+        let store = TempStore::new("store");
+        let first = snapshot(device_id(1), 1);
+        let second = snapshot(device_id(2), 2);
+
+        store.0.store(&first).unwrap();
+        store.0.store(&second).unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), Some(first));
+        assert_eq!(store.0.load(&device_id(2)).unwrap(), Some(second.clone()));
+
+        let replaced = snapshot(device_id(1), 3);
+        store.0.store(&replaced).unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), Some(replaced));
+
+        store.0.remove(&device_id(1)).unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), None);
+        assert_eq!(store.0.load(&device_id(2)).unwrap(), Some(second));
+    }
+
+    #[test]
+    fn invalid_snapshots_are_missing() {
+        let store = TempStore::new("invalid");
+        std::fs::create_dir_all(store.0.dir()).unwrap();
+
+        std::fs::write(store.0.path(&device_id(1)), b"not json").unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), None);
+
+        // A snapshot for a different device stored under this device's name is ignored
+        let other = serde_json::to_vec(&snapshot(device_id(2), 2)).unwrap();
+        std::fs::write(store.0.path(&device_id(1)), other).unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), None);
+
+        store.0.store(&snapshot(device_id(1), 1)).unwrap();
+        assert_eq!(store.0.load(&device_id(1)).unwrap(), Some(snapshot(device_id(1), 1)));
+    }
+}
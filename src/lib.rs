@@ -53,10 +53,13 @@
 //!   - Discovering device [services][Device::discover_services]
 //!   - Discovering service [characteristics][Service::discover_characteristics]
 //!   - Discovering characteristic [descriptors][Characteristic::discover_descriptors]
+//!   - Taking [snapshots][Device::gatt_database] of a device's entire GATT database
 //!   - [Read][Characteristic::read], [write][Characteristic::write] (including
 //!     [write without response][Characteristic::write_without_response]), and
 //!     [notify/indicate][Characteristic::notify] operations on remote characteristics
+//!   - [Reliable write][Device::reliable_write] transactions (spanning multiple characteristics on Windows)
 //!   - [Read][Descriptor::read] and [write][Descriptor::write] operations on characteristic descriptors
+//!   - Decoding standard descriptors such as the [presentation format][Characteristic::presentation_format]
 //!
 //! # Asynchronous runtimes
 //!
@@ -76,21 +79,51 @@
 //! Most Bluest APIs should behave consistently across all supported platforms. Those APIs with significant differences
 //! in behavior are summarized in the table below.
 //!
-//!| Method                                                   | MacOS/iOS | Windows | Linux |
-//!|----------------------------------------------------------|:---------:|:-------:|:-----:|
-//!| [`Adapter::connect_device`][Adapter::connect_device]                     | ✅ | ✨ | ✅ |
-//!| [`Adapter::disconnect_device`][Adapter::disconnect_device]               | ✅ | ✨ | ✅ |
-//!| [`Adapter::device_connection_events`][Adapter::device_connection_events] | 📱 | ✅ | ✅ |
-//!| [`Device::name`][Device::name]                                           | ✅ | ✅ | ⌛️ |
-//!| [`Device::is_paired`][Device::is_paired]                                 | ❌ | ✅ | ✅ |
-//!| [`Device::pair`][Device::pair]                                           | ✨ | ✅ | ✅ |
-//!| [`Device::pair_with_agent`][Device::pair_with_agent]                     | ✨ | ✅ | ✅ |
-//!| [`Device::unpair`][Device::unpair]                                       | ❌ | ✅ | ✅ |
-//!| [`Device::rssi`][Device::rssi]                                           | ✅ | ❌ | ❌ |
-//!| [`Service::uuid`][Service::uuid]                                         | ✅ | ✅ | ⌛️ |
-//!| [`Service::is_primary`][Service::is_primary]                             | ✅ | ❌ | ✅ |
-//!| [`Characteristic::uuid`][Characteristic::uuid]                           | ✅ | ✅ | ⌛️ |
-//!| [`Descriptor::uuid`][Descriptor::uuid]                                   | ✅ | ✅ | ⌛️ |
+//!| Method                                                                       | MacOS/iOS | Windows | Linux |
+//!|------------------------------------------------------------------------------|:---------:|:-------:|:-----:|
+//!| [`Adapter::bonded_devices`][Adapter::bonded_devices]                         | ❌ | ✅ | ✅ |
+//!| [`Adapter::connect_device`][Adapter::connect_device]                         | ✅ | ✨ | ✅ |
+//!| [`Adapter::disconnect_device`][Adapter::disconnect_device]                   | ✅ | ✨ | ✅ |
+//!| [`Adapter::device_connection_events`][Adapter::device_connection_events]     | 📱 | ✅ | ✅ |
+//!| [`Adapter::register_default_agent`][Adapter::register_default_agent]         | ❌ | ❌ | ✅ |
+//!| [`Adapter::local_oob_data`][Adapter::local_oob_data]                         | ❌ | ❌ | ✅ |
+//!| [`Adapter::export_bond`][Adapter::export_bond]                               | ❌ | ❌ | ✅ |
+//!| [`Adapter::import_bond`][Adapter::import_bond]                               | ❌ | ❌ | ✅ |
+//!| [`Device::name`][Device::name]                                               | ✅ | ✅ | ⌛️ |
+//!| [`Device::is_paired`][Device::is_paired]                                     | ❌ | ✅ | ✅ |
+//!| [`Device::pair`][Device::pair]                                               | ✨ | ✅ | ✅ |
+//!| [`Device::pair_with_agent`][Device::pair_with_agent]                         | ✨ | ✅ | ✅ |
+//!| [`Device::pair_with_oob_data`][Device::pair_with_oob_data]                   | ❌ | ❌ | ✅ |
+//!| [`Device::unpair`][Device::unpair]                                           | ❌ | ✅ | ✅ |
+//!| [`Device::remove_bond`][Device::remove_bond]                                 | ❌ | ❌ | ✅ |
+//!| [`Device::bond_info`][Device::bond_info]                                     | ❌ | ❌ | ✅ |
+//!| [`Device::pairing_events`][Device::pairing_events]                           | ❌ | ✅ | ✅ |
+//!| [`Device::is_trusted`][Device::is_trusted]                                   | ❌ | ❌ | ✅ |
+//!| [`Device::set_trusted`][Device::set_trusted]                                 | ❌ | ❌ | ✅ |
+//!| [`Device::is_blocked`][Device::is_blocked]                                   | ❌ | ❌ | ✅ |
+//!| [`Device::set_blocked`][Device::set_blocked]                                 | ❌ | ❌ | ✅ |
+//!| [`Device::security_level`][Device::security_level]                           | ❌ | ✅ | ✅ |
+//!| [`Device::require_security`][Device::require_security]                       | ✨ | ✅ | ✅ |
+//!| [`Device::rssi`][Device::rssi]                                               | ✅ | ❌ | ❌ |
+//!| [`Device::connection_parameters`][Device::connection_parameters]             | ❌ | ✅ | ❌ |
+//!| [`Device::connection_phy`][Device::connection_phy]                           | ❌ | ✅ | ✅ |
+//!| [`Device::request_connection_priority`][Device::request_connection_priority] | ❌ | ✅ | ✅ |
+//!| [`Device::set_preferred_phy`][Device::set_preferred_phy]                     | ❌ | ❌ | ✅ |
+//!| [`Device::reliable_write`][Device::reliable_write]                           | ❌ | ✅ | ✅ |
+//!| [`Device::services_changed_range`][Device::services_changed_range]           | ❌ | ❌ | ✅ |
+//!| [`Device::characteristic_by_handle`][Device::characteristic_by_handle]       | ❌ | ✅ | ✅ |
+//!| [`Service::uuid`][Service::uuid]                                             | ✅ | ✅ | ⌛️ |
+//!| [`Service::is_primary`][Service::is_primary]                                 | ✅ | ❌ | ✅ |
+//!| [`Service::handle`][Service::handle]                                         | ❌ | ✅ | ✅ |
+//!| [`Service::handle_range`][Service::handle_range]                             | ❌ | ✅ | ✅ |
+//!| [`Characteristic::uuid`][Characteristic::uuid]                               | ✅ | ✅ | ⌛️ |
+//!| [`Characteristic::handle`][Characteristic::handle]                           | ❌ | ✅ | ✅ |
+//!| [`Characteristic::read_at`][Characteristic::read_at]                         | ❌ | ❌ | ✅ |
+//!| [`Characteristic::write_at`][Characteristic::write_at]                       | ❌ | ❌ | ✅ |
+//!| [`Characteristic::set_cccd`][Characteristic::set_cccd]                       | ❌ | ✅ | ❌ |
+//!| [`Descriptor::uuid`][Descriptor::uuid]                                       | ✅ | ✅ | ⌛️ |
+//!| [`Descriptor::handle`][Descriptor::handle]                                   | ❌ | ✅ | ✅ |
+//!| [`Descriptor::read_at`][Descriptor::read_at]                                 | ❌ | ❌ | ✅ |
 //!
 //! ✅ = supported  
 //! ✨ = managed automatically by the OS, this method is a no-op  
@@ -107,7 +140,17 @@
 //! # Feature flags
 //!
 //! The `serde` feature is available to enable serializing/deserializing device
-//! identifiers.
+//! identifiers and GATT database snapshots.
+//!
+//! The `derive` feature provides `#[derive(GattProfile)]`, which generates the service and characteristic discovery
+//! code for a struct describing a GATT service.
+//!
+//! The `snapshot` feature provides `gatt_snapshot::GattSnapshotStore`, which persists GATT database snapshots to
+//! disk so that reconnections only need to read the device's database hash to know whether a stored layout is still
+//! valid. Snapshots do not replace service discovery.
+//!
+//! The `testing` feature provides `pairing::testing`, which checks the callbacks a pairing agent receives for each
+//! association model.
 //!
 //! # Examples
 //!
@@ -116,12 +159,24 @@
 //! [examples folder]: https://github.com/alexmoon/bluest/tree/master/bluest/examples
 
 mod adapter;
+mod aes;
 pub mod btuuid;
 mod characteristic;
+pub mod codec;
 mod descriptor;
+pub mod descriptors;
 mod device;
+pub mod ead;
 pub mod error;
+pub mod gatt_database;
+#[cfg(feature = "snapshot")]
+pub mod gatt_snapshot;
 pub mod pairing;
+pub mod privacy;
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod profile;
+mod reliable_write;
 mod service;
 mod util;
 
@@ -133,15 +188,19 @@ mod corebluetooth;
 mod windows;
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(target_os = "linux")]
 pub use ::bluer::Uuid;
 pub use adapter::Adapter;
+#[cfg(feature = "derive")]
+pub use bluest_derive::GattProfile;
 pub use btuuid::BluetoothUuidExt;
 pub use characteristic::Characteristic;
 pub use descriptor::Descriptor;
 pub use device::Device;
 pub use error::Error;
+pub use reliable_write::ReliableWrite;
 pub use service::Service;
 pub use sys::DeviceId;
 #[cfg(not(target_os = "linux"))]
@@ -175,6 +234,15 @@ pub enum ConnectionEvent {
     Connected,
 }
 
+/// Events generated by [`Device::service_changes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServicesChangedEvent {
+    /// The range of attribute handles affected by the change, if reported by the platform (Linux only)
+    pub range: Option<HandleRange>,
+    /// The services invalidated by the change, if reported by the platform (MacOS/iOS only)
+    pub invalidated_services: Vec<Service>,
+}
+
 /// Represents a device discovered during a scan operation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AdvertisingDevice {
@@ -201,6 +269,13 @@ pub struct AdvertisementData {
     pub tx_power_level: Option<i16>,
     /// Set to true for connectable advertising packets
     pub is_connectable: bool,
+    /// The payload of an Encrypted Data AD structure (CSS §A.1.23), which can be decrypted with
+    /// [`decrypt`][Self::decrypt]
+    ///
+    /// # Platform specific
+    ///
+    /// Always `None` on MacOS/iOS, where CoreBluetooth does not provide the raw advertising data.
+    pub encrypted_data: Option<Vec<u8>>,
 }
 
 /// Manufacturer specific data included in Bluetooth advertisements. See the Bluetooth Core Specification Supplement
@@ -213,11 +288,118 @@ pub struct ManufacturerData {
     pub data: Vec<u8>,
 }
 
+/// An inclusive range of GATT attribute handles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandleRange {
+    /// The first handle in the range
+    pub start: u16,
+    /// The last handle in the range
+    pub end: u16,
+}
+
+impl HandleRange {
+    /// Returns `true` if `handle` is within this range
+    pub fn contains(&self, handle: u16) -> bool {
+        (self.start..=self.end).contains(&handle)
+    }
+
+    /// Parses the value of a Service Changed characteristic indication.
+    #[cfg_attr(not(target_os = "linux"), allow(unused))]
+    pub(crate) fn from_service_changed(value: &[u8]) -> Result<Self> {
+        match *value {
+            [a, b, c, d] => Ok(HandleRange {
+                start: u16::from_le_bytes([a, b]),
+                end: u16::from_le_bytes([c, d]),
+            }),
+            _ => Err(Error::new(
+                error::ErrorKind::Other,
+                None,
+                format!("invalid service changed value: {value:?}"),
+            )),
+        }
+    }
+}
+
+/// The type of write operation to use when writing a characteristic value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WriteKind {
+    /// A write request, which the device acknowledges with a write response
+    WithResponse,
+    /// A write command, which is not acknowledged by the device
+    WithoutResponse,
+}
+
+/// Whether a read or discovery operation may be answered from the OS's cache
+///
+/// # Platform specific
+///
+/// - On Linux, [`Cached`][CacheMode::Cached] reads return the last value BlueZ received from the device, and fail if
+///   there is none. BlueZ keeps its own copy of the GATT database up to date, so discovery always uses it regardless
+///   of the cache mode.
+/// - On Windows, [`Cached`][CacheMode::Cached] operations query the device if the OS has no cached result.
+/// - On MacOS/iOS, [`Cached`][CacheMode::Cached] operations return the results of earlier reads and discoveries,
+///   and reads fail if the value has not been read.
+///
+/// [`Uncached`][CacheMode::Uncached] operations always query the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheMode {
+    /// Use the OS's cached result, if available
+    Cached,
+    /// Query the device
+    Uncached,
+}
+
+/// The LE connection parameters of a connection, as returned by [`Device::connection_parameters`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectionParameters {
+    /// The time between connection events
+    pub interval: Duration,
+    /// The number of connection events the peripheral may skip
+    pub peripheral_latency: u16,
+    /// The time without a valid packet after which the connection is considered lost
+    pub supervision_timeout: Duration,
+}
+
+/// An LE physical layer
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phy {
+    /// The LE 1M PHY
+    Le1M,
+    /// The LE 2M PHY
+    Le2M,
+    /// The LE Coded PHY
+    LeCoded,
+}
+
+/// The PHYs used by a connection, as returned by [`Device::connection_phy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectionPhy {
+    /// The PHY used to transmit to the device
+    pub tx: Phy,
+    /// The PHY used to receive from the device
+    pub rx: Phy,
+}
+
+/// A preference for the connection parameters of a connection, used by [`Device::request_connection_priority`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConnectionPriority {
+    /// The OS default parameters, balancing throughput, latency and power use
+    #[default]
+    Balanced,
+    /// Short connection intervals for high throughput and low latency
+    HighThroughput,
+    /// Long connection intervals for low power use
+    LowPower,
+}
+
 /// GATT characteristic properties as defined in the Bluetooth Core Specification, Vol 3, Part G, §3.3.1.1.
 /// Extended properties are also included as defined in §3.3.3.1.
 #[allow(missing_docs)]
 #[non_exhaustive]
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CharacteristicProperties {
     pub broadcast: bool,
     pub read: bool,
@@ -266,3 +448,22 @@ impl CharacteristicProperties {
             | (u32::from(self.writable_auxiliaries) << 9)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_changed_range() {
+        let range = HandleRange::from_service_changed(&[0x10, 0x00, 0xff, 0xff]).unwrap();
+        assert_eq!(
+            range,
+            HandleRange {
+                start: 0x0010,
+                end: 0xffff
+            }
+        );
+        assert!(HandleRange::from_service_changed(&[0x10, 0x00, 0xff]).is_err());
+        assert!(HandleRange::from_service_changed(&[]).is_err());
+    }
+}
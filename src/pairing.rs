@@ -1,8 +1,20 @@
 //! Custom Bluetooth pairing agent.
 
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
+use futures_channel::mpsc;
+
+use crate::error::ErrorKind;
+use crate::{sys, Device, DeviceId, Result, Uuid};
+
+mod callback;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
-use crate::Device;
+pub use callback::{CallbackPairingAgent, PairingCancelHandle, PairingRequest, PairingResponder};
 
 /// Bluetooth input/output capabilities for pairing
 ///
@@ -22,6 +34,23 @@ pub enum IoCapability {
     KeyboardDisplay,
 }
 
+/// The security level of a connection
+///
+/// These are the levels of LE security mode 1, see the Bluetooth Core Specification, Vol 3, Part C, §10.2.1. Levels
+/// are ordered from least to most secure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum SecurityLevel {
+    /// No authentication and no encryption
+    None,
+    /// Encryption with an unauthenticated key, e.g. from "JustWorks" pairing
+    Encrypted,
+    /// Encryption with an authenticated key, e.g. from passkey entry or numeric comparison
+    Authenticated,
+    /// Encryption with an authenticated 128-bit key from LE Secure Connections pairing
+    SecureConnections,
+}
+
 /// An error indicating the pairing request has been rejected
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[non_exhaustive]
@@ -50,6 +79,16 @@ impl std::fmt::Display for InvalidPasskey {
 impl std::error::Error for InvalidPasskey {}
 
 /// A Bluetooth 6-digit passkey
+///
+/// Passkeys are displayed as six digits, including leading zeros.
+///
+/// ```
+/// use bluest::pairing::Passkey;
+///
+/// let passkey = Passkey::new(1234);
+/// assert_eq!(passkey.to_string(), "001234");
+/// assert_eq!("001234".parse::<Passkey>(), Ok(passkey));
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Passkey(u32);
 
@@ -59,6 +98,31 @@ impl Passkey {
         assert!(n <= 999_999);
         Passkey(n)
     }
+
+    /// Generates a random `Passkey` using the operating system's cryptographically secure random number generator
+    ///
+    /// Every passkey is equally likely.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the operating system's random number generator fails.
+    pub fn random() -> Self {
+        loop {
+            let mut bytes = [0; 4];
+            getrandom::getrandom(&mut bytes).expect("failed to generate a random passkey");
+            if let Some(passkey) = Self::from_random(u32::from_le_bytes(bytes)) {
+                return passkey;
+            }
+        }
+    }
+
+    /// Converts a uniformly distributed random value into a uniformly distributed passkey, or `None` if the value
+    /// must be rejected
+    fn from_random(n: u32) -> Option<Self> {
+        // The largest multiple of 1,000,000 which fits in a u32. Rejecting values above it avoids modulo bias.
+        const LIMIT: u32 = u32::MAX - u32::MAX % 1_000_000;
+        (n < LIMIT).then_some(Passkey(n % 1_000_000))
+    }
 }
 
 impl std::fmt::Display for Passkey {
@@ -96,6 +160,13 @@ impl std::str::FromStr for Passkey {
 }
 
 /// A custom pairing agent responsible for interacting with the user during the peripheral pairing process.
+///
+/// # Platform specific
+///
+/// On Linux, BlueZ is told the agent's [`io_capability`][Self::io_capability] by the set of methods the agent
+/// handles. Requests for methods which are not listed as supported for the agent's capability are rejected without
+/// calling the agent. The `authorize_service`, `request_pin_code` and `display_pin_code` methods are only used on
+/// Linux.
 #[async_trait]
 pub trait PairingAgent: Send + Sync {
     /// The input/output capabilities of this agent
@@ -129,6 +200,48 @@ pub trait PairingAgent: Send + Sync {
     ///
     /// Must be supported if `io_capability` is `DisplayOnly`, `DisplayYesNo`, or `KeyboardDisplay`
     fn display_passkey(&self, _device: &Device, _passkey: Passkey) {}
+
+    /// Request authorization from the user for an incoming pairing attempt which uses the "JustWorks" association
+    /// model.
+    ///
+    /// Only used if `io_capability` is `DisplayYesNo` or `KeyboardDisplay`. The default implementation calls
+    /// [`confirm`][Self::confirm].
+    async fn request_authorization(&self, device: &Device) -> Result<(), PairingRejected> {
+        self.confirm(device).await
+    }
+
+    /// Request authorization from the user for a device to connect to the local `service`.
+    ///
+    /// Only used if `io_capability` is `DisplayYesNo` or `KeyboardDisplay`
+    async fn authorize_service(&self, _device: &Device, _service: Uuid) -> Result<(), PairingRejected> {
+        Err(PairingRejected)
+    }
+
+    /// Request a legacy PIN code from the user. The PIN code must be between 1 and 16 alphanumeric characters.
+    ///
+    /// Only used when pairing with devices which do not support Secure Simple Pairing. Must be supported if
+    /// `io_capability` is `KeyboardOnly` or `KeyboardDisplay`
+    async fn request_pin_code(&self, _device: &Device) -> Result<String, PairingRejected> {
+        Err(PairingRejected)
+    }
+
+    /// Display a legacy PIN code to the user, to be entered on the device.
+    ///
+    /// Only used when pairing with devices which do not support Secure Simple Pairing. Must be supported if
+    /// `io_capability` is `DisplayOnly`, `DisplayYesNo`, or `KeyboardDisplay`
+    fn display_pin_code(&self, _device: &Device, _pin_code: &str) {}
+
+    /// Completes when the user aborts pairing.
+    ///
+    /// Pairing with this agent is canceled when the returned future completes. The default implementation never
+    /// completes.
+    ///
+    /// # Platform specific
+    ///
+    /// On MacOS/iOS, where pairing agents are not used by the OS, this method is not called.
+    async fn canceled(&self) {
+        futures_util::future::pending().await
+    }
 }
 
 /// The simplest possible pairing agent.
@@ -148,3 +261,504 @@ impl PairingAgent for NoInputOutputPairingAgent {
         Ok(())
     }
 }
+
+/// The LE role of a device, as reported in [`OobData`]
+///
+/// See the Bluetooth Core Specification Supplement, Part A, §1.17
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LeRole {
+    /// Only the peripheral role is supported
+    PeripheralOnly,
+    /// Only the central role is supported
+    CentralOnly,
+    /// Both roles are supported, and the peripheral role is preferred for connection establishment
+    PeripheralPreferred,
+    /// Both roles are supported, and the central role is preferred for connection establishment
+    CentralPreferred,
+}
+
+/// An error returned when decoding an invalid LE OOB data block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidOobData(());
+
+impl std::fmt::Display for InvalidOobData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid OOB data")
+    }
+}
+
+impl std::error::Error for InvalidOobData {}
+
+/// LE Secure Connections out-of-band (OOB) pairing data
+///
+/// OOB data is exchanged over another channel, such as NFC or a QR code, to authenticate pairing without user
+/// interaction. The data of the local adapter is created with
+/// [`Adapter::local_oob_data`][crate::Adapter::local_oob_data], and the data of a remote device is used with
+/// [`Device::pair_with_oob_data`][crate::Device::pair_with_oob_data].
+///
+/// [`encode`][Self::encode] and [`decode`][Self::decode] convert to and from the LE OOB data block format used by
+/// NFC and other OOB channels, which is a sequence of AD structures.
+///
+/// ```
+/// use bluest::pairing::{LeRole, OobData};
+///
+/// let data = OobData {
+///     address: [0xc0, 0x11, 0x22, 0x33, 0x44, 0x55],
+///     random_address: true,
+///     role: Some(LeRole::PeripheralOnly),
+///     confirm: [0xaa; 16],
+///     random: [0xbb; 16],
+///     flags: Some(0x06),
+/// };
+///
+/// let block = data.encode();
+/// assert_eq!(&block[..9], &[0x08, 0x1b, 0x55, 0x44, 0x33, 0x22, 0x11, 0xc0, 0x01]);
+/// assert_eq!(OobData::decode(&block), Ok(data));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OobData {
+    /// The Bluetooth device address, most significant byte first
+    pub address: [u8; 6],
+    /// Whether `address` is a random address rather than a public address
+    pub random_address: bool,
+    /// The LE role of the device
+    pub role: Option<LeRole>,
+    /// The LE Secure Connections confirmation value, in the byte order of the data block
+    pub confirm: [u8; 16],
+    /// The LE Secure Connections random value, in the byte order of the data block
+    pub random: [u8; 16],
+    /// The advertising flags of the device
+    pub flags: Option<u8>,
+}
+
+const AD_FLAGS: u8 = 0x01;
+const AD_LE_ADDRESS: u8 = 0x1b;
+const AD_LE_ROLE: u8 = 0x1c;
+const AD_LE_SC_CONFIRM: u8 = 0x22;
+const AD_LE_SC_RANDOM: u8 = 0x23;
+
+impl OobData {
+    /// Encodes this data as an LE OOB data block.
+    pub fn encode(&self) -> Vec<u8> {
+        fn push(buf: &mut Vec<u8>, ad_type: u8, data: &[u8]) {
+            buf.push(data.len() as u8 + 1);
+            buf.push(ad_type);
+            buf.extend_from_slice(data);
+        }
+
+        let mut buf = Vec::new();
+
+        // The address is little-endian, followed by its type
+        let [a, b, c, d, e, f] = self.address;
+        push(
+            &mut buf,
+            AD_LE_ADDRESS,
+            &[f, e, d, c, b, a, u8::from(self.random_address)],
+        );
+
+        if let Some(role) = self.role {
+            let role = match role {
+                LeRole::PeripheralOnly => 0x00,
+                LeRole::CentralOnly => 0x01,
+                LeRole::PeripheralPreferred => 0x02,
+                LeRole::CentralPreferred => 0x03,
+            };
+            push(&mut buf, AD_LE_ROLE, &[role]);
+        }
+        push(&mut buf, AD_LE_SC_CONFIRM, &self.confirm);
+        push(&mut buf, AD_LE_SC_RANDOM, &self.random);
+        if let Some(flags) = self.flags {
+            push(&mut buf, AD_FLAGS, &[flags]);
+        }
+
+        buf
+    }
+
+    /// Decodes an LE OOB data block.
+    ///
+    /// The block must contain the device address and the LE Secure Connections confirmation and random values. AD
+    /// structures of other types are ignored.
+    pub fn decode(mut data: &[u8]) -> Result<Self, InvalidOobData> {
+        let mut address = None;
+        let mut role = None;
+        let mut confirm = None;
+        let mut random = None;
+        let mut flags = None;
+
+        while let Some((&len, rest)) = data.split_first() {
+            // A zero length structure marks the end of the significant part of the data
+            if len == 0 {
+                break;
+            }
+            let len = usize::from(len);
+            if rest.len() < len {
+                return Err(InvalidOobData(()));
+            }
+            let (ad_type, value) = (rest[0], &rest[1..len]);
+            data = &rest[len..];
+
+            match ad_type {
+                AD_LE_ADDRESS => match *value {
+                    [a, b, c, d, e, f, kind] => address = Some(([f, e, d, c, b, a], kind & 0x01 != 0)),
+                    _ => return Err(InvalidOobData(())),
+                },
+                AD_LE_ROLE => {
+                    role = Some(match value {
+                        [0x00] => LeRole::PeripheralOnly,
+                        [0x01] => LeRole::CentralOnly,
+                        [0x02] => LeRole::PeripheralPreferred,
+                        [0x03] => LeRole::CentralPreferred,
+                        _ => return Err(InvalidOobData(())),
+                    })
+                }
+                AD_LE_SC_CONFIRM => confirm = Some(value.try_into().map_err(|_| InvalidOobData(()))?),
+                AD_LE_SC_RANDOM => random = Some(value.try_into().map_err(|_| InvalidOobData(()))?),
+                AD_FLAGS => flags = Some(*value.first().ok_or(InvalidOobData(()))?),
+                _ => (),
+            }
+        }
+
+        let (address, random_address) = address.ok_or(InvalidOobData(()))?;
+        Ok(OobData {
+            address,
+            random_address,
+            role,
+            confirm: confirm.ok_or(InvalidOobData(()))?,
+            random: random.ok_or(InvalidOobData(()))?,
+            flags,
+        })
+    }
+}
+
+/// Information about the bond with a device, as returned by [`Device::bond_info`][crate::Device::bond_info]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BondInfo {
+    /// Whether an LE long term key (LTK) is stored for the device
+    pub long_term_key: bool,
+    /// Whether the identity resolving key (IRK) of the device is stored
+    pub identity_resolving_key: bool,
+    /// Whether the stored keys were created by an authenticated pairing method
+    pub authenticated: bool,
+    /// The size of the encryption key in bytes, if known
+    pub key_size: Option<u8>,
+}
+
+/// The stored bond with a device, including its keys
+///
+/// Created with [`Adapter::export_bond`][crate::Adapter::export_bond] and restored with
+/// [`Adapter::import_bond`][crate::Adapter::import_bond]. The data contains the keys shared with the device and must
+/// be kept secret.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BondData {
+    /// The bonded device
+    pub device: DeviceId,
+    /// The platform-specific bond data. On Linux, this is BlueZ's storage file for the device.
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Debug for BondData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BondData")
+            .field("device", &self.device)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A default pairing agent registered with [`Adapter::register_default_agent`][crate::Adapter::register_default_agent]
+///
+/// The agent is unregistered when this guard is dropped.
+#[derive(Debug)]
+pub struct DefaultAgentGuard {
+    _inner: sys::adapter::DefaultAgentImpl,
+}
+
+impl DefaultAgentGuard {
+    pub(crate) fn new(inner: sys::adapter::DefaultAgentImpl) -> Self {
+        DefaultAgentGuard { _inner: inner }
+    }
+}
+
+/// An agent registered with [`Device::set_auto_pair`]
+///
+/// The agent is unregistered when this guard is dropped.
+#[derive(Debug)]
+#[must_use = "the agent is unregistered when the guard is dropped"]
+pub struct AutoPairGuard {
+    id: u64,
+}
+
+impl Drop for AutoPairGuard {
+    fn drop(&mut self) {
+        AUTO_PAIR_AGENTS.lock().unwrap().retain(|x| x.id != self.id);
+    }
+}
+
+struct AutoPairAgent {
+    id: u64,
+    device: DeviceId,
+    agent: Arc<dyn PairingAgent>,
+}
+
+/// Agents registered with [`Device::set_auto_pair`], in the order they were registered
+static AUTO_PAIR_AGENTS: Mutex<Vec<AutoPairAgent>> = Mutex::new(Vec::new());
+
+pub(crate) fn set_auto_pair_agent(device: DeviceId, agent: Arc<dyn PairingAgent>) -> AutoPairGuard {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    AUTO_PAIR_AGENTS
+        .lock()
+        .unwrap()
+        .push(AutoPairAgent { id, device, agent });
+    AutoPairGuard { id }
+}
+
+/// The agent most recently registered with [`Device::set_auto_pair`] for `device` which is still registered, if any
+pub(crate) fn auto_pair_agent(device: &DeviceId) -> Option<Arc<dyn PairingAgent>> {
+    let agents = AUTO_PAIR_AGENTS.lock().unwrap();
+    agents
+        .iter()
+        .rev()
+        .find(|x| x.device == *device)
+        .map(|x| x.agent.clone())
+}
+
+/// A change in the pairing state of a device, reported by [`Device::pairing_events`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PairingEvent {
+    /// Pairing with the device has started
+    Started,
+    /// Pairing with the device has completed successfully
+    Paired,
+    /// Pairing with the device has failed. Pairing failures reported by the OS have the kind
+    /// [`PairingFailed`][ErrorKind::PairingFailed].
+    Failed(ErrorKind),
+    /// The device has been unpaired or its bond has been removed
+    Unpaired,
+}
+
+/// Subscribers registered with [`Device::pairing_events`]
+static PAIRING_EVENT_SENDERS: Mutex<Vec<(DeviceId, mpsc::UnboundedSender<PairingEvent>)>> = Mutex::new(Vec::new());
+
+/// Subscribes to the pairing events of `device`, removing any subscribers which have been dropped
+pub(crate) fn pairing_events(device: DeviceId) -> mpsc::UnboundedReceiver<PairingEvent> {
+    let (sender, receiver) = mpsc::unbounded();
+    let mut senders = PAIRING_EVENT_SENDERS.lock().unwrap();
+    senders.retain(|(_, sender)| !sender.is_closed());
+    senders.push((device, sender));
+    receiver
+}
+
+/// Sends `event` to the subscribers for `device`, removing any subscribers which have been dropped
+pub(crate) fn send_pairing_event(device: &DeviceId, event: PairingEvent) {
+    let mut senders = PAIRING_EVENT_SENDERS.lock().unwrap();
+    senders.retain(|(id, sender)| !sender.is_closed() && (id != device || sender.unbounded_send(event).is_ok()));
+}
+
+/// Runs the pairing operation `fut`, reporting its start and result to the subscribers for `device`
+pub(crate) async fn report_pairing(device: &DeviceId, fut: impl Future<Output = Result<()>>) -> Result<()> {
+    send_pairing_event(device, PairingEvent::Started);
+    let res = fut.await;
+    let event = match &res {
+        Ok(()) => PairingEvent::Paired,
+        Err(err) => PairingEvent::Failed(err.kind()),
+    };
+    send_pairing_event(device, event);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{FutureExt, StreamExt};
+
+    use super::*;
+
+    #[test]
+    fn passkey_rejection_sampling() {
+        // The largest multiple of 1,000,000 which fits in a u32
+        const LIMIT: u32 = 4_294_000_000;
+
+        assert_eq!(Passkey::from_random(0), Some(Passkey(0)));
+        assert_eq!(Passkey::from_random(999_999), Some(Passkey(999_999)));
+        assert_eq!(Passkey::from_random(1_000_000), Some(Passkey(0)));
+        assert_eq!(Passkey::from_random(LIMIT - 1), Some(Passkey(999_999)));
+        assert_eq!(Passkey::from_random(LIMIT), None);
+        assert_eq!(Passkey::from_random(u32::MAX), None);
+
+        for _ in 0..1000 {
+            let passkey = Passkey::random();
+            assert!(passkey.0 <= 999_999);
+            assert_eq!(passkey.to_string().len(), 6);
+        }
+    }
+
+    #[test]
+    fn pairing_event_subscribers() {
+        let device = DeviceId::test(0x45);
+        let other = DeviceId::test(0x46);
+        let subscribers = || {
+            PAIRING_EVENT_SENDERS
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(id, _)| *id == device || *id == other)
+                .count()
+        };
+
+        let mut events = pairing_events(device.clone());
+        let dropped = pairing_events(other.clone());
+        assert_eq!(subscribers(), 2);
+
+        // Dropped subscribers for any device are removed
+        drop(dropped);
+        send_pairing_event(&device, PairingEvent::Started);
+        assert_eq!(subscribers(), 1);
+        assert_eq!(events.next().now_or_never(), Some(Some(PairingEvent::Started)));
+
+        let dropped = pairing_events(other.clone());
+        drop(dropped);
+        drop(events);
+        let _events = pairing_events(other.clone());
+        assert_eq!(subscribers(), 1);
+    }
+
+    fn oob_data() -> OobData {
+        OobData {
+            address: [0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+            random_address: true,
+            role: Some(LeRole::CentralPreferred),
+            confirm: [0xc0; 16],
+            random: [0x4a; 16],
+            flags: Some(0x06),
+        }
+    }
+
+    /// An AD structure with the given type and value
+    fn ad(ad_type: u8, value: &[u8]) -> Vec<u8> {
+        let mut buf = vec![value.len() as u8 + 1, ad_type];
+        buf.extend_from_slice(value);
+        buf
+    }
+
+    /// The AD structures required by [`OobData::decode`]
+    fn required() -> Vec<u8> {
+        [
+            ad(AD_LE_ADDRESS, &[0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00]),
+            ad(AD_LE_SC_CONFIRM, &[0xc0; 16]),
+            ad(AD_LE_SC_RANDOM, &[0x4a; 16]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn oob_data_round_trip() {
+        let data = oob_data();
+        let encoded = data.encode();
+        assert_eq!(
+            &encoded[..9],
+            &[0x08, AD_LE_ADDRESS, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01]
+        );
+        assert_eq!(OobData::decode(&encoded), Ok(data));
+
+        let decoded = OobData::decode(&required()).unwrap();
+        assert_eq!(decoded.address, [0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        assert!(!decoded.random_address);
+        assert_eq!(decoded.role, None);
+        assert_eq!(decoded.flags, None);
+    }
+
+    #[test]
+    fn oob_data_ignores_unknown_structures() {
+        let data = [ad(0xff, &[0x01, 0x02]), required(), ad(0x09, b"name")].concat();
+        assert!(OobData::decode(&data).is_ok());
+    }
+
+    #[test]
+    fn oob_data_truncated() {
+        let data = required();
+        for len in [1, 5, data.len() - 1] {
+            assert_eq!(OobData::decode(&data[..len]), Err(InvalidOobData(())));
+        }
+        // A structure whose length exceeds the remaining data
+        let data = [required(), vec![0x05, AD_FLAGS, 0x06]].concat();
+        assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+    }
+
+    #[test]
+    fn oob_data_bad_address() {
+        for address in [&[0x55, 0x44, 0x33, 0x22, 0x11, 0x00][..], &[0; 8]] {
+            let data = [ad(AD_LE_ADDRESS, address), required()].concat();
+            assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+        }
+    }
+
+    #[test]
+    fn oob_data_bad_role() {
+        for role in [&[0x04][..], &[], &[0x00, 0x00]] {
+            let data = [required(), ad(AD_LE_ROLE, role)].concat();
+            assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+        }
+        let data = [required(), ad(AD_LE_ROLE, &[0x01])].concat();
+        assert_eq!(OobData::decode(&data).unwrap().role, Some(LeRole::CentralOnly));
+    }
+
+    #[test]
+    fn oob_data_missing_values() {
+        let address = ad(AD_LE_ADDRESS, &[0; 7]);
+        let confirm = ad(AD_LE_SC_CONFIRM, &[0; 16]);
+        let random = ad(AD_LE_SC_RANDOM, &[0; 16]);
+        for data in [
+            [confirm.clone(), random.clone()].concat(),
+            [address.clone(), random].concat(),
+            [address.clone(), confirm].concat(),
+        ] {
+            assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+        }
+
+        let data = [address, ad(AD_LE_SC_CONFIRM, &[0; 15]), ad(AD_LE_SC_RANDOM, &[0; 16])].concat();
+        assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+        assert_eq!(OobData::decode(&[]), Err(InvalidOobData(())));
+    }
+
+    #[test]
+    fn oob_data_zero_length_terminates() {
+        // Data after a zero length structure is ignored, even if it is not valid
+        let data = [required(), vec![0x00, 0xff, AD_LE_ROLE, 0x07]].concat();
+        assert_eq!(OobData::decode(&data).unwrap().role, None);
+
+        // Required structures after the terminator are missing
+        let data = [ad(AD_LE_ADDRESS, &[0; 7]), vec![0x00], ad(AD_LE_SC_CONFIRM, &[0; 16])].concat();
+        assert_eq!(OobData::decode(&data), Err(InvalidOobData(())));
+    }
+
+    #[test]
+    fn auto_pair_guards() {
+        let device = DeviceId::test(0x39);
+        let other = DeviceId::test(0x3a);
+        let first: Arc<dyn PairingAgent> = Arc::new(NoInputOutputPairingAgent);
+        let second: Arc<dyn PairingAgent> = Arc::new(NoInputOutputPairingAgent);
+        let is = |x: Option<Arc<dyn PairingAgent>>, agent: &Arc<dyn PairingAgent>| match x {
+            Some(x) => Arc::ptr_eq(&x, agent),
+            None => false,
+        };
+
+        assert!(auto_pair_agent(&device).is_none());
+        let first_guard = set_auto_pair_agent(device.clone(), first.clone());
+        let second_guard = set_auto_pair_agent(device.clone(), second.clone());
+        assert!(is(auto_pair_agent(&device), &second));
+        assert!(auto_pair_agent(&other).is_none());
+
+        // Dropping an older registration leaves the newer one in place
+        drop(first_guard);
+        assert!(is(auto_pair_agent(&device), &second));
+
+        drop(second_guard);
+        assert!(auto_pair_agent(&device).is_none());
+        assert!(AUTO_PAIR_AGENTS.lock().unwrap().iter().all(|x| x.device != device));
+    }
+}
@@ -0,0 +1,363 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures_channel::{mpsc, oneshot};
+use futures_util::future::{select, Either};
+use futures_util::{pin_mut, Stream};
+
+use super::{IoCapability, PairingAgent, PairingRejected, Passkey};
+use crate::Device;
+
+type ConfirmFn = Box<dyn Fn(&Device) -> bool + Send + Sync>;
+type ConfirmPasskeyFn = Box<dyn Fn(&Device, Passkey) -> bool + Send + Sync>;
+type RequestPasskeyFn = Box<dyn Fn(&Device) -> Option<Passkey> + Send + Sync>;
+type DisplayPasskeyFn = Box<dyn Fn(&Device, Passkey) + Send + Sync>;
+
+/// A request made to a [`CallbackPairingAgent`] created with [`CallbackPairingAgent::with_channel`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PairingRequest {
+    /// See [`PairingAgent::confirm`]
+    Confirm(Device, PairingResponder<()>),
+    /// See [`PairingAgent::confirm_passkey`]
+    ConfirmPasskey(Device, Passkey, PairingResponder<()>),
+    /// See [`PairingAgent::request_passkey`]
+    RequestPasskey(Device, PairingResponder<Passkey>),
+    /// See [`PairingAgent::display_passkey`]
+    DisplayPasskey(Device, Passkey),
+}
+
+/// Answers a [`PairingRequest`]
+///
+/// Dropping the responder without answering rejects the request.
+#[derive(Debug)]
+pub struct PairingResponder<T>(oneshot::Sender<Result<T, PairingRejected>>);
+
+impl<T> PairingResponder<T> {
+    /// Accepts the request with `value`.
+    pub fn accept(self, value: T) {
+        self.respond(Ok(value))
+    }
+
+    /// Rejects the request.
+    pub fn reject(self) {
+        self.respond(Err(PairingRejected))
+    }
+
+    /// Answers the request with `res`.
+    pub fn respond(self, res: Result<T, PairingRejected>) {
+        let _ = self.0.send(res);
+    }
+
+    /// Returns `true` if the request is no longer waiting for an answer because the pairing was canceled or the
+    /// request timed out.
+    pub fn is_canceled(&self) -> bool {
+        self.0.is_canceled()
+    }
+}
+
+/// A handle which cancels pairing with a [`CallbackPairingAgent`]
+#[derive(Debug, Clone, Default)]
+pub struct PairingCancelHandle(Arc<Mutex<Vec<oneshot::Sender<()>>>>);
+
+impl PairingCancelHandle {
+    /// Aborts pairing with the agent.
+    ///
+    /// The agent's pending request, if any, is rejected and the OS is told to cancel pairing. Only pairing which is in
+    /// progress is canceled, so the agent can be used to pair again afterwards.
+    pub fn cancel(&self) {
+        // Dropping the senders completes every waiter
+        self.0.lock().unwrap().clear();
+    }
+
+    /// Completes when [`cancel`][Self::cancel] is called
+    async fn canceled(&self) {
+        let rx = {
+            let mut waiters = self.0.lock().unwrap();
+            let (tx, rx) = oneshot::channel();
+            waiters.retain(|x| !x.is_canceled());
+            waiters.push(tx);
+            rx
+        };
+        let _ = rx.await;
+    }
+
+    /// Runs `fut`, rejecting the request if pairing is canceled before it completes
+    async fn run<T>(&self, fut: impl Future<Output = Result<T, PairingRejected>>) -> Result<T, PairingRejected> {
+        let canceled = self.canceled();
+        pin_mut!(fut, canceled);
+        match select(fut, canceled).await {
+            Either::Left((res, _)) => res,
+            Either::Right(_) => Err(PairingRejected),
+        }
+    }
+}
+
+/// A [`PairingAgent`] which answers requests using closures or a channel
+///
+/// Requests are answered by the closure registered for them if there is one. Otherwise, for an agent created with
+/// [`with_channel`][Self::with_channel], they are sent to the channel to be answered asynchronously. Requests which
+/// are not answered by either are rejected.
+///
+/// ```rust,no_run
+///# use bluest::Device;
+///# use bluest::pairing::{CallbackPairingAgent, IoCapability, PairingRequest};
+///# use futures_util::StreamExt;
+///# async fn example(device: Device) -> Result<(), Box<dyn std::error::Error>> {
+///let (agent, mut requests) = CallbackPairingAgent::with_channel(IoCapability::KeyboardDisplay);
+///let cancel = agent.cancel_handle();
+///
+///tokio::spawn(async move {
+///    while let Some(request) = requests.next().await {
+///        match request {
+///            PairingRequest::ConfirmPasskey(_device, passkey, responder) => {
+///                println!("Does the device show {}?", passkey);
+///                responder.accept(());
+///            }
+///            PairingRequest::RequestPasskey(_device, _responder) => {
+///                // Canceling aborts the pairing
+///                cancel.cancel();
+///            }
+///            _ => (),
+///        }
+///    }
+///});
+///
+///device.pair_with_agent(&agent).await?;
+///# Ok(())
+///# }
+/// ```
+pub struct CallbackPairingAgent {
+    io_capability: IoCapability,
+    confirm: Option<ConfirmFn>,
+    confirm_passkey: Option<ConfirmPasskeyFn>,
+    request_passkey: Option<RequestPasskeyFn>,
+    display_passkey: Option<DisplayPasskeyFn>,
+    requests: Option<mpsc::UnboundedSender<PairingRequest>>,
+    cancel: PairingCancelHandle,
+}
+
+impl std::fmt::Debug for CallbackPairingAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackPairingAgent")
+            .field("io_capability", &self.io_capability)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CallbackPairingAgent {
+    /// Creates an agent with the given IO capability which rejects every request until closures are registered
+    pub fn new(io_capability: IoCapability) -> Self {
+        CallbackPairingAgent {
+            io_capability,
+            confirm: None,
+            confirm_passkey: None,
+            request_passkey: None,
+            display_passkey: None,
+            requests: None,
+            cancel: PairingCancelHandle::default(),
+        }
+    }
+
+    /// Creates an agent with the given IO capability which sends its requests to the returned stream
+    ///
+    /// If the stream is dropped, requests are rejected.
+    pub fn with_channel(io_capability: IoCapability) -> (Self, impl Stream<Item = PairingRequest> + Send + Unpin) {
+        let (tx, rx) = mpsc::unbounded();
+        let agent = CallbackPairingAgent {
+            requests: Some(tx),
+            ..Self::new(io_capability)
+        };
+        (agent, rx)
+    }
+
+    /// Answers [`PairingAgent::confirm`] requests by calling `f`, which returns `true` to accept pairing.
+    pub fn on_confirm(mut self, f: impl Fn(&Device) -> bool + Send + Sync + 'static) -> Self {
+        self.confirm = Some(Box::new(f));
+        self
+    }
+
+    /// Answers [`PairingAgent::confirm_passkey`] requests by calling `f`, which returns `true` if the passkey matches.
+    pub fn on_confirm_passkey(mut self, f: impl Fn(&Device, Passkey) -> bool + Send + Sync + 'static) -> Self {
+        self.confirm_passkey = Some(Box::new(f));
+        self
+    }
+
+    /// Answers [`PairingAgent::request_passkey`] requests by calling `f`, which returns the passkey entered by the
+    /// user or `None` to reject pairing.
+    pub fn on_request_passkey(mut self, f: impl Fn(&Device) -> Option<Passkey> + Send + Sync + 'static) -> Self {
+        self.request_passkey = Some(Box::new(f));
+        self
+    }
+
+    /// Handles [`PairingAgent::display_passkey`] requests by calling `f`.
+    pub fn on_display_passkey(mut self, f: impl Fn(&Device, Passkey) + Send + Sync + 'static) -> Self {
+        self.display_passkey = Some(Box::new(f));
+        self
+    }
+
+    /// Returns a handle which can cancel pairing with this agent.
+    pub fn cancel_handle(&self) -> PairingCancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Sends a request to the channel and waits for the answer
+    async fn request<T>(
+        &self,
+        request: impl FnOnce(PairingResponder<T>) -> PairingRequest,
+    ) -> Result<T, PairingRejected> {
+        let sender = self.requests.as_ref().ok_or(PairingRejected)?;
+        self.cancel.run(send_request(sender, request)).await
+    }
+}
+
+/// Sends the request made by `request` to `sender` and waits for the answer
+async fn send_request<R, T>(
+    sender: &mpsc::UnboundedSender<R>,
+    request: impl FnOnce(PairingResponder<T>) -> R,
+) -> Result<T, PairingRejected> {
+    let (tx, rx) = oneshot::channel();
+    sender
+        .unbounded_send(request(PairingResponder(tx)))
+        .map_err(|_| PairingRejected)?;
+    rx.await.unwrap_or(Err(PairingRejected))
+}
+
+fn accepted(accept: bool) -> Result<(), PairingRejected> {
+    if accept {
+        Ok(())
+    } else {
+        Err(PairingRejected)
+    }
+}
+
+#[async_trait]
+impl PairingAgent for CallbackPairingAgent {
+    fn io_capability(&self) -> IoCapability {
+        self.io_capability
+    }
+
+    async fn confirm(&self, device: &Device) -> Result<(), PairingRejected> {
+        match &self.confirm {
+            Some(f) => self.cancel.run(async { accepted(f(device)) }).await,
+            None => {
+                self.request(|responder| PairingRequest::Confirm(device.clone(), responder))
+                    .await
+            }
+        }
+    }
+
+    async fn confirm_passkey(&self, device: &Device, passkey: Passkey) -> Result<(), PairingRejected> {
+        match &self.confirm_passkey {
+            Some(f) => self.cancel.run(async { accepted(f(device, passkey)) }).await,
+            None => {
+                self.request(|responder| PairingRequest::ConfirmPasskey(device.clone(), passkey, responder))
+                    .await
+            }
+        }
+    }
+
+    async fn request_passkey(&self, device: &Device) -> Result<Passkey, PairingRejected> {
+        match &self.request_passkey {
+            Some(f) => self.cancel.run(async { f(device).ok_or(PairingRejected) }).await,
+            None => {
+                self.request(|responder| PairingRequest::RequestPasskey(device.clone(), responder))
+                    .await
+            }
+        }
+    }
+
+    fn display_passkey(&self, device: &Device, passkey: Passkey) {
+        match (&self.display_passkey, &self.requests) {
+            (Some(f), _) => f(device, passkey),
+            (None, Some(sender)) => {
+                let _ = sender.unbounded_send(PairingRequest::DisplayPasskey(device.clone(), passkey));
+            }
+            (None, None) => (),
+        }
+    }
+
+    async fn canceled(&self) {
+        self.cancel.canceled().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future::join;
+    use futures_util::{FutureExt, StreamExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn channel_responses() {
+        let (tx, mut rx) = mpsc::unbounded::<PairingResponder<Passkey>>();
+
+        let answer = async { rx.next().await.unwrap().accept(Passkey::new(42)) };
+        let (res, ()) = join(send_request(&tx, |responder| responder), answer).await;
+        assert_eq!(res, Ok(Passkey::new(42)));
+
+        let answer = async { rx.next().await.unwrap().reject() };
+        let (res, ()) = join(send_request(&tx, |responder| responder), answer).await;
+        assert_eq!(res, Err(PairingRejected));
+
+        let answer = async { rx.next().await.unwrap().respond(Ok(Passkey::new(7))) };
+        let (res, ()) = join(send_request(&tx, |responder| responder), answer).await;
+        assert_eq!(res, Ok(Passkey::new(7)));
+
+        // Dropping the responder rejects the request
+        let answer = async { drop(rx.next().await.unwrap()) };
+        let (res, ()) = join(send_request(&tx, |responder| responder), answer).await;
+        assert_eq!(res, Err(PairingRejected));
+
+        // Requests are rejected once the stream is dropped
+        drop(rx);
+        assert_eq!(send_request(&tx, |responder| responder).await, Err(PairingRejected));
+    }
+
+    #[tokio::test]
+    async fn responder_dropped_after_cancel() {
+        let cancel = PairingCancelHandle::default();
+        let (tx, mut rx) = mpsc::unbounded::<PairingResponder<()>>();
+
+        let request = cancel.run(send_request(&tx, |responder| responder));
+        pin_mut!(request);
+        assert!(request.as_mut().now_or_never().is_none());
+
+        let responder = rx.next().await.unwrap();
+        assert!(!responder.is_canceled());
+        cancel.cancel();
+        assert_eq!(request.await, Err(PairingRejected));
+
+        // The request is no longer waiting, so answering or dropping the responder has no effect
+        assert!(responder.is_canceled());
+        responder.accept(());
+    }
+
+    #[tokio::test]
+    async fn cancel_is_not_sticky() {
+        let cancel = PairingCancelHandle::default();
+        cancel.cancel();
+
+        // Only pairing in progress when `cancel` is called is canceled
+        assert_eq!(cancel.run(async { Ok(()) }).await, Ok(()));
+        assert!(cancel.canceled().now_or_never().is_none());
+
+        let canceled = cancel.canceled();
+        pin_mut!(canceled);
+        assert!(canceled.as_mut().now_or_never().is_none());
+        cancel.clone().cancel();
+        assert_eq!(canceled.now_or_never(), Some(()));
+        assert!(cancel.0.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn canceled_waiters_are_pruned() {
+        let cancel = PairingCancelHandle::default();
+        for _ in 0..10 {
+            assert!(cancel.canceled().now_or_never().is_none());
+        }
+        assert_eq!(cancel.0.lock().unwrap().len(), 1);
+    }
+}
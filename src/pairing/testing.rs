@@ -0,0 +1,406 @@
+//! Tools for testing pairing agents.
+//!
+//! [`ScriptedPairingAgent`] is a [`PairingAgent`] which answers requests with fixed responses and records every
+//! callback it receives. [`AssociationModel::new`] selects the association model the Security Manager uses for a
+//! pair of IO capabilities, and [`simulate_pairing`] delivers the requests made for an association model to any
+//! agent through the same code path as real pairing requests. [`check_association_models`] combines these to check
+//! the callbacks made for every combination of IO capabilities.
+//!
+//! The callbacks of a [`PairingAgent`] receive the [`Device`] being paired, so simulating pairing requires a `Device`.
+//! The device is only passed to the agent; it is not connected to or paired with.
+//!
+//! This module is only available with the `testing` feature.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use super::{IoCapability, PairingAgent, PairingRejected, Passkey};
+use crate::{sys, Device, Uuid};
+
+pub(crate) const IO_CAPABILITIES: [IoCapability; 5] = [
+    IoCapability::DisplayOnly,
+    IoCapability::DisplayYesNo,
+    IoCapability::KeyboardOnly,
+    IoCapability::NoInputNoOutput,
+    IoCapability::KeyboardDisplay,
+];
+
+/// A call to a [`PairingAgent`] method
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AgentCall {
+    /// [`PairingAgent::confirm`]
+    Confirm,
+    /// [`PairingAgent::confirm_passkey`]
+    ConfirmPasskey(Passkey),
+    /// [`PairingAgent::request_passkey`]
+    RequestPasskey,
+    /// [`PairingAgent::display_passkey`]
+    DisplayPasskey(Passkey),
+    /// [`PairingAgent::authorize_service`]
+    AuthorizeService(Uuid),
+    /// [`PairingAgent::request_pin_code`]
+    RequestPinCode,
+    /// [`PairingAgent::display_pin_code`]
+    DisplayPinCode(String),
+}
+
+/// A pairing agent with scripted responses which records the callbacks it receives
+///
+/// By default the agent accepts every confirmation and rejects requests for a passkey or PIN code.
+#[derive(Debug)]
+pub struct ScriptedPairingAgent {
+    io_capability: IoCapability,
+    confirm: bool,
+    passkey: Option<Passkey>,
+    pin_code: Option<String>,
+    calls: Mutex<Vec<AgentCall>>,
+}
+
+impl ScriptedPairingAgent {
+    /// Creates an agent with the given IO capability
+    pub fn new(io_capability: IoCapability) -> Self {
+        ScriptedPairingAgent {
+            io_capability,
+            confirm: true,
+            passkey: None,
+            pin_code: None,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets whether the agent accepts confirmation and authorization requests.
+    pub fn with_confirm(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Sets the passkey the agent enters, or `None` to reject passkey requests.
+    pub fn with_passkey(mut self, passkey: Option<Passkey>) -> Self {
+        self.passkey = passkey;
+        self
+    }
+
+    /// Sets the PIN code the agent enters, or `None` to reject PIN code requests.
+    pub fn with_pin_code(mut self, pin_code: Option<String>) -> Self {
+        self.pin_code = pin_code;
+        self
+    }
+
+    /// The callbacks the agent has received, in order
+    pub fn calls(&self) -> Vec<AgentCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Returns the callbacks the agent has received, in order, and clears the record.
+    pub fn take_calls(&self) -> Vec<AgentCall> {
+        std::mem::take(&mut *self.calls.lock().unwrap())
+    }
+
+    fn record(&self, call: AgentCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+
+    fn confirmation(&self) -> Result<(), PairingRejected> {
+        if self.confirm {
+            Ok(())
+        } else {
+            Err(PairingRejected)
+        }
+    }
+}
+
+#[async_trait]
+impl PairingAgent for ScriptedPairingAgent {
+    fn io_capability(&self) -> IoCapability {
+        self.io_capability
+    }
+
+    async fn confirm(&self, _device: &Device) -> Result<(), PairingRejected> {
+        self.record(AgentCall::Confirm);
+        self.confirmation()
+    }
+
+    async fn confirm_passkey(&self, _device: &Device, passkey: Passkey) -> Result<(), PairingRejected> {
+        self.record(AgentCall::ConfirmPasskey(passkey));
+        self.confirmation()
+    }
+
+    async fn request_passkey(&self, _device: &Device) -> Result<Passkey, PairingRejected> {
+        self.record(AgentCall::RequestPasskey);
+        self.passkey.ok_or(PairingRejected)
+    }
+
+    fn display_passkey(&self, _device: &Device, passkey: Passkey) {
+        self.record(AgentCall::DisplayPasskey(passkey));
+    }
+
+    async fn authorize_service(&self, _device: &Device, service: Uuid) -> Result<(), PairingRejected> {
+        self.record(AgentCall::AuthorizeService(service));
+        self.confirmation()
+    }
+
+    async fn request_pin_code(&self, _device: &Device) -> Result<String, PairingRejected> {
+        self.record(AgentCall::RequestPinCode);
+        self.pin_code.clone().ok_or(PairingRejected)
+    }
+
+    fn display_pin_code(&self, _device: &Device, pin_code: &str) {
+        self.record(AgentCall::DisplayPinCode(pin_code.to_owned()));
+    }
+}
+
+/// The method used to authenticate LE pairing, from the point of view of the local device
+///
+/// See the Bluetooth Core Specification, Vol 3, Part H, §2.3.5.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum AssociationModel {
+    /// Pairing is not authenticated, but the user may be asked to confirm it
+    JustWorks,
+    /// The user confirms that both devices display the same passkey
+    NumericComparison,
+    /// The local device displays a passkey which the user enters on the remote device
+    PasskeyDisplay,
+    /// The user enters a passkey on the local device
+    PasskeyInput,
+}
+
+impl AssociationModel {
+    /// The association model used for pairing between a local device with the IO capability `local` and a remote
+    /// device with the IO capability `remote`.
+    ///
+    /// `secure_connections` is whether both devices support LE Secure Connections. `local_is_initiator` is whether
+    /// the local device started pairing, which only matters when both devices have the
+    /// [`KeyboardDisplay`][IoCapability::KeyboardDisplay] capability.
+    ///
+    /// ```
+    /// use bluest::pairing::testing::AssociationModel;
+    /// use bluest::pairing::IoCapability;
+    ///
+    /// let model = AssociationModel::new(IoCapability::KeyboardOnly, IoCapability::DisplayOnly, true, true);
+    /// assert_eq!(model, AssociationModel::PasskeyInput);
+    /// let model = AssociationModel::new(IoCapability::DisplayYesNo, IoCapability::KeyboardDisplay, true, true);
+    /// assert_eq!(model, AssociationModel::NumericComparison);
+    /// let model = AssociationModel::new(IoCapability::DisplayYesNo, IoCapability::KeyboardDisplay, false, true);
+    /// assert_eq!(model, AssociationModel::PasskeyDisplay);
+    /// ```
+    pub fn new(local: IoCapability, remote: IoCapability, secure_connections: bool, local_is_initiator: bool) -> Self {
+        use IoCapability::*;
+
+        let has_keyboard = |x| matches!(x, KeyboardOnly | KeyboardDisplay);
+        let has_yes_no = |x| matches!(x, DisplayYesNo | KeyboardDisplay);
+
+        if local == NoInputNoOutput || remote == NoInputNoOutput {
+            return AssociationModel::JustWorks;
+        }
+        if secure_connections && has_yes_no(local) && has_yes_no(remote) {
+            return AssociationModel::NumericComparison;
+        }
+        if !has_keyboard(local) && !has_keyboard(remote) {
+            return AssociationModel::JustWorks;
+        }
+
+        let local_inputs = match (local, remote) {
+            (KeyboardOnly, _) => true,
+            (_, KeyboardOnly) => false,
+            // The initiator displays the passkey and the responder inputs it
+            (KeyboardDisplay, KeyboardDisplay) => !local_is_initiator,
+            (KeyboardDisplay, _) => true,
+            _ => false,
+        };
+        if local_inputs {
+            AssociationModel::PasskeyInput
+        } else {
+            AssociationModel::PasskeyDisplay
+        }
+    }
+
+    /// The callbacks a pairing agent with the IO capability `io_capability` receives when pairing with this
+    /// association model, in order
+    ///
+    /// `passkey` is the passkey which is displayed or compared.
+    ///
+    /// # Platform specific
+    ///
+    /// The OS only makes the requests which belong to the agent's IO capability. On Linux, an agent which cannot
+    /// confirm pairing (`DisplayOnly`, `KeyboardOnly` or `NoInputNoOutput`) is not asked to confirm Just Works pairing.
+    /// On Windows, a `DisplayOnly` agent is not asked to confirm Just Works pairing. On MacOS/iOS, every request is
+    /// made.
+    pub fn expected_calls(&self, io_capability: IoCapability, passkey: Passkey) -> Vec<AgentCall> {
+        if !sys::device::DeviceImpl::handles_agent_request(io_capability, *self) {
+            return Vec::new();
+        }
+
+        match self {
+            AssociationModel::JustWorks => vec![AgentCall::Confirm],
+            AssociationModel::NumericComparison => vec![AgentCall::ConfirmPasskey(passkey)],
+            AssociationModel::PasskeyDisplay => vec![AgentCall::DisplayPasskey(passkey)],
+            AssociationModel::PasskeyInput => vec![AgentCall::RequestPasskey],
+        }
+    }
+}
+
+/// Delivers the requests made when pairing with `device` using the association model `model` to `agent`.
+///
+/// Requests are delivered through the same code path as the requests made by the OS during
+/// [`Device::pair_with_agent`], so requests which do not belong to the agent's IO capability are not made. `passkey`
+/// is the passkey which is displayed or compared, and which the agent must enter for
+/// [`PasskeyInput`][AssociationModel::PasskeyInput]. Returns an error if the agent rejects pairing or enters a
+/// different passkey.
+///
+/// # Platform specific
+///
+/// On MacOS/iOS, where pairing agents are not used by the OS, the agent's methods are called directly.
+pub async fn simulate_pairing<T: PairingAgent + ?Sized>(
+    agent: &T,
+    device: &Device,
+    model: AssociationModel,
+    passkey: Passkey,
+) -> Result<(), PairingRejected> {
+    match device.0.simulate_agent_request(agent, model, passkey).await? {
+        Some(entered) if entered != passkey => Err(PairingRejected),
+        _ => Ok(()),
+    }
+}
+
+/// A combination of IO capabilities for which a pairing agent did not receive the expected callbacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The IO capability of the local device
+    pub local: IoCapability,
+    /// The IO capability of the remote device
+    pub remote: IoCapability,
+    /// Whether both devices support LE Secure Connections
+    pub secure_connections: bool,
+    /// Whether the local device started pairing
+    pub local_is_initiator: bool,
+    /// The association model used
+    pub model: AssociationModel,
+    /// The callbacks which were expected
+    pub expected: Vec<AgentCall>,
+    /// The callbacks which were received
+    pub actual: Vec<AgentCall>,
+}
+
+/// Simulates pairing with a [`ScriptedPairingAgent`] for every combination of local and remote IO capability, LE
+/// Secure Connections support and pairing direction, returning the combinations where the agent did not receive the
+/// callbacks in [`AssociationModel::expected_calls`].
+pub async fn check_association_models(device: &Device) -> Vec<Mismatch> {
+    let passkey = Passkey::new(123456);
+    let mut mismatches = Vec::new();
+
+    for local in IO_CAPABILITIES {
+        let agent = ScriptedPairingAgent::new(local).with_passkey(Some(passkey));
+        for remote in IO_CAPABILITIES {
+            for secure_connections in [false, true] {
+                for local_is_initiator in [true, false] {
+                    let model = AssociationModel::new(local, remote, secure_connections, local_is_initiator);
+                    let res = simulate_pairing(&agent, device, model, passkey).await;
+                    let expected = model.expected_calls(local, passkey);
+                    let actual = agent.take_calls();
+                    if res.is_err() || actual != expected {
+                        mismatches.push(Mismatch {
+                            local,
+                            remote,
+                            secure_connections,
+                            local_is_initiator,
+                            model,
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn association_models() {
+        use AssociationModel::*;
+        use IoCapability::*;
+
+        let cases = [
+            (DisplayOnly, DisplayOnly, false, JustWorks),
+            (DisplayOnly, KeyboardOnly, false, PasskeyDisplay),
+            (KeyboardOnly, DisplayOnly, true, PasskeyInput),
+            (KeyboardOnly, KeyboardOnly, true, PasskeyInput),
+            (DisplayYesNo, DisplayYesNo, false, JustWorks),
+            (DisplayYesNo, DisplayYesNo, true, NumericComparison),
+            (DisplayYesNo, KeyboardDisplay, false, PasskeyDisplay),
+            (DisplayYesNo, KeyboardDisplay, true, NumericComparison),
+            (KeyboardOnly, DisplayYesNo, true, PasskeyInput),
+            (KeyboardDisplay, KeyboardDisplay, false, PasskeyDisplay),
+            (KeyboardDisplay, KeyboardDisplay, true, NumericComparison),
+            (NoInputNoOutput, KeyboardDisplay, true, JustWorks),
+            (KeyboardDisplay, NoInputNoOutput, false, JustWorks),
+        ];
+        for (local, remote, secure_connections, model) in cases {
+            assert_eq!(
+                AssociationModel::new(local, remote, secure_connections, true),
+                model,
+                "{local:?} with {remote:?}, {secure_connections}"
+            );
+        }
+    }
+
+    #[test]
+    fn association_models_are_symmetric() {
+        let mirror = |model| match model {
+            AssociationModel::PasskeyDisplay => AssociationModel::PasskeyInput,
+            AssociationModel::PasskeyInput => AssociationModel::PasskeyDisplay,
+            model => model,
+        };
+
+        for local in IO_CAPABILITIES {
+            for remote in IO_CAPABILITIES {
+                // Both devices input the passkey
+                if local == IoCapability::KeyboardOnly && remote == IoCapability::KeyboardOnly {
+                    continue;
+                }
+
+                for secure_connections in [false, true] {
+                    for local_is_initiator in [true, false] {
+                        let model = AssociationModel::new(local, remote, secure_connections, local_is_initiator);
+                        let remote_model =
+                            AssociationModel::new(remote, local, secure_connections, !local_is_initiator);
+                        assert_eq!(mirror(model), remote_model, "{local:?} with {remote:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expected_calls_follow_io_capability() {
+        let passkey = Passkey::new(123456);
+        for io_capability in IO_CAPABILITIES {
+            for model in [
+                AssociationModel::JustWorks,
+                AssociationModel::NumericComparison,
+                AssociationModel::PasskeyDisplay,
+                AssociationModel::PasskeyInput,
+            ] {
+                let calls = model.expected_calls(io_capability, passkey);
+                if sys::device::DeviceImpl::handles_agent_request(io_capability, model) {
+                    assert_eq!(calls.len(), 1, "{io_capability:?} {model:?}");
+                } else {
+                    assert!(calls.is_empty(), "{io_capability:?} {model:?}");
+                }
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        assert!(AssociationModel::JustWorks
+            .expected_calls(IoCapability::NoInputNoOutput, passkey)
+            .is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+//! Resolution of resolvable private addresses.
+//!
+//! Devices with LE privacy enabled advertise using a resolvable private address (RPA) which changes periodically, so
+//! they appear with a new [`DeviceId`][crate::DeviceId] each time the address changes. The identity resolving key
+//! (IRK) a device distributes during pairing can be used to recognize its RPAs. An [`IdentityResolver`] matches the
+//! addresses of devices against a set of IRKs.
+//!
+//! See the Bluetooth Core Specification, Vol 6, Part B, §1.3.2.2.
+//!
+//! ```rust,no_run
+//!# use bluest::Adapter;
+//!# use bluest::privacy::{IdentityResolver, IdentityResolvingKey};
+//!# use futures_util::StreamExt;
+//!# #[tokio::main]
+//!# async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!let mut resolver = IdentityResolver::new();
+//!resolver.insert(&IdentityResolvingKey([0; 16]), "my device");
+//!
+//!let adapter = Adapter::default().await.ok_or("Bluetooth adapter not found")?;
+//!adapter.wait_available().await?;
+//!let scan = adapter.scan(&[]).await?;
+//!let mut scan = Box::pin(resolver.resolve_advertisements(scan));
+//!while let Some(resolved) = scan.next().await {
+//!    if let Some(identity) = resolved.identity {
+//!        println!("{} is {}", resolved.advertising_device.device.id(), identity);
+//!    }
+//!}
+//!# Ok(())
+//!# }
+//! ```
+//!
+//! # Platform specific
+//!
+//! CoreBluetooth does not expose device addresses, so devices are never resolved on MacOS/iOS. Linux and MacOS/iOS
+//! resolve the RPAs of bonded devices themselves and report them using the device's identity.
+
+use futures_util::{Stream, StreamExt};
+
+use crate::aes::Aes128;
+use crate::{AdvertisingDevice, Device};
+
+/// The random address hash function `ah`
+///
+/// Returns the 24-bit hash of `r` using the key `k`. Values are in the most significant octet first order used by the
+/// Bluetooth Core Specification, Vol 3, Part H, §2.2.2.
+///
+/// ```
+/// use bluest::privacy::ah;
+///
+/// // Sample data from the Bluetooth Core Specification, Vol 3, Part H, §D.7
+/// let k = [
+///     0xec, 0x02, 0x34, 0xa3, 0x57, 0xc8, 0xad, 0x05, 0x34, 0x10, 0x10, 0xa6, 0x0a, 0x39, 0x7d, 0x9b,
+/// ];
+/// assert_eq!(ah(&k, [0x70, 0x81, 0x94]), [0x0d, 0xfb, 0xaa]);
+/// ```
+pub fn ah(k: &[u8; 16], r: [u8; 3]) -> [u8; 3] {
+    hash(&Aes128::new(k), r)
+}
+
+fn hash(cipher: &Aes128, r: [u8; 3]) -> [u8; 3] {
+    let mut block = [0; 16];
+    block[13..].copy_from_slice(&r);
+    let out = cipher.encrypt(&block);
+    [out[13], out[14], out[15]]
+}
+
+/// Returns `true` if the random device address `address` is a resolvable private address.
+///
+/// `address` is in most significant octet first order. Public addresses cannot be distinguished from random addresses
+/// by their value, so the caller must check that `address` is a random address.
+pub fn is_resolvable_private_address(address: [u8; 6]) -> bool {
+    address[0] & 0xc0 == 0x40
+}
+
+/// An identity resolving key (IRK), in most significant octet first order
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentityResolvingKey(pub [u8; 16]);
+
+impl std::fmt::Debug for IdentityResolvingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("IdentityResolvingKey(..)")
+    }
+}
+
+impl IdentityResolvingKey {
+    /// Creates a key from bytes in least significant octet first order, as used by the Security Manager Protocol and
+    /// HCI.
+    pub fn from_le_bytes(mut bytes: [u8; 16]) -> Self {
+        bytes.reverse();
+        IdentityResolvingKey(bytes)
+    }
+
+    /// Returns `true` if `address` is a resolvable private address generated from this key.
+    ///
+    /// ```
+    /// use bluest::privacy::IdentityResolvingKey;
+    ///
+    /// let irk = IdentityResolvingKey([0x5a; 16]);
+    /// let address = irk.resolvable_private_address([0x12, 0x34, 0x56]);
+    /// assert!(irk.resolves(address));
+    /// assert!(!IdentityResolvingKey([0xa5; 16]).resolves(address));
+    /// ```
+    pub fn resolves(&self, address: [u8; 6]) -> bool {
+        resolves(&Aes128::new(&self.0), address)
+    }
+
+    /// Generates a resolvable private address from this key and the random part `prand`, replacing the two most
+    /// significant bits of `prand` with the address type bits.
+    pub fn resolvable_private_address(&self, prand: [u8; 3]) -> [u8; 6] {
+        let prand = [(prand[0] & 0x3f) | 0x40, prand[1], prand[2]];
+        let hash = ah(&self.0, prand);
+        [prand[0], prand[1], prand[2], hash[0], hash[1], hash[2]]
+    }
+}
+
+fn resolves(cipher: &Aes128, address: [u8; 6]) -> bool {
+    let [p0, p1, p2, h0, h1, h2] = address;
+    is_resolvable_private_address(address) && hash(cipher, [p0, p1, p2]) == [h0, h1, h2]
+}
+
+/// A set of identity resolving keys, each tagged with the identity of the device it belongs to
+///
+/// The identity can be any value which identifies the device to the application, such as a [`DeviceId`] or a
+/// database key.
+///
+/// [`DeviceId`]: crate::DeviceId
+#[derive(Clone)]
+pub struct IdentityResolver<I> {
+    keys: Vec<(Aes128, I)>,
+}
+
+impl<I> Default for IdentityResolver<I> {
+    fn default() -> Self {
+        IdentityResolver { keys: Vec::new() }
+    }
+}
+
+impl<I: std::fmt::Debug> std::fmt::Debug for IdentityResolver<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.keys.iter().map(|(_, identity)| identity))
+            .finish()
+    }
+}
+
+/// An advertisement tagged with the identity of the device which sent it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAdvertisingDevice<I> {
+    /// The advertisement
+    pub advertising_device: AdvertisingDevice,
+    /// The identity of the device, if its address was resolved
+    pub identity: Option<I>,
+}
+
+impl<I> IdentityResolver<I> {
+    /// Creates an empty resolver
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `irk`, which belongs to the device identified by `identity`.
+    pub fn insert(&mut self, irk: &IdentityResolvingKey, identity: I) {
+        self.keys.push((Aes128::new(&irk.0), identity));
+    }
+
+    /// Removes the keys belonging to `identity`.
+    pub fn remove(&mut self, identity: &I)
+    where
+        I: PartialEq,
+    {
+        self.keys.retain(|(_, x)| x != identity);
+    }
+
+    /// The number of keys in the resolver
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the resolver contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the identity of the key which resolves `address`, if any.
+    ///
+    /// `address` is a random device address in most significant octet first order.
+    pub fn resolve(&self, address: [u8; 6]) -> Option<&I> {
+        self.keys
+            .iter()
+            .find(|(cipher, _)| resolves(cipher, address))
+            .map(|(_, identity)| identity)
+    }
+
+    /// Returns the identity of the key which resolves the address of `device`, if any.
+    ///
+    /// # Platform specific
+    ///
+    /// Always returns `None` on MacOS/iOS.
+    pub async fn resolve_device(&self, device: &Device) -> Option<&I> {
+        let address = device.0.random_address().await?;
+        self.resolve(address)
+    }
+
+    /// Tags each advertisement in `advertisements`, such as the stream returned by
+    /// [`Adapter::scan`][crate::Adapter::scan], with the identity of the device which sent it.
+    pub fn resolve_advertisements<'a, S>(
+        &'a self,
+        advertisements: S,
+    ) -> impl Stream<Item = ResolvedAdvertisingDevice<I>> + 'a
+    where
+        S: Stream<Item = AdvertisingDevice> + 'a,
+        I: Clone,
+    {
+        advertisements.then(move |advertising_device| async move {
+            let identity = self.resolve_device(&advertising_device.device).await.cloned();
+            ResolvedAdvertisingDevice {
+                advertising_device,
+                identity,
+            }
+        })
+    }
+}
@@ -0,0 +1,60 @@
+//! Support code for `#[derive(GattProfile)]`. Not public API.
+
+use crate::error::ErrorKind;
+use crate::{Characteristic, Device, Error, Result, Service, Uuid};
+
+/// Discovers the service with the given `uuid` on `device`.
+pub async fn service(device: &Device, uuid: Uuid) -> Result<Service> {
+    for service in device.discover_services_with_uuid(uuid).await? {
+        // Some platforms return every service from `discover_services_with_uuid`, so filter them here
+        if service.uuid_async().await? == uuid {
+            return Ok(service);
+        }
+    }
+
+    Err(Error::new(
+        ErrorKind::NotFound,
+        None,
+        format!("service {uuid} not found"),
+    ))
+}
+
+/// Discovers the characteristic with the given `uuid` in `service`, returning `None` if it does not exist.
+///
+/// If `notify` is true, the characteristic must support notifications or indications.
+pub async fn optional_characteristic(service: &Service, uuid: Uuid, notify: bool) -> Result<Option<Characteristic>> {
+    for characteristic in service.discover_characteristics_with_uuid(uuid).await? {
+        if characteristic.uuid_async().await? == uuid {
+            if notify {
+                let props = characteristic.properties().await?;
+                if !(props.notify || props.indicate) {
+                    return Err(Error::new(
+                        ErrorKind::NotSupported,
+                        None,
+                        format!("characteristic {uuid} does not support notifications or indications"),
+                    ));
+                }
+            }
+            return Ok(Some(characteristic));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Discovers the characteristic with the given `uuid` in `service`.
+///
+/// If `notify` is true, the characteristic must support notifications or indications.
+pub async fn characteristic(service: &Service, uuid: Uuid, notify: bool) -> Result<Characteristic> {
+    match optional_characteristic(service, uuid, notify).await? {
+        Some(characteristic) => Ok(characteristic),
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            None,
+            format!(
+                "characteristic {uuid} not found in service {}",
+                service.uuid_async().await?
+            ),
+        )),
+    }
+}
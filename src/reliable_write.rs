@@ -0,0 +1,38 @@
+use crate::{sys, Characteristic, Result};
+
+/// A reliable write transaction
+///
+/// Queues writes to one or more characteristics of a device and then executes them atomically. Values are sent using
+/// the prepared write procedure and verified against the values echoed by the device before the writes are executed.
+/// If any prepared write fails or cannot be verified, the whole transaction is cancelled and none of the values are
+/// written.
+///
+/// Nothing is sent to the device until [`commit`][ReliableWrite::commit] is called. Dropping the transaction (or
+/// calling [`abort`][ReliableWrite::abort]) before then discards the queued writes.
+///
+/// Created with [`Device::reliable_write`][crate::Device::reliable_write].
+#[derive(Debug)]
+pub struct ReliableWrite(pub(crate) sys::reliable_write::ReliableWriteImpl);
+
+impl ReliableWrite {
+    /// Queue a write of `value` to `characteristic` in this transaction.
+    ///
+    /// # Platform specific
+    ///
+    /// On Linux, BlueZ only supports reliable writes to a single characteristic. Queuing a second write in a
+    /// transaction will return a [`NotSupported`][crate::error::ErrorKind::NotSupported] error.
+    #[inline]
+    pub fn write(&mut self, characteristic: &Characteristic, value: &[u8]) -> Result<()> {
+        self.0.write(characteristic, value)
+    }
+
+    /// Execute all queued writes atomically.
+    #[inline]
+    pub async fn commit(self) -> Result<()> {
+        self.0.commit().await
+    }
+
+    /// Discard all queued writes without sending anything to the device.
+    #[inline]
+    pub fn abort(self) {}
+}
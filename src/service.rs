@@ -1,4 +1,4 @@
-use crate::{sys, Characteristic, Result, Uuid};
+use crate::{sys, CacheMode, Characteristic, HandleRange, Result, Uuid};
 
 /// A Bluetooth GATT service
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -23,6 +23,43 @@ impl Service {
         self.0.uuid_async().await
     }
 
+    /// The attribute handle of this GATT service's declaration
+    ///
+    /// Handles distinguish multiple instances of services with the same [`Uuid`]. They are only stable for as long as
+    /// the device's GATT database is unchanged.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    ///
+    /// On Linux, BlueZ does not expose attribute handles, so the handle is taken from the D-Bus object path, which
+    /// BlueZ names after the handle.
+    #[inline]
+    pub fn handle(&self) -> Result<u16> {
+        self.0.handle()
+    }
+
+    /// The range of attribute handles belonging to this GATT service
+    ///
+    /// None of the supported platforms report the end of a service's handle range, so it is taken to be the highest
+    /// handle of the service's characteristic values and descriptors. This performs characteristic and descriptor
+    /// discovery.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [`NotSupported`][crate::error::ErrorKind::NotSupported] on MacOS/iOS.
+    pub async fn handle_range(&self) -> Result<HandleRange> {
+        let start = self.handle()?;
+        let mut end = start;
+        for characteristic in self.discover_characteristics().await? {
+            end = end.max(characteristic.handle()?);
+            for descriptor in characteristic.discover_descriptors().await? {
+                end = end.max(descriptor.handle()?);
+            }
+        }
+        Ok(HandleRange { start, end })
+    }
+
     /// Whether this is a primary service of the device.
     ///
     /// # Platform specific
@@ -54,6 +91,18 @@ impl Service {
         self.0.characteristics().await
     }
 
+    /// Discover all characteristics associated with this service, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`characteristics`][Self::characteristics] for [`CacheMode::Cached`] and
+    /// [`discover_characteristics`][Self::discover_characteristics] for [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn discover_characteristics_with(&self, mode: CacheMode) -> Result<Vec<Characteristic>> {
+        match mode {
+            CacheMode::Cached => self.0.characteristics().await,
+            CacheMode::Uncached => self.0.discover_characteristics().await,
+        }
+    }
+
     /// Discover the included services of this service.
     #[inline]
     pub async fn discover_included_services(&self) -> Result<Vec<Service>> {
@@ -74,4 +123,16 @@ impl Service {
     pub async fn included_services(&self) -> Result<Vec<Service>> {
         self.0.included_services().await
     }
+
+    /// Discover the included services of this service, using the cache according to `mode`.
+    ///
+    /// Equivalent to [`included_services`][Self::included_services] for [`CacheMode::Cached`] and
+    /// [`discover_included_services`][Self::discover_included_services] for [`CacheMode::Uncached`].
+    #[inline]
+    pub async fn discover_included_services_with(&self, mode: CacheMode) -> Result<Vec<Service>> {
+        match mode {
+            CacheMode::Cached => self.0.included_services().await,
+            CacheMode::Uncached => self.0.discover_included_services().await,
+        }
+    }
 }
@@ -3,6 +3,7 @@ pub mod characteristic;
 pub mod descriptor;
 pub mod device;
 pub mod error;
+pub mod reliable_write;
 pub mod service;
 mod types;
 
@@ -16,3 +17,11 @@ impl std::fmt::Display for DeviceId {
         std::fmt::Display::fmt(&self.0.to_string_lossy(), f)
     }
 }
+
+#[cfg(test)]
+impl DeviceId {
+    /// A device identifier for use in tests, distinct for each value of `n`
+    pub(crate) fn test(n: u8) -> Self {
+        DeviceId(format!("BluetoothLE#{n}").into())
+    }
+}
@@ -20,6 +20,7 @@ use windows::Storage::Streams::DataReader;
 
 use super::types::StringVec;
 use crate::error::{Error, ErrorKind};
+use crate::pairing::{BondData, OobData, PairingAgent};
 use crate::util::defer;
 use crate::{
     AdapterEvent, AdvertisementData, AdvertisingDevice, BluetoothUuidExt, ConnectionEvent, Device, DeviceId,
@@ -142,6 +143,29 @@ impl AdapterImpl {
         Ok(res)
     }
 
+    /// Finds all devices bonded with this adapter
+    pub async fn bonded_devices(&self) -> Result<Vec<Device>> {
+        let aqsfilter = BluetoothLEDevice::GetDeviceSelectorFromPairingState(true)?;
+
+        let op = DeviceInformation::FindAllAsyncWithKindAqsFilterAndAdditionalProperties(
+            &aqsfilter,
+            None,
+            DeviceInformationKind::AssociationEndpoint,
+        )?;
+        let devices = op.await?;
+        let device_ids: Vec<HSTRING> = devices
+            .into_iter()
+            .map(|x| x.Id())
+            .collect::<windows::core::Result<_>>()?;
+
+        let mut res = Vec::with_capacity(device_ids.len());
+        for id in device_ids {
+            res.push(Device::from_id(&id).await?);
+        }
+
+        Ok(res)
+    }
+
     /// Finds all connected devices providing any service in `services`
     ///
     /// # Panics
@@ -421,8 +445,50 @@ impl AdapterImpl {
             ConnectionEvent::from(x)
         }))
     }
+
+    /// Registers `agent` as the system's default pairing agent.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn register_default_agent(&self, _agent: Arc<dyn PairingAgent>) -> Result<DefaultAgentImpl> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Creates new LE Secure Connections out-of-band pairing data for this adapter.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn local_oob_data(&self) -> Result<OobData> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Exports the stored bond with the device identified by `id`, including its keys.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn export_bond(&self, _id: &DeviceId) -> Result<BondData> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Stores a bond previously exported with [`export_bond`][Self::export_bond].
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn import_bond(&self, _bond: &BondData) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
 }
 
+/// A default pairing agent
+///
+/// Pairing requests are always handled by the OS, so this type cannot be constructed.
+#[derive(Debug)]
+pub enum DefaultAgentImpl {}
+
 impl From<BluetoothConnectionStatus> for ConnectionEvent {
     fn from(value: BluetoothConnectionStatus) -> Self {
         match value {
@@ -475,6 +541,12 @@ impl From<BluetoothLEAdvertisementReceivedEventArgs> for AdvertisementData {
         } else {
             (None, None, Vec::new(), HashMap::new())
         };
+        let encrypted_data = event_args
+            .Advertisement()
+            .and_then(|adv| adv.DataSections())
+            .and_then(|data_sections| to_encrypted_data(&data_sections))
+            .ok()
+            .flatten();
 
         AdvertisementData {
             local_name,
@@ -483,6 +555,7 @@ impl From<BluetoothLEAdvertisementReceivedEventArgs> for AdvertisementData {
             tx_power_level,
             is_connectable,
             service_data,
+            encrypted_data,
         }
     }
 }
@@ -533,3 +606,19 @@ fn to_service_data(
 
     Ok(service_data)
 }
+
+fn to_encrypted_data(
+    data_sections: &IVector<BluetoothLEAdvertisementDataSection>,
+) -> windows::core::Result<Option<Vec<u8>>> {
+    for data in data_sections {
+        if data.DataType()? == 0x31 {
+            let buf = data.Data()?;
+            let mut value = vec![0; buf.Length()? as usize];
+            let reader = DataReader::FromBuffer(&buf)?;
+            reader.ReadBytes(value.as_mut_slice())?;
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
+}
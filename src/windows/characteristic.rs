@@ -8,15 +8,18 @@ use windows::Devices::Bluetooth::GenericAttributeProfile::{
 use windows::Foundation::{AsyncOperationCompletedHandler, TypedEventHandler};
 use windows::Storage::Streams::{DataReader, DataWriter};
 
+use super::device::with_auto_pair;
 use super::error::check_communication_status;
+use crate::characteristic::MAX_ATTRIBUTE_VALUE_LEN;
+use crate::descriptors::Cccd;
 use crate::error::ErrorKind;
 use crate::util::defer;
-use crate::{Characteristic, CharacteristicProperties, Descriptor, Error, Result, Uuid};
+use crate::{Characteristic, CharacteristicProperties, Descriptor, Error, Result, Service, Uuid, WriteKind};
 
 /// A Bluetooth GATT characteristic
 #[derive(Clone)]
 pub struct CharacteristicImpl {
-    inner: GattCharacteristic,
+    pub(super) inner: GattCharacteristic,
 }
 
 impl PartialEq for CharacteristicImpl {
@@ -77,6 +80,18 @@ impl CharacteristicImpl {
         Ok(Uuid::from_u128(self.inner.Uuid()?.to_u128()))
     }
 
+    /// The attribute handle of this GATT characteristic's value
+    ///
+    /// `GattCharacteristic::AttributeHandle` is the handle of the characteristic value.
+    pub fn handle(&self) -> Result<u16> {
+        self.inner.AttributeHandle().map_err(Into::into)
+    }
+
+    /// The [`Service`] containing this characteristic
+    pub async fn service(&self) -> Result<Service> {
+        Ok(Service::new(self.inner.Service()?))
+    }
+
     /// The properties of this this GATT characteristic.
     ///
     /// Characteristic properties indicate which operations (e.g. read, write, notify, etc) may be performed on this
@@ -99,15 +114,33 @@ impl CharacteristicImpl {
     }
 
     async fn read_value(&self, cachemode: BluetoothCacheMode) -> Result<Vec<u8>> {
-        let res = self.inner.ReadValueWithCacheModeAsync(cachemode)?.await?;
+        with_auto_pair(&self.inner, || async move {
+            let res = self.inner.ReadValueWithCacheModeAsync(cachemode)?.await?;
+
+            check_communication_status(res.Status()?, res.ProtocolError(), "reading characteristic")?;
+
+            let buf = res.Value()?;
+            let mut data = vec![0; buf.Length()? as usize];
+            let reader = DataReader::FromBuffer(&buf)?;
+            reader.ReadBytes(data.as_mut_slice())?;
+            Ok(data)
+        })
+        .await
+    }
 
-        check_communication_status(res.Status()?, res.ProtocolError(), "reading characteristic")?;
+    /// Read the value of this characteristic from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "reading at a non-zero offset is not supported".to_string(),
+            ));
+        }
 
-        let buf = res.Value()?;
-        let mut data = vec![0; buf.Length()? as usize];
-        let reader = DataReader::FromBuffer(&buf)?;
-        reader.ReadBytes(data.as_mut_slice())?;
-        Ok(data)
+        self.read().await
     }
 
     /// Write the value of this descriptor on the device to `value`
@@ -115,21 +148,52 @@ impl CharacteristicImpl {
         self.write_kind(value, GattWriteOption::WriteWithResponse).await
     }
 
+    /// Write `value` to the value of this characteristic on the device starting at `offset`, using the long write
+    /// procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn write_at(&self, offset: u16, value: &[u8]) -> Result<()> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "writing at a non-zero offset is not supported".to_string(),
+            ));
+        }
+
+        self.write(value).await
+    }
+
     /// Write the value of this descriptor on the device to `value` without requesting a response.
     pub async fn write_without_response(&self, value: &[u8]) {
         let _res = self.write_kind(value, GattWriteOption::WriteWithoutResponse).await;
     }
 
     async fn write_kind(&self, value: &[u8], writeoption: GattWriteOption) -> Result<()> {
-        let op = {
-            let writer = DataWriter::new()?;
-            writer.WriteBytes(value)?;
-            let buf = writer.DetachBuffer()?;
-            self.inner.WriteValueWithResultAndOptionAsync(&buf, writeoption)?
-        };
-        let res = op.await?;
+        with_auto_pair(&self.inner, || async move {
+            let op = {
+                let writer = DataWriter::new()?;
+                writer.WriteBytes(value)?;
+                let buf = writer.DetachBuffer()?;
+                self.inner.WriteValueWithResultAndOptionAsync(&buf, writeoption)?
+            };
+            let res = op.await?;
+
+            check_communication_status(res.Status()?, res.ProtocolError(), "writing characteristic")
+        })
+        .await
+    }
 
-        check_communication_status(res.Status()?, res.ProtocolError(), "writing characteristic")
+    /// The maximum number of bytes which may be written to this characteristic in a single call.
+    pub async fn max_write_len(&self, kind: WriteKind) -> Result<usize> {
+        match kind {
+            // Windows automatically uses the long write procedure for writes which do not fit in a single packet
+            WriteKind::WithResponse => Ok(MAX_ATTRIBUTE_VALUE_LEN),
+            WriteKind::WithoutResponse => {
+                let mtu = self.inner.Service()?.Session()?.MaxPduSize()?;
+                Ok(usize::from(mtu) - 3)
+            }
+        }
     }
 
     /// Enables notification of value changes for this GATT characteristic.
@@ -179,12 +243,15 @@ impl CharacteristicImpl {
             }
         });
 
-        let res = self
-            .inner
-            .WriteClientCharacteristicConfigurationDescriptorWithResultAsync(value)?
-            .await?;
+        with_auto_pair(&self.inner, || async move {
+            let res = self
+                .inner
+                .WriteClientCharacteristicConfigurationDescriptorWithResultAsync(value)?
+                .await?;
 
-        check_communication_status(res.Status()?, res.ProtocolError(), "enabling notifications")?;
+            check_communication_status(res.Status()?, res.ProtocolError(), "enabling notifications")
+        })
+        .await?;
 
         let guard = defer(move || {
             let _guard = guard;
@@ -225,6 +292,12 @@ impl CharacteristicImpl {
 
     /// Is the device currently sending notifications for this characteristic?
     pub async fn is_notifying(&self) -> Result<bool> {
+        let cccd = self.cccd().await?;
+        Ok(cccd.notify || cccd.indicate)
+    }
+
+    /// Read the client characteristic configuration descriptor of this characteristic from the device.
+    pub async fn cccd(&self) -> Result<Cccd> {
         let res = self
             .inner
             .ReadClientCharacteristicConfigurationDescriptorAsync()?
@@ -239,7 +312,33 @@ impl CharacteristicImpl {
         const INDICATE: i32 = GattClientCharacteristicConfigurationDescriptorValue::Indicate.0;
         const NOTIFY: i32 = GattClientCharacteristicConfigurationDescriptorValue::Notify.0;
         let cccd = res.ClientCharacteristicConfigurationDescriptor()?;
-        Ok((cccd.0 & (INDICATE | NOTIFY)) != 0)
+        Ok(Cccd {
+            notify: (cccd.0 & NOTIFY) != 0,
+            indicate: (cccd.0 & INDICATE) != 0,
+        })
+    }
+
+    /// Write the client characteristic configuration descriptor of this characteristic on the device.
+    pub async fn set_cccd(&self, cccd: Cccd) -> Result<()> {
+        const INDICATE: i32 = GattClientCharacteristicConfigurationDescriptorValue::Indicate.0;
+        const NOTIFY: i32 = GattClientCharacteristicConfigurationDescriptorValue::Notify.0;
+        let value = GattClientCharacteristicConfigurationDescriptorValue(
+            (if cccd.notify { NOTIFY } else { 0 }) | (if cccd.indicate { INDICATE } else { 0 }),
+        );
+
+        with_auto_pair(&self.inner, || async move {
+            let res = self
+                .inner
+                .WriteClientCharacteristicConfigurationDescriptorWithResultAsync(value)?
+                .await?;
+
+            check_communication_status(
+                res.Status()?,
+                res.ProtocolError(),
+                "writing client characteristic configuration descriptor",
+            )
+        })
+        .await
     }
 
     /// Discover the descriptors associated with this characteristic.
@@ -259,6 +358,9 @@ impl CharacteristicImpl {
         let res = self.inner.GetDescriptorsWithCacheModeAsync(cachemode)?.await?;
         check_communication_status(res.Status()?, res.ProtocolError(), "discovering descriptors")?;
         let descriptors = res.Descriptors()?;
-        Ok(descriptors.into_iter().map(Descriptor::new).collect())
+        Ok(descriptors
+            .into_iter()
+            .map(|x| Descriptor::new(x, self.inner.clone()))
+            .collect())
     }
 }
@@ -1,14 +1,18 @@
 use windows::Devices::Bluetooth::BluetoothCacheMode;
-use windows::Devices::Bluetooth::GenericAttributeProfile::GattDescriptor;
+use windows::Devices::Bluetooth::GenericAttributeProfile::{GattCharacteristic, GattDescriptor};
 use windows::Storage::Streams::{DataReader, DataWriter};
 
+use super::device::with_auto_pair;
 use super::error::check_communication_status;
-use crate::{Descriptor, Result, Uuid};
+use crate::btuuid::descriptors::CLIENT_CHARACTERISTIC_CONFIGURATION;
+use crate::error::ErrorKind;
+use crate::{Characteristic, Descriptor, Error, Result, Uuid};
 
 /// A Bluetooth GATT descriptor
 #[derive(Clone, PartialEq, Eq)]
 pub struct DescriptorImpl {
     inner: GattDescriptor,
+    characteristic: GattCharacteristic,
 }
 
 impl std::fmt::Debug for DescriptorImpl {
@@ -21,8 +25,11 @@ impl std::fmt::Debug for DescriptorImpl {
 }
 
 impl Descriptor {
-    pub(super) fn new(descriptor: GattDescriptor) -> Self {
-        Descriptor(DescriptorImpl { inner: descriptor })
+    pub(super) fn new(descriptor: GattDescriptor, characteristic: GattCharacteristic) -> Self {
+        Descriptor(DescriptorImpl {
+            inner: descriptor,
+            characteristic,
+        })
     }
 }
 
@@ -37,6 +44,16 @@ impl DescriptorImpl {
         Ok(Uuid::from_u128(self.inner.Uuid()?.to_u128()))
     }
 
+    /// The attribute handle of this GATT descriptor
+    pub fn handle(&self) -> Result<u16> {
+        self.inner.AttributeHandle().map_err(Into::into)
+    }
+
+    /// The [`Characteristic`] containing this descriptor
+    pub async fn characteristic(&self) -> Result<Characteristic> {
+        Ok(Characteristic::new(self.characteristic.clone()))
+    }
+
     /// The cached value of this descriptor
     ///
     /// If the value has not yet been read, this method may either return an error or perform a read of the value.
@@ -50,27 +67,57 @@ impl DescriptorImpl {
     }
 
     async fn read_value(&self, cachemode: BluetoothCacheMode) -> Result<Vec<u8>> {
-        let res = self.inner.ReadValueWithCacheModeAsync(cachemode)?.await?;
+        with_auto_pair(&self.characteristic, || async move {
+            let res = self.inner.ReadValueWithCacheModeAsync(cachemode)?.await?;
+
+            check_communication_status(res.Status()?, res.ProtocolError(), "reading descriptor value")?;
 
-        check_communication_status(res.Status()?, res.ProtocolError(), "reading descriptor value")?;
+            let buf = res.Value()?;
+            let mut data = vec![0; buf.Length()? as usize];
+            let reader = DataReader::FromBuffer(&buf)?;
+            reader.ReadBytes(data.as_mut_slice())?;
+            Ok(data)
+        })
+        .await
+    }
+
+    /// Read the value of this descriptor from the device, starting at `offset`, using the read blob procedure.
+    ///
+    /// Returns [ErrorKind::NotSupported] if `offset` is not zero.
+    pub async fn read_at(&self, offset: u16) -> Result<Vec<u8>> {
+        if offset != 0 {
+            return Err(Error::new(
+                ErrorKind::NotSupported,
+                None,
+                "reading at a non-zero offset is not supported".to_string(),
+            ));
+        }
 
-        let buf = res.Value()?;
-        let mut data = vec![0; buf.Length()? as usize];
-        let reader = DataReader::FromBuffer(&buf)?;
-        reader.ReadBytes(data.as_mut_slice())?;
-        Ok(data)
+        self.read().await
     }
 
     /// Write the value of this descriptor on the device to `value`
     pub async fn write(&self, value: &[u8]) -> Result<()> {
-        let op = {
-            let writer = DataWriter::new()?;
-            writer.WriteBytes(value)?;
-            let buf = writer.DetachBuffer()?;
-            self.inner.WriteValueWithResultAsync(&buf)?
-        };
-        let res = op.await?;
-
-        check_communication_status(res.Status()?, res.ProtocolError(), "writing descriptor value")
+        // Reject CCCD writes like BlueZ does, the CCCD is configured through `notify` or `set_cccd`
+        if self.uuid_async().await? == CLIENT_CHARACTERISTIC_CONFIGURATION {
+            return Err(Error::new(
+                ErrorKind::NotAuthorized,
+                None,
+                "the client characteristic configuration descriptor cannot be written directly".to_string(),
+            ));
+        }
+
+        with_auto_pair(&self.characteristic, || async move {
+            let op = {
+                let writer = DataWriter::new()?;
+                writer.WriteBytes(value)?;
+                let buf = writer.DetachBuffer()?;
+                self.inner.WriteValueWithResultAsync(&buf)?
+            };
+            let res = op.await?;
+
+            check_communication_status(res.Status()?, res.ProtocolError(), "writing descriptor value")
+        })
+        .await
     }
 }
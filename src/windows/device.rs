@@ -1,19 +1,37 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
 use futures_channel::mpsc;
 use futures_util::future::{select, Either};
-use futures_util::{pin_mut, StreamExt};
+use futures_util::{pin_mut, Stream, StreamExt};
 use tracing::error;
 use windows::core::{GUID, HSTRING};
+use windows::Devices::Bluetooth::GenericAttributeProfile::{GattCharacteristic, GattSession};
 use windows::Devices::Bluetooth::{
-    BluetoothAddressType, BluetoothCacheMode, BluetoothConnectionStatus, BluetoothLEDevice,
+    BluetoothAddressType, BluetoothCacheMode, BluetoothConnectionStatus, BluetoothLEConnectionPhyInfo,
+    BluetoothLEDevice, BluetoothLEPreferredConnectionParameters, BluetoothLEPreferredConnectionParametersRequest,
+    BluetoothLEPreferredConnectionParametersRequestStatus,
+};
+use windows::Devices::Enumeration::{
+    DevicePairingKinds, DevicePairingProtectionLevel, DevicePairingRequestedEventArgs,
 };
-use windows::Devices::Enumeration::{DevicePairingKinds, DevicePairingRequestedEventArgs};
 use windows::Foundation::TypedEventHandler;
 
 use super::error::{check_communication_status, check_pairing_status, check_unpairing_status};
-use crate::error::ErrorKind;
-use crate::pairing::{IoCapability, PairingAgent, Passkey};
+use super::reliable_write::ReliableWriteImpl;
+use crate::error::{AttError, ErrorKind, PairingError};
+use crate::pairing::{
+    auto_pair_agent, pairing_events, report_pairing, send_pairing_event, BondInfo, IoCapability, OobData, PairingAgent,
+    PairingEvent, Passkey, SecurityLevel,
+};
+#[cfg(any(test, feature = "testing"))]
+use crate::pairing::{testing::AssociationModel, PairingRejected};
 use crate::util::defer;
-use crate::{Device, DeviceId, Error, Result, Service, Uuid};
+use crate::{
+    ConnectionParameters, ConnectionPhy, ConnectionPriority, Device, DeviceId, Error, HandleRange, Phy, ReliableWrite,
+    Result, Service, ServicesChangedEvent, Uuid,
+};
 
 /// A Bluetooth LE device
 #[derive(Clone)]
@@ -75,6 +93,15 @@ impl DeviceImpl {
         )
     }
 
+    /// The address of this device if it is a random address, in most significant octet first order
+    pub(crate) async fn random_address(&self) -> Option<[u8; 6]> {
+        if self.inner.BluetoothAddressType().ok()? != BluetoothAddressType::Random {
+            return None;
+        }
+        let [_, _, address @ ..] = self.inner.BluetoothAddress().ok()?.to_be_bytes();
+        Some(address)
+    }
+
     /// The local name for this device, if available
     ///
     /// This can either be a name advertised or read from the device, or a name assigned to the device by the OS.
@@ -108,27 +135,38 @@ impl DeviceImpl {
     ///
     /// This will fail unless it is called from a UWP application.
     pub async fn pair(&self) -> Result<()> {
-        let op = self.inner.DeviceInformation()?.Pairing()?.PairAsync()?;
-        let res = op.await?;
-        check_pairing_status(res.Status()?)
+        report_pairing(&self.id(), async {
+            let op = self.inner.DeviceInformation()?.Pairing()?.PairAsync()?;
+            let res = op.await?;
+            check_pairing_status(res.Status()?)
+        })
+        .await
     }
 
     /// Attempt to pair this device using the system default pairing UI
-    pub async fn pair_with_agent<T: PairingAgent>(&self, agent: &T) -> Result<()> {
-        let pairing_kinds_supported = match agent.io_capability() {
-            IoCapability::DisplayOnly => DevicePairingKinds::DisplayPin,
-            IoCapability::DisplayYesNo => {
-                DevicePairingKinds::ConfirmOnly | DevicePairingKinds::DisplayPin | DevicePairingKinds::ConfirmPinMatch
-            }
-            IoCapability::KeyboardOnly => DevicePairingKinds::ConfirmOnly | DevicePairingKinds::ProvidePin,
-            IoCapability::NoInputNoOutput => DevicePairingKinds::ConfirmOnly,
-            IoCapability::KeyboardDisplay => {
-                DevicePairingKinds::ConfirmOnly
-                    | DevicePairingKinds::DisplayPin
-                    | DevicePairingKinds::ProvidePin
-                    | DevicePairingKinds::ConfirmPinMatch
-            }
-        };
+    pub async fn pair_with_agent<T: PairingAgent + ?Sized>(&self, agent: &T) -> Result<()> {
+        report_pairing(
+            &self.id(),
+            self.pair_with_agent_and_protection_level(agent, DevicePairingProtectionLevel::Default),
+        )
+        .await
+    }
+
+    /// Attempt to pair this device using LE Secure Connections out-of-band pairing data received from the device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn pair_with_oob_data(&self, _oob: &OobData) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    async fn pair_with_agent_and_protection_level<T: PairingAgent + ?Sized>(
+        &self,
+        agent: &T,
+        protection_level: DevicePairingProtectionLevel,
+    ) -> Result<()> {
+        let pairing_kinds_supported = pairing_kinds(agent.io_capability());
 
         let (mut tx, mut rx) = mpsc::channel(1);
         let custom = self.inner.DeviceInformation()?.Pairing()?.Custom()?;
@@ -142,35 +180,17 @@ impl DeviceImpl {
             },
         ))?;
 
-        let op = custom.PairAsync(pairing_kinds_supported)?;
+        let op = custom.PairWithProtectionLevelAsync(pairing_kinds_supported, protection_level)?;
+        let pairing_op = op.clone();
 
         let device = Device(self.clone());
         let pairing_fut = async move {
             while let Some((event_args, deferral)) = rx.next().await {
-                match event_args.PairingKind()? {
-                    DevicePairingKinds::ConfirmOnly => {
-                        if agent.confirm(&device).await.is_ok() {
-                            event_args.Accept()?;
-                        }
-                    }
-                    DevicePairingKinds::DisplayPin => {
-                        if let Ok(passkey) = event_args.Pin()?.to_string_lossy().parse::<Passkey>() {
-                            agent.display_passkey(&device, passkey);
-                        }
-                    }
-                    DevicePairingKinds::ProvidePin => {
-                        if let Ok(passkey) = agent.request_passkey(&device).await {
-                            event_args.AcceptWithPin(&passkey.to_string().into())?;
-                        }
-                    }
-                    DevicePairingKinds::ConfirmPinMatch => {
-                        if let Ok(passkey) = event_args.Pin()?.to_string_lossy().parse::<Passkey>() {
-                            if let Ok(()) = agent.confirm_passkey(&device, passkey).await {
-                                event_args.Accept()?;
-                            }
-                        }
-                    }
-                    _ => (),
+                let pin = event_args.Pin().ok().and_then(|x| x.to_string_lossy().parse().ok());
+                match pairing_response(agent, &device, event_args.PairingKind()?, pin).await {
+                    PairingResponse::Accept => event_args.Accept()?,
+                    PairingResponse::AcceptWithPin(passkey) => event_args.AcceptWithPin(&passkey.to_string().into())?,
+                    PairingResponse::Ignore => (),
                 }
 
                 deferral.Complete()?;
@@ -178,16 +198,65 @@ impl DeviceImpl {
 
             Result::<_, Error>::Ok(())
         };
-        pin_mut!(pairing_fut);
+        let pairing = async move {
+            pin_mut!(pairing_fut);
+            match select(op, pairing_fut).await {
+                Either::Left((res, _)) => check_pairing_status(res?.Status()?),
+                Either::Right((Ok(()), _)) => Err(Error::new(
+                    ErrorKind::Other,
+                    None,
+                    "Pairing agent terminated unexpectedly".to_owned(),
+                )),
+                Either::Right((Err(err), _)) => Err(err),
+            }
+        };
+        let canceled = agent.canceled();
+        pin_mut!(pairing, canceled);
 
-        match select(op, pairing_fut).await {
-            Either::Left((res, _)) => check_pairing_status(res?.Status()?),
-            Either::Right((Ok(()), _)) => Err(Error::new(
-                ErrorKind::Other,
-                None,
-                "Pairing agent terminated unexpectedly".to_owned(),
-            )),
-            Either::Right((Err(err), _)) => Err(err),
+        match select(pairing, canceled).await {
+            Either::Left((res, _)) => res,
+            Either::Right(((), _)) => {
+                pairing_op.Cancel()?;
+                Err(Error::new(
+                    ErrorKind::PairingFailed(PairingError::Canceled),
+                    None,
+                    "canceled by the pairing agent".to_owned(),
+                ))
+            }
+        }
+    }
+
+    /// Returns `true` if Windows makes a request to an agent with the IO capability `io_capability` when pairing with
+    /// the association model `model`.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn handles_agent_request(io_capability: IoCapability, model: AssociationModel) -> bool {
+        (pairing_kinds(io_capability).0 & pairing_kind(model).0) != 0
+    }
+
+    /// Delivers the pairing request made for `model` to `agent`, returning the passkey the agent entered, if any
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) async fn simulate_agent_request<T: PairingAgent + ?Sized>(
+        &self,
+        agent: &T,
+        model: AssociationModel,
+        passkey: Passkey,
+    ) -> std::result::Result<Option<Passkey>, PairingRejected> {
+        // Windows only makes requests for the pairing kinds the agent supports
+        if !Self::handles_agent_request(agent.io_capability(), model) {
+            return Ok(None);
+        }
+
+        let kind = pairing_kind(model);
+        let pin = match model {
+            AssociationModel::NumericComparison | AssociationModel::PasskeyDisplay => Some(passkey),
+            AssociationModel::JustWorks | AssociationModel::PasskeyInput => None,
+        };
+        match pairing_response(agent, &Device(self.clone()), kind, pin).await {
+            PairingResponse::Accept => Ok(None),
+            PairingResponse::AcceptWithPin(passkey) => Ok(Some(passkey)),
+            // Windows does not expect a response to a request to display a PIN
+            PairingResponse::Ignore if kind == DevicePairingKinds::DisplayPin => Ok(None),
+            PairingResponse::Ignore => Err(PairingRejected),
         }
     }
 
@@ -195,7 +264,134 @@ impl DeviceImpl {
     pub async fn unpair(&self) -> Result<()> {
         let op = self.inner.DeviceInformation()?.Pairing()?.UnpairAsync()?;
         let res = op.await?;
-        check_unpairing_status(res.Status()?)
+        check_unpairing_status(res.Status()?)?;
+        send_pairing_event(&self.id(), PairingEvent::Unpaired);
+        Ok(())
+    }
+
+    /// Deletes the bond with this device without disconnecting it.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn remove_bond(&self) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Information about the stored bond with this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn bond_info(&self) -> Result<BondInfo> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Monitors the pairing state of this device.
+    pub async fn pairing_events(&self) -> Result<impl Stream<Item = PairingEvent> + '_> {
+        Ok(pairing_events(self.id()))
+    }
+
+    /// Whether this device is trusted
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn is_trusted(&self) -> Result<bool> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Sets whether this device is trusted.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_trusted(&self, _trusted: bool) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Whether this device is blocked
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn is_blocked(&self) -> Result<bool> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Sets whether this device is blocked.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_blocked(&self, _blocked: bool) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// The security level of the connection to this device
+    pub async fn security_level(&self) -> Result<SecurityLevel> {
+        if !self.is_connected().await {
+            return Err(ErrorKind::NotConnected.into());
+        }
+
+        let pairing = self.inner.DeviceInformation()?.Pairing()?;
+        if !pairing.IsPaired()? {
+            return Ok(SecurityLevel::None);
+        }
+
+        match pairing.ProtectionLevel()? {
+            DevicePairingProtectionLevel::EncryptionAndAuthentication => Ok(SecurityLevel::Authenticated),
+            _ => Ok(SecurityLevel::Encrypted),
+        }
+    }
+
+    /// Pairs with this device if necessary to raise the security of the connection to at least `level`.
+    pub async fn require_security(&self, level: SecurityLevel) -> Result<()> {
+        let protection_level = match level {
+            SecurityLevel::None => return Ok(()),
+            SecurityLevel::Encrypted => DevicePairingProtectionLevel::Encryption,
+            SecurityLevel::Authenticated => DevicePairingProtectionLevel::EncryptionAndAuthentication,
+            SecurityLevel::SecureConnections => return Err(ErrorKind::NotSupported.into()),
+        };
+
+        let current = self.security_level().await?;
+        if current >= level {
+            return Ok(());
+        } else if current > SecurityLevel::None {
+            return Err(Error::new(
+                ErrorKind::NotAuthorized,
+                None,
+                "the device is paired with a lower security level".to_string(),
+            ));
+        }
+
+        report_pairing(&self.id(), async {
+            match auto_pair_agent(&self.id()) {
+                Some(agent) => {
+                    self.pair_with_agent_and_protection_level(&*agent, protection_level)
+                        .await
+                }
+                None => {
+                    let op = self
+                        .inner
+                        .DeviceInformation()?
+                        .Pairing()?
+                        .PairWithProtectionLevelAsync(protection_level)?;
+                    check_pairing_status(op.await?.Status()?)
+                }
+            }
+        })
+        .await?;
+
+        if self.security_level().await? >= level {
+            Ok(())
+        } else {
+            Err(Error::new(
+                ErrorKind::NotAuthorized,
+                None,
+                "pairing did not reach the required security level".to_string(),
+            ))
+        }
     }
 
     /// Discover the primary services of this device.
@@ -256,10 +452,282 @@ impl DeviceImpl {
         Ok(())
     }
 
+    /// Monitors this device for GATT services changed packets.
+    pub async fn service_changes(&self) -> Result<impl Stream<Item = Result<ServicesChangedEvent>> + '_> {
+        let (mut sender, receiver) = futures_channel::mpsc::channel(16);
+        let token = self.inner.GattServicesChanged(&TypedEventHandler::new(move |_, _| {
+            if let Err(err) = sender.try_send(()) {
+                error!("Unable to send services changed event: {:?}", err);
+            }
+            Ok(())
+        }))?;
+
+        let guard = defer(move || {
+            if let Err(err) = self.inner.RemoveGattServicesChanged(token) {
+                error!("Error removing services changed handler: {:?}", err);
+            }
+        });
+
+        Ok(receiver.map(move |()| {
+            let _guard = &guard;
+            Ok(ServicesChangedEvent {
+                range: None,
+                invalidated_services: Vec::new(),
+            })
+        }))
+    }
+
+    /// Asynchronously blocks until a GATT services changed packet is received and returns the affected handle range
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn services_changed_range(&self) -> Result<HandleRange> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Begin a [`ReliableWrite`] transaction on this device.
+    pub fn reliable_write(&self) -> Result<ReliableWrite> {
+        Ok(ReliableWrite(ReliableWriteImpl::new()?))
+    }
+
+    /// The ATT MTU negotiated for the connection to this device.
+    pub async fn mtu(&self) -> Result<u16> {
+        let session = GattSession::FromDeviceIdAsync(&self.inner.BluetoothDeviceId()?)?.await?;
+        session.MaxPduSize().map_err(Into::into)
+    }
+
     /// Get the current signal strength from the device in dBm.
     ///
     /// Returns [ErrorKind::NotSupported].
     pub async fn rssi(&self) -> Result<i16> {
         Err(ErrorKind::NotSupported.into())
     }
+
+    /// The LE connection parameters of the connection to this device.
+    pub async fn connection_parameters(&self) -> Result<ConnectionParameters> {
+        connection_parameters(&self.inner)
+    }
+
+    /// Monitors the LE connection parameters of the connection to this device.
+    pub async fn connection_parameter_changes(&self) -> Result<impl Stream<Item = Result<ConnectionParameters>> + '_> {
+        let (mut sender, receiver) = futures_channel::mpsc::channel(16);
+        let token = self
+            .inner
+            .ConnectionParametersChanged(&TypedEventHandler::new(move |_, _| {
+                if let Err(err) = sender.try_send(()) {
+                    error!("Unable to send connection parameters changed event: {:?}", err);
+                }
+                Ok(())
+            }))?;
+
+        let guard = defer(move || {
+            if let Err(err) = self.inner.RemoveConnectionParametersChanged(token) {
+                error!("Error removing connection parameters changed handler: {:?}", err);
+            }
+        });
+
+        Ok(receiver.map(move |()| {
+            let _guard = &guard;
+            connection_parameters(&self.inner)
+        }))
+    }
+
+    /// The PHYs used by the connection to this device.
+    pub async fn connection_phy(&self) -> Result<ConnectionPhy> {
+        connection_phy(&self.inner)
+    }
+
+    /// Monitors the PHYs used by the connection to this device.
+    pub async fn connection_phy_changes(&self) -> Result<impl Stream<Item = Result<ConnectionPhy>> + '_> {
+        let (mut sender, receiver) = futures_channel::mpsc::channel(16);
+        let token = self.inner.ConnectionPhyChanged(&TypedEventHandler::new(move |_, _| {
+            if let Err(err) = sender.try_send(()) {
+                error!("Unable to send connection PHY changed event: {:?}", err);
+            }
+            Ok(())
+        }))?;
+
+        let guard = defer(move || {
+            if let Err(err) = self.inner.RemoveConnectionPhyChanged(token) {
+                error!("Error removing connection PHY changed handler: {:?}", err);
+            }
+        });
+
+        Ok(receiver.map(move |()| {
+            let _guard = &guard;
+            connection_phy(&self.inner)
+        }))
+    }
+
+    /// Asks the controller to use `phy` for the connection to this device.
+    ///
+    /// # Platform specific
+    ///
+    /// Returns [ErrorKind::NotSupported].
+    pub async fn set_preferred_phy(&self, _phy: ConnectionPhy) -> Result<()> {
+        Err(ErrorKind::NotSupported.into())
+    }
+
+    /// Asks the OS to use connection parameters suited to `priority` for the connection to this device.
+    pub async fn request_connection_priority(&self, priority: ConnectionPriority) -> Result<()> {
+        let id = self.id();
+        let mut requests = CONNECTION_PRIORITY_REQUESTS.lock().unwrap();
+        if let Some(index) = requests.iter().position(|(x, _)| *x == id) {
+            let (_, request) = requests.swap_remove(index);
+            request.Close()?;
+        }
+
+        let params = match priority {
+            // Closing the previous request restores the default parameters
+            ConnectionPriority::Balanced => return Ok(()),
+            ConnectionPriority::HighThroughput => BluetoothLEPreferredConnectionParameters::ThroughputOptimized()?,
+            ConnectionPriority::LowPower => BluetoothLEPreferredConnectionParameters::PowerOptimized()?,
+        };
+
+        let request = self.inner.RequestPreferredConnectionParameters(&params)?;
+        match request.Status()? {
+            BluetoothLEPreferredConnectionParametersRequestStatus::Success => {
+                requests.push((id, request));
+                Ok(())
+            }
+            BluetoothLEPreferredConnectionParametersRequestStatus::DeviceNotAvailable => {
+                Err(ErrorKind::NotConnected.into())
+            }
+            BluetoothLEPreferredConnectionParametersRequestStatus::AccessDenied => Err(ErrorKind::NotAuthorized.into()),
+            status => Err(Error::new(
+                ErrorKind::Other,
+                None,
+                format!("requesting connection parameters failed: {status:?}"),
+            )),
+        }
+    }
+}
+
+fn connection_parameters(device: &BluetoothLEDevice) -> Result<ConnectionParameters> {
+    let params = device.GetConnectionParameters()?;
+    Ok(ConnectionParameters {
+        // The interval is in units of 1.25ms and the timeout in units of 10ms
+        interval: Duration::from_micros(u64::from(params.ConnectionInterval()?) * 1250),
+        peripheral_latency: params.ConnectionLatency()?,
+        supervision_timeout: Duration::from_millis(u64::from(params.LinkTimeout()?) * 10),
+    })
+}
+
+fn connection_phy(device: &BluetoothLEDevice) -> Result<ConnectionPhy> {
+    fn phy(info: BluetoothLEConnectionPhyInfo) -> Result<Phy> {
+        Ok(if info.IsCodedPhy()? {
+            Phy::LeCoded
+        } else if info.IsUncoded2MPhy()? {
+            Phy::Le2M
+        } else {
+            Phy::Le1M
+        })
+    }
+
+    let connection_phy = device.GetConnectionPhy()?;
+    Ok(ConnectionPhy {
+        tx: phy(connection_phy.TransmitInfo()?)?,
+        rx: phy(connection_phy.ReceiveInfo()?)?,
+    })
+}
+
+/// Connection parameter requests made with [`Device::request_connection_priority`]. The OS applies a request until
+/// it is closed.
+static CONNECTION_PRIORITY_REQUESTS: Mutex<Vec<(DeviceId, BluetoothLEPreferredConnectionParametersRequest)>> =
+    Mutex::new(Vec::new());
+
+/// Runs `op`, and if it fails because the connection is not secure enough and an agent has been registered with
+/// [`Device::set_auto_pair`], pairs with the device containing `characteristic` and runs `op` again.
+///
+/// The device is only looked up when `op` fails for one of these reasons.
+pub(super) async fn with_auto_pair<T, F, Fut>(characteristic: &GattCharacteristic, op: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    match op().await {
+        Err(err)
+            if matches!(
+                err.kind(),
+                ErrorKind::Protocol(AttError::INSUFFICIENT_AUTHENTICATION | AttError::INSUFFICIENT_ENCRYPTION)
+            ) =>
+        {
+            let device = DeviceImpl {
+                inner: characteristic.Service()?.Device()?,
+            };
+            match auto_pair_agent(&device.id()) {
+                Some(agent) => {
+                    device.pair_with_agent(&*agent).await?;
+                    op().await
+                }
+                None => Err(err),
+            }
+        }
+        res => res,
+    }
+}
+
+/// The kinds of pairing request an agent with the IO capability `io_capability` supports
+fn pairing_kinds(io_capability: IoCapability) -> DevicePairingKinds {
+    match io_capability {
+        IoCapability::DisplayOnly => DevicePairingKinds::DisplayPin,
+        IoCapability::DisplayYesNo => {
+            DevicePairingKinds::ConfirmOnly | DevicePairingKinds::DisplayPin | DevicePairingKinds::ConfirmPinMatch
+        }
+        IoCapability::KeyboardOnly => DevicePairingKinds::ConfirmOnly | DevicePairingKinds::ProvidePin,
+        IoCapability::NoInputNoOutput => DevicePairingKinds::ConfirmOnly,
+        IoCapability::KeyboardDisplay => {
+            DevicePairingKinds::ConfirmOnly
+                | DevicePairingKinds::DisplayPin
+                | DevicePairingKinds::ProvidePin
+                | DevicePairingKinds::ConfirmPinMatch
+        }
+    }
+}
+
+/// The kind of pairing request Windows makes for the association model `model`
+#[cfg(any(test, feature = "testing"))]
+fn pairing_kind(model: AssociationModel) -> DevicePairingKinds {
+    match model {
+        AssociationModel::JustWorks => DevicePairingKinds::ConfirmOnly,
+        AssociationModel::NumericComparison => DevicePairingKinds::ConfirmPinMatch,
+        AssociationModel::PasskeyDisplay => DevicePairingKinds::DisplayPin,
+        AssociationModel::PasskeyInput => DevicePairingKinds::ProvidePin,
+    }
+}
+
+/// The response to a Windows pairing request
+enum PairingResponse {
+    Accept,
+    AcceptWithPin(Passkey),
+    Ignore,
+}
+
+/// Asks `agent` how to respond to a pairing request of kind `kind`. `pin` is the PIN included in the request, if any.
+async fn pairing_response<T: PairingAgent + ?Sized>(
+    agent: &T,
+    device: &Device,
+    kind: DevicePairingKinds,
+    pin: Option<Passkey>,
+) -> PairingResponse {
+    match (kind, pin) {
+        (DevicePairingKinds::ConfirmOnly, _) => match agent.confirm(device).await {
+            Ok(()) => PairingResponse::Accept,
+            Err(_) => PairingResponse::Ignore,
+        },
+        (DevicePairingKinds::DisplayPin, Some(passkey)) => {
+            agent.display_passkey(device, passkey);
+            PairingResponse::Ignore
+        }
+        (DevicePairingKinds::ProvidePin, _) => match agent.request_passkey(device).await {
+            Ok(passkey) => PairingResponse::AcceptWithPin(passkey),
+            Err(_) => PairingResponse::Ignore,
+        },
+        (DevicePairingKinds::ConfirmPinMatch, Some(passkey)) => match agent.confirm_passkey(device, passkey).await {
+            Ok(()) => PairingResponse::Accept,
+            Err(_) => PairingResponse::Ignore,
+        },
+        _ => PairingResponse::Ignore,
+    }
 }
@@ -2,7 +2,7 @@ use windows::Devices::Bluetooth::GenericAttributeProfile::GattCommunicationStatu
 use windows::Devices::Enumeration::{DevicePairingResultStatus, DeviceUnpairingResultStatus};
 use windows::Foundation::IReference;
 
-use crate::error::ErrorKind;
+use crate::error::{ErrorKind, PairingError};
 use crate::Result;
 
 impl From<windows::core::Error> for crate::Error {
@@ -65,15 +65,15 @@ pub(super) fn check_communication_status(
     }
 }
 
-struct PairingError(DevicePairingResultStatus);
+struct PairingResultError(DevicePairingResultStatus);
 
-impl std::fmt::Debug for PairingError {
+impl std::fmt::Debug for PairingResultError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "PairingError({})", self)
+        write!(f, "PairingResultError({})", self)
     }
 }
 
-impl std::fmt::Display for PairingError {
+impl std::fmt::Display for PairingResultError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self.0 {
             DevicePairingResultStatus::Paired => "paired",
@@ -103,39 +103,42 @@ impl std::fmt::Display for PairingError {
 }
 
 fn kind_from_pairing_status(status: DevicePairingResultStatus) -> ErrorKind {
-    match status {
-        DevicePairingResultStatus::NotReadyToPair => ErrorKind::NotReady,
-        DevicePairingResultStatus::AuthenticationTimeout => ErrorKind::Timeout,
-        DevicePairingResultStatus::AuthenticationNotAllowed | DevicePairingResultStatus::AccessDenied => {
-            ErrorKind::NotAuthorized
+    let reason = match status {
+        DevicePairingResultStatus::NotReadyToPair => return ErrorKind::NotReady,
+        DevicePairingResultStatus::AccessDenied => return ErrorKind::NotAuthorized,
+        DevicePairingResultStatus::TooManyConnections => return ErrorKind::ConnectionFailed,
+        DevicePairingResultStatus::AuthenticationFailure | DevicePairingResultStatus::InvalidCeremonyData => {
+            PairingError::AuthenticationFailed
+        }
+        DevicePairingResultStatus::AuthenticationNotAllowed | DevicePairingResultStatus::ConnectionRejected => {
+            PairingError::RejectedByDevice
         }
-        DevicePairingResultStatus::ConnectionRejected | DevicePairingResultStatus::TooManyConnections => {
-            ErrorKind::ConnectionFailed
+        DevicePairingResultStatus::PairingCanceled | DevicePairingResultStatus::RejectedByHandler => {
+            PairingError::Canceled
         }
+        DevicePairingResultStatus::AuthenticationTimeout => PairingError::Timeout,
+        DevicePairingResultStatus::OperationAlreadyInProgress => PairingError::InProgress,
+        // The association model required by the devices' capabilities is not handled by the pairing agent
+        DevicePairingResultStatus::ProtectionLevelCouldNotBeMet
+        | DevicePairingResultStatus::RequiredHandlerNotRegistered => PairingError::InsufficientSecurity,
         DevicePairingResultStatus::NotPaired
         | DevicePairingResultStatus::HardwareFailure
-        | DevicePairingResultStatus::AuthenticationFailure
         | DevicePairingResultStatus::NoSupportedProfiles
-        | DevicePairingResultStatus::ProtectionLevelCouldNotBeMet
-        | DevicePairingResultStatus::InvalidCeremonyData
-        | DevicePairingResultStatus::PairingCanceled
-        | DevicePairingResultStatus::OperationAlreadyInProgress
-        | DevicePairingResultStatus::RequiredHandlerNotRegistered
-        | DevicePairingResultStatus::RejectedByHandler
         | DevicePairingResultStatus::RemoteDeviceHasAssociation
-        | DevicePairingResultStatus::Failed => ErrorKind::Other,
-        _ => ErrorKind::Other,
-    }
+        | DevicePairingResultStatus::Failed => PairingError::Other,
+        _ => PairingError::Other,
+    };
+    ErrorKind::PairingFailed(reason)
 }
 
-impl std::error::Error for PairingError {}
+impl std::error::Error for PairingResultError {}
 
 pub(super) fn check_pairing_status(status: DevicePairingResultStatus) -> Result<()> {
     match status {
         DevicePairingResultStatus::Paired | DevicePairingResultStatus::AlreadyPaired => Ok(()),
         _ => Err(crate::Error::new(
             kind_from_pairing_status(status),
-            Some(Box::new(PairingError(status))),
+            Some(Box::new(PairingResultError(status))),
             String::new(),
         )),
     }
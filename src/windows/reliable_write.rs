@@ -0,0 +1,34 @@
+use windows::Devices::Bluetooth::GenericAttributeProfile::GattReliableWriteTransaction;
+use windows::Storage::Streams::DataWriter;
+
+use super::error::check_communication_status;
+use crate::{Characteristic, Result};
+
+/// A reliable write transaction
+#[derive(Debug)]
+pub struct ReliableWriteImpl {
+    inner: GattReliableWriteTransaction,
+}
+
+impl ReliableWriteImpl {
+    pub(super) fn new() -> Result<Self> {
+        Ok(ReliableWriteImpl {
+            inner: GattReliableWriteTransaction::new()?,
+        })
+    }
+
+    /// Queue a write of `value` to `characteristic` in this transaction.
+    pub fn write(&mut self, characteristic: &Characteristic, value: &[u8]) -> Result<()> {
+        let writer = DataWriter::new()?;
+        writer.WriteBytes(value)?;
+        let buf = writer.DetachBuffer()?;
+        self.inner.WriteValue(&characteristic.0.inner, &buf)?;
+        Ok(())
+    }
+
+    /// Execute all queued writes atomically.
+    pub async fn commit(self) -> Result<()> {
+        let res = self.inner.CommitWithResultAsync()?.await?;
+        check_communication_status(res.Status()?, res.ProtocolError(), "committing reliable write")
+    }
+}
@@ -80,6 +80,11 @@ impl ServiceImpl {
         Ok(Uuid::from_u128(self.inner.Uuid()?.to_u128()))
     }
 
+    /// The attribute handle of this GATT service
+    pub fn handle(&self) -> Result<u16> {
+        self.inner.AttributeHandle().map_err(Into::into)
+    }
+
     /// Whether this is a primary service of the device.
     ///
     /// Returns [ErrorKind::NotSupported].
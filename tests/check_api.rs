@@ -19,6 +19,12 @@ async fn check_adapter_apis(adapter: Adapter) -> Result<Device> {
     let scan: Result<_> = assert_send(adapter.scan(&[btuuid::services::GENERIC_ACCESS])).await;
     let _adv: Option<AdvertisingDevice> = assert_send(scan?.next()).await;
 
+    let mut resolver = privacy::IdentityResolver::new();
+    resolver.insert(&privacy::IdentityResolvingKey([0; 16]), 0u32);
+    let scan: Result<_> = assert_send(adapter.scan(&[])).await;
+    let mut resolved = Box::pin(assert_send(resolver.resolve_advertisements(scan?)));
+    let _adv: Option<privacy::ResolvedAdvertisingDevice<u32>> = assert_send(resolved.next()).await;
+
     let discovery: Result<_> = assert_send(adapter.discover_devices(&[btuuid::services::GENERIC_ACCESS])).await;
     let _device: Option<Result<Device>> = assert_send(discovery?.next()).await;
 
@@ -28,6 +34,13 @@ async fn check_adapter_apis(adapter: Adapter) -> Result<Device> {
     let _res: Result<()> = assert_send(adapter.connect_device(&device)).await;
     let _res: Result<()> = assert_send(adapter.disconnect_device(&device)).await;
 
+    let agent: std::sync::Arc<dyn pairing::PairingAgent> = std::sync::Arc::new(pairing::NoInputOutputPairingAgent);
+    let _guard: Result<pairing::DefaultAgentGuard> = assert_send(adapter.register_default_agent(agent)).await;
+    let _oob: Result<pairing::OobData> = assert_send(adapter.local_oob_data()).await;
+    let _devices: Result<Vec<Device>> = assert_send(adapter.bonded_devices()).await;
+    let bond: Result<pairing::BondData> = assert_send(adapter.export_bond(&device.id())).await;
+    let _res: Result<()> = assert_send(adapter.import_bond(&bond?)).await;
+
     Ok(device)
 }
 
@@ -37,18 +50,64 @@ async fn check_device_apis(device: Device) -> Result<Service> {
     let _name: Result<String> = assert_send(device.name_async()).await;
     let _is_connected: bool = assert_send(device.is_connected()).await;
     let _is_paired: Result<bool> = assert_send(device.is_paired()).await;
+    let _bond: Result<pairing::BondInfo> = assert_send(device.bond_info()).await;
+    let _res: Result<()> = assert_send(device.remove_bond()).await;
+    let _trusted: Result<bool> = assert_send(device.is_trusted()).await;
+    let _res: Result<()> = assert_send(device.set_trusted(true)).await;
+    let _blocked: Result<bool> = assert_send(device.is_blocked()).await;
+    let _res: Result<()> = assert_send(device.set_blocked(false)).await;
 
     let _pair: Result<()> = assert_send(device.pair()).await;
     let _pair: Result<()> = assert_send(device.pair_with_agent(&pairing::NoInputOutputPairingAgent)).await;
+    let agent: std::sync::Arc<dyn pairing::PairingAgent> = std::sync::Arc::new(pairing::NoInputOutputPairingAgent);
+    let _pair: Result<()> = assert_send(device.pair_with_agent(&*agent)).await;
+    let oob = pairing::OobData::decode(&[]).unwrap();
+    let _pair: Result<()> = assert_send(device.pair_with_oob_data(&oob)).await;
+    let _guard: pairing::AutoPairGuard = device.set_auto_pair(agent);
+    let _level: Result<pairing::SecurityLevel> = assert_send(device.security_level()).await;
+    let _res: Result<()> = assert_send(device.require_security(pairing::SecurityLevel::Encrypted)).await;
+    let mut events = assert_send(device.pairing_events()).await?;
+    let _event: Option<pairing::PairingEvent> = assert_send(events.next()).await;
+    let (callback_agent, mut requests) =
+        pairing::CallbackPairingAgent::with_channel(pairing::IoCapability::KeyboardDisplay);
+    let callback_agent = callback_agent.on_display_passkey(|_device, _passkey| ());
+    let cancel: pairing::PairingCancelHandle = callback_agent.cancel_handle();
+    cancel.cancel();
+    let _pair: Result<()> = assert_send(device.pair_with_agent(&callback_agent)).await;
+    if let Some(pairing::PairingRequest::RequestPasskey(_device, responder)) = assert_send(requests.next()).await {
+        responder.accept(pairing::Passkey::random());
+    }
 
     let _discovery: Result<Vec<Service>> = assert_send(device.discover_services()).await;
+    let _discovery: Result<Vec<Service>> = assert_send(device.discover_services_with(CacheMode::Uncached)).await;
     let _discovery: Result<Vec<Service>> =
         assert_send(device.discover_services_with_uuid(btuuid::services::GENERIC_ACCESS)).await;
     let services: Result<Vec<Service>> = assert_send(device.services()).await;
 
     let _services_changed: Result<()> = assert_send(device.services_changed()).await;
+    let _range: Result<HandleRange> = assert_send(device.services_changed_range()).await;
+    let _characteristic: Result<Characteristic> = assert_send(device.characteristic_by_handle(0x0010)).await;
+    let mut changes = assert_send(device.service_changes()).await?;
+    let _event: Option<Result<ServicesChangedEvent>> = assert_send(changes.next()).await;
+    let _hash: Result<[u8; 16]> = assert_send(device.database_hash()).await;
+    let database: Result<gatt_database::GattDatabase> = assert_send(device.gatt_database()).await;
+    let _diff: gatt_database::GattDatabaseDiff = database?.diff(&gatt_database::GattDatabase::default());
 
+    let _values: Result<Vec<Vec<u8>>> = assert_send(device.read_multiple(&[])).await;
+    let _mtu: Result<u16> = assert_send(device.mtu()).await;
     let _rssi: Result<i16> = assert_send(device.rssi()).await;
+    let _params: Result<ConnectionParameters> = assert_send(device.connection_parameters()).await;
+    let mut changes = assert_send(device.connection_parameter_changes()).await?;
+    let _params: Option<Result<ConnectionParameters>> = assert_send(changes.next()).await;
+    let _phy: Result<ConnectionPhy> = assert_send(device.connection_phy()).await;
+    let mut changes = assert_send(device.connection_phy_changes()).await?;
+    let _phy: Option<Result<ConnectionPhy>> = assert_send(changes.next()).await;
+    let phy = ConnectionPhy {
+        tx: Phy::Le2M,
+        rx: Phy::Le2M,
+    };
+    let _res: Result<()> = assert_send(device.set_preferred_phy(phy)).await;
+    let _res: Result<()> = assert_send(device.request_connection_priority(ConnectionPriority::HighThroughput)).await;
 
     Ok(services?.into_iter().next().unwrap())
 }
@@ -57,13 +116,19 @@ async fn check_service_apis(service: Service) -> Result<Characteristic> {
     let _uuid: Uuid = service.uuid();
     let _uuid: Result<Uuid> = assert_send(service.uuid_async()).await;
     let _is_primary: Result<bool> = assert_send(service.is_primary()).await;
+    let _handle: Result<u16> = service.handle();
+    let _range: Result<HandleRange> = assert_send(service.handle_range()).await;
 
     let _discovery: Result<Vec<Characteristic>> = assert_send(service.discover_characteristics()).await;
+    let _discovery: Result<Vec<Characteristic>> =
+        assert_send(service.discover_characteristics_with(CacheMode::Uncached)).await;
     let _discovery: Result<Vec<Characteristic>> =
         assert_send(service.discover_characteristics_with_uuid(btuuid::characteristics::DEVICE_NAME)).await;
     let characteristics: Result<Vec<Characteristic>> = assert_send(service.characteristics()).await;
 
     let _discovery: Result<Vec<Service>> = assert_send(service.discover_included_services()).await;
+    let _discovery: Result<Vec<Service>> =
+        assert_send(service.discover_included_services_with(CacheMode::Cached)).await;
     let _discovery: Result<Vec<Service>> =
         assert_send(service.discover_included_services_with_uuid(btuuid::services::GENERIC_ACCESS)).await;
     let _services: Result<Vec<Service>> = assert_send(service.included_services()).await;
@@ -75,41 +140,161 @@ async fn check_characteristic_apis(characteristic: Characteristic) -> Result<Des
     let _uuid: Uuid = characteristic.uuid();
     let _uuid: Result<Uuid> = assert_send(characteristic.uuid_async()).await;
     let _props: Result<CharacteristicProperties> = assert_send(characteristic.properties()).await;
+    let _handle: Result<u16> = characteristic.handle();
+    let _service: Result<Service> = assert_send(characteristic.service()).await;
 
     let _value: Result<Vec<u8>> = assert_send(characteristic.value()).await;
     let _value: Result<Vec<u8>> = assert_send(characteristic.read()).await;
+    let _value: Result<Vec<u8>> = assert_send(characteristic.read_at(0)).await;
+    let _value: Result<Vec<u8>> = assert_send(characteristic.read_with(CacheMode::Uncached)).await;
     let _res: Result<()> = assert_send(characteristic.write(&[0u8])).await;
     let _res: () = assert_send(characteristic.write_without_response(&[0u8])).await;
+    let _res: Result<()> = assert_send(characteristic.write_at(0, &[0u8])).await;
+    let _len: Result<usize> = assert_send(characteristic.max_write_len(WriteKind::WithResponse)).await;
+    let _len: Result<usize> = assert_send(characteristic.max_write_len(WriteKind::WithoutResponse)).await;
 
     let notifications: Result<_> = assert_send(characteristic.notify()).await;
     let _notification: Option<Result<Vec<u8>>> = assert_send(notifications?.next()).await;
     let _is_notifying: Result<bool> = assert_send(characteristic.is_notifying()).await;
+    let _cccd: Result<descriptors::Cccd> = assert_send(characteristic.cccd()).await;
+    let _res: Result<()> = assert_send(characteristic.set_cccd(descriptors::Cccd {
+        notify: true,
+        indicate: false,
+    }))
+    .await;
 
     let _discovery: Result<Vec<Descriptor>> = assert_send(characteristic.discover_descriptors()).await;
+    let _discovery: Result<Vec<Descriptor>> =
+        assert_send(characteristic.discover_descriptors_with(CacheMode::Cached)).await;
     let descriptors: Result<Vec<Descriptor>> = assert_send(characteristic.descriptors()).await;
 
     Ok(descriptors?.into_iter().next().unwrap())
 }
 
+async fn check_reliable_write_apis(device: Device, characteristic: &Characteristic) -> Result<()> {
+    let mut reliable_write: ReliableWrite = device.reliable_write()?;
+    let _res: Result<()> = reliable_write.write(characteristic, &[0u8]);
+    let _res: Result<()> = assert_send(reliable_write.commit()).await;
+
+    let reliable_write: ReliableWrite = device.reliable_write()?;
+    reliable_write.abort();
+
+    Ok(())
+}
+
+async fn check_codec_apis(characteristic: &Characteristic) -> Result<()> {
+    let _value: Result<u16> = assert_send(characteristic.read_typed()).await;
+    let _res: Result<()> = assert_send(characteristic.write_typed(&codec::Sint24::MAX)).await;
+
+    let typed = codec::TypedCharacteristic::<codec::SFloat>::new(characteristic.clone());
+    let _value: Result<codec::SFloat> = assert_send(typed.read_typed()).await;
+    let _res: Result<()> = assert_send(typed.write_typed(&codec::SFloat::new(364, -1).unwrap())).await;
+    let mut updates = assert_send(typed.notify_typed()).await?;
+    let _value: Option<Result<codec::SFloat>> = assert_send(updates.next()).await;
+
+    Ok(())
+}
+
+async fn check_descriptors_apis(characteristic: &Characteristic) -> Result<()> {
+    let format: Result<Option<descriptors::PresentationFormat>> =
+        assert_send(characteristic.presentation_format()).await;
+    let format = format?.unwrap();
+    let _value: Result<f64, codec::InvalidValue> = format.to_f64(&[0u8]);
+    let _symbol: Option<&str> = format.unit.symbol();
+    let _size: Option<usize> = format.format.size();
+    let _sccd: Result<Option<descriptors::Sccd>> = assert_send(characteristic.sccd()).await;
+    let _res: Result<()> = assert_send(characteristic.set_sccd(descriptors::Sccd { broadcast: true })).await;
+    let _description: Result<Option<String>> = assert_send(characteristic.user_description()).await;
+    let range: Result<Option<descriptors::ValidRange>> = assert_send(characteristic.valid_range()).await;
+    let _range: Result<descriptors::ValidRange<u16>, codec::InvalidValue> = range?.unwrap().decode_as();
+
+    Ok(())
+}
+
 async fn check_descriptor_apis(descriptor: Descriptor) -> Result<()> {
     let _uuid: Uuid = descriptor.uuid();
     let _uuid: Result<Uuid> = assert_send(descriptor.uuid_async()).await;
+    let _handle: Result<u16> = descriptor.handle();
+    let _characteristic: Result<Characteristic> = assert_send(descriptor.characteristic()).await;
 
     let _value: Result<Vec<u8>> = assert_send(descriptor.value()).await;
     let _value: Result<Vec<u8>> = assert_send(descriptor.read()).await;
+    let _value: Result<Vec<u8>> = assert_send(descriptor.read_at(0)).await;
+    let _value: Result<Vec<u8>> = assert_send(descriptor.read_with(CacheMode::Cached)).await;
     let _res: Result<()> = assert_send(descriptor.write(&[0u8])).await;
 
     Ok(())
 }
 
+#[cfg(feature = "derive")]
+async fn check_derive_apis(device: Device) -> Result<()> {
+    #[derive(GattProfile)]
+    #[service(uuid = "180f")]
+    struct Battery {
+        #[service]
+        service: Service,
+        #[characteristic(uuid = btuuid::characteristics::BATTERY_LEVEL, notify)]
+        level: codec::TypedCharacteristic<u8>,
+        #[characteristic(uuid = "00002a1a-0000-1000-8000-00805f9b34fb", optional)]
+        power_state: Option<Characteristic>,
+    }
+
+    let battery: Battery = assert_send(Battery::connect(&device)).await?;
+    let _service: Service = battery.service;
+    let _level: Result<u8> = assert_send(battery.level.read_typed()).await;
+    let _power_state: Option<Characteristic> = battery.power_state;
+
+    Ok(())
+}
+
+#[cfg(feature = "snapshot")]
+async fn check_snapshot_apis(device: Device) -> Result<()> {
+    let store = gatt_snapshot::GattSnapshotStore::new("gatt-snapshots");
+    let _database: Result<gatt_database::GattDatabase> = assert_send(store.gatt_database(&device)).await;
+    let snapshot: Option<gatt_snapshot::GattSnapshot> = store.load(&device.id())?;
+    store.store(&snapshot.unwrap())?;
+    store.remove(&device.id())?;
+
+    Ok(())
+}
+
+#[cfg(feature = "testing")]
+async fn check_testing_apis(device: Device) -> Result<()> {
+    let scripted = pairing::testing::ScriptedPairingAgent::new(pairing::IoCapability::KeyboardDisplay);
+    let model = pairing::testing::AssociationModel::PasskeyInput;
+    let _res: std::result::Result<(), pairing::PairingRejected> = assert_send(pairing::testing::simulate_pairing(
+        &scripted,
+        &device,
+        model,
+        pairing::Passkey::new(0),
+    ))
+    .await;
+    let _calls: Vec<pairing::testing::AgentCall> = scripted.calls();
+    let _expected: Vec<pairing::testing::AgentCall> =
+        model.expected_calls(pairing::IoCapability::KeyboardDisplay, pairing::Passkey::new(0));
+    let _mismatches: Vec<pairing::testing::Mismatch> =
+        assert_send(pairing::testing::check_association_models(&device)).await;
+
+    Ok(())
+}
+
 #[allow(unused)]
 async fn check_apis() -> Result<()> {
     let adapter: Option<Adapter> = assert_send(Adapter::default()).await;
     let device = check_adapter_apis(adapter.unwrap()).await?;
-    let service = check_device_apis(device).await?;
+    let service = check_device_apis(device.clone()).await?;
     let characteristic = check_service_apis(service).await?;
+    check_reliable_write_apis(device.clone(), &characteristic).await?;
+    check_codec_apis(&characteristic).await?;
+    check_descriptors_apis(&characteristic).await?;
     let descriptor = check_characteristic_apis(characteristic).await?;
     check_descriptor_apis(descriptor).await?;
+    #[cfg(feature = "derive")]
+    check_derive_apis(device.clone()).await?;
+    #[cfg(feature = "snapshot")]
+    check_snapshot_apis(device.clone()).await?;
+    #[cfg(feature = "testing")]
+    check_testing_apis(device.clone()).await?;
 
     Ok(())
 }
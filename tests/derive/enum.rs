@@ -0,0 +1,9 @@
+use bluest::GattProfile;
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+enum Battery {
+    Level,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+struct Battery {
+    #[characteristic(uuid = "2a1")]
+    level: Characteristic,
+}
+
+fn main() {}
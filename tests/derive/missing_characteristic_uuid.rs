@@ -0,0 +1,10 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+struct Battery {
+    #[characteristic(notify)]
+    level: Characteristic,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+struct Battery {
+    #[characteristic(uuid = "2a19")]
+    level: Characteristic,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+#[service()]
+struct Battery {
+    #[characteristic(uuid = "2a19")]
+    level: Characteristic,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bluest::{GattProfile, Service};
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+struct Battery {
+    #[service(uuid = "180f")]
+    service: Service,
+}
+
+fn main() {}
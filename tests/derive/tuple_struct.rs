@@ -0,0 +1,7 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+struct Battery(Characteristic);
+
+fn main() {}
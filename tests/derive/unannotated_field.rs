@@ -0,0 +1,9 @@
+use bluest::{Characteristic, GattProfile};
+
+#[derive(GattProfile)]
+#[service(uuid = "180f")]
+struct Battery {
+    level: Characteristic,
+}
+
+fn main() {}